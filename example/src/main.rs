@@ -109,9 +109,12 @@ impl Application for StressApp {
     }
 
     fn on_draw(&mut self, mw: &mut MoonWalk) {
+        let objects = mw.objects_mut();
+
         for b in &self.bouncers {
-            mw.set_position(b.id, b.pos);
-            mw.set_rotation(b.id, b.angle);
+            let index = b.id.index();
+            objects.positions[index] = b.pos;
+            objects.rotations[index] = b.angle;
         }
     }
 
@@ -0,0 +1,698 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::f32::consts::TAU;
+
+use glam::{Vec2, Vec4};
+use rustc_hash::FxHashMap;
+
+use crate::easy_gpu::{Buffer, Context, Pipeline, RenderPass, Texture};
+use crate::rendering::vertex::{PathVertex, QuadVertex, RectInstance};
+use crate::rendering::pipeline::{
+    ShaderStore, CLIP_INTERSECT, CLIP_PATH_INTERSECT, CLIP_PATH_WRITE, CLIP_WRITE, MASK_COMPOSITE,
+    CLIP_SHADER, MASK_SHADER, PATH_SHADER,
+};
+use crate::rendering::texture::TextureStore;
+use crate::objects::store::ObjectStore;
+use crate::objects::{ClipRegion, MaskMode, ObjectMask, ObjectType, TextureId};
+use crate::batching::culling;
+use crate::batching::shapes::path::world_vertices;
+use crate::batching::shapes::rect::rect_instance;
+use crate::batching::submiter::{RenderTarget, Resources};
+use crate::error::MoonWalkError;
+
+/// Цепочка обрезки объекта без обрезки
+pub const NO_CLIP: u32 = 0;
+
+/// Самое большое значение трафарета (Он восьмибитный)
+const MAX_STENCIL: u32 = 255;
+
+/// Фигура для трафарета: инстанс прямоугольника или диапазон
+/// индексов треугольников пути
+#[derive(Debug, Clone, Copy)]
+enum ClipShape {
+    Rect(u32),
+    Path { first: u32, count: u32 },
+}
+
+/// Цепочка вложенных областей обрезки и масок: своя область
+/// контейнера и все области его обрезанных предков. Объекты с одной
+/// цепочкой обрезаются одинаково
+#[derive(Debug, Clone, Copy)]
+struct ClipChain {
+    container: usize,
+    parent: u32,
+
+    // Цепочка, которая рисуется в свой слой (Группа с альфа маской).
+    // NO_CLIP если цепочка рисуется прямо в проход
+    layer: u32,
+
+    // Пересечение рамок всех областей цепочки в логических пикселях.
+    // Прямоугольные области обрезаются только им (scissor)
+    min: Vec2,
+    max: Vec2,
+
+    // Фигуры цепочки для трафарета (Скруглённые или повёрнутые
+    // области и маски) в shapes, от внешней к внутренней
+    first: u32,
+    count: u32,
+}
+
+impl ClipChain {
+    const ROOT: Self = Self {
+        container: usize::MAX,
+        parent: NO_CLIP,
+        layer: NO_CLIP,
+        min: Vec2::NEG_INFINITY,
+        max: Vec2::INFINITY,
+        first: 0,
+        count: 0,
+    };
+}
+
+/// Слой группы с альфа маской. Содержимое группы рисуется в него до
+/// основного прохода, а в общем порядке вместо содержимого рисуется
+/// слой, умноженный на альфу текстуры маски
+#[derive(Debug, Clone, Copy)]
+struct Layer {
+    chain: u32,
+    mask: TextureId,
+    // Инстанс квада маски в rects
+    instance: u32,
+}
+
+/// Конвейеры фигур. Без трафарета в проходе их нет
+struct ShapePipelines<'a> {
+    write: &'a Pipeline,
+    intersect: &'a Pipeline,
+    path_write: &'a Pipeline,
+    path_intersect: &'a Pipeline,
+}
+
+/// Состояние обрезки внутри одного прохода
+pub struct ClipCursor {
+    active: u32,
+    // Следующее свободное значение трафарета
+    next: u32,
+}
+
+impl ClipCursor {
+    pub fn new() -> Self {
+        Self {
+            active: NO_CLIP,
+            next: 1,
+        }
+    }
+}
+
+/// Обрезка и маски потомков контейнеров (MoonWalk::set_clip и
+/// MoonWalk::set_mask). Каждый объект получает цепочку ближайшего
+/// обрезанного предка, и общий порядок отрисовки режется на отрезки
+/// там, где цепочка меняется.
+///
+/// Перед отрезком выставляется scissor по пересечению рамок всех
+/// областей цепочки. Если в цепочке есть фигуры (Скруглённые или
+/// повёрнутые области и маски трафаретом), они рисуются в трафарет:
+/// первая записывает новое значение (Mask::write), каждая следующая
+/// увеличивает его внутри предыдущей (Mask::increment_equal).
+/// Содержимое рисуется с опорным значением последней фигуры и
+/// проходит тест только в пересечении всех фигур. Значения только
+/// растут в течение кадра, поэтому старые фигуры трафарет не
+/// сбрасывают
+pub struct Clipper {
+    clip_of: Vec<u32>,
+    chains: Vec<ClipChain>,
+
+    // Фигуры всех цепочек. Первая это квад на весь экран для сброса
+    // трафарета, когда значения закончились
+    shapes: Vec<ClipShape>,
+    rects: Vec<RectInstance>,
+    path_vertices: Vec<PathVertex>,
+    path_indices: Vec<u32>,
+
+    rect_buffer: Option<Buffer<RectInstance>>,
+    path_vbo: Option<Buffer<PathVertex>>,
+    path_ibo: Option<Buffer<u32>>,
+    static_vbo: Buffer<QuadVertex>,
+    static_ibo: Buffer<u32>,
+
+    // Слои групп с альфа маской и текстуры под них. Текстуры
+    // переиспользуются между кадрами, пока совпадает размер
+    layers: Vec<Layer>,
+    targets: Vec<(Texture, wgpu::BindGroup)>,
+
+    stencil: bool,
+    viewport: Vec2,
+    viewport_dirty: bool,
+}
+
+impl Clipper {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            clip_of: Vec::new(),
+            chains: vec![ClipChain::ROOT],
+            shapes: Vec::new(),
+            rects: Vec::new(),
+            path_vertices: Vec::new(),
+            path_indices: Vec::new(),
+            rect_buffer: None,
+            path_vbo: None,
+            path_ibo: None,
+            static_vbo: Buffer::vertex(ctx, &QuadVertex::QUAD),
+            static_ibo: Buffer::<u32>::index(ctx, &QuadVertex::INDICES),
+            layers: Vec::new(),
+            targets: Vec::new(),
+            stencil: false,
+            viewport: Vec2::ONE,
+            viewport_dirty: true,
+        }
+    }
+
+    /// Собирает конвейер слоёв и конвейеры фигур (Только если в
+    /// проходе есть трафарет). Ошибка в одном шейдере не мешает
+    /// собрать другой, возвращается первая ошибка
+    pub fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        let composite = shaders.create_mask_composite(ctx, target, &textures.layout);
+
+        if target.stencil {
+            return composite.and(shaders.create_clip_shapes(ctx, target));
+        }
+
+        composite
+    }
+
+    /// Встроенные шейдеры, из которых собраны конвейеры обрезки
+    pub fn shader_files(&self) -> &[&'static str] {
+        &[MASK_SHADER.name, CLIP_SHADER.name, PATH_SHADER.name]
+    }
+
+    /// Логический размер экрана (Как у проекции)
+    pub fn set_viewport(&mut self, size: Vec2) {
+        if self.viewport != size {
+            self.viewport = size;
+            self.viewport_dirty = true;
+        }
+    }
+
+    /// Нужен ли проходу трафарет (Есть цепочки с фигурами)
+    pub fn needs_stencil(&self) -> bool {
+        self.stencil
+    }
+
+    /// Цепочка обрезки каждого объекта (NO_CLIP если обрезки нет)
+    pub fn clips(&self) -> &[u32] {
+        &self.clip_of
+    }
+
+    #[inline(always)]
+    pub fn is_clipped(&self, index: usize) -> bool {
+        self.clip_of.get(index).is_some_and(|&clip| clip != NO_CLIP)
+    }
+
+    /// Слой, в который рисуется цепочка (NO_CLIP если прямо в проход)
+    #[inline(always)]
+    pub fn layer_of(&self, clip: u32) -> u32 {
+        self.chains.get(clip as usize).map_or(NO_CLIP, |chain| chain.layer)
+    }
+
+    /// Слои кадра: цепочка слоя и текстура, в которую он рисуется
+    pub fn layers(&self) -> impl Iterator<Item = (u32, &wgpu::TextureView)> {
+        self.layers.iter()
+            .zip(self.targets.iter())
+            .map(|(layer, (texture, _))| (layer.chain, &texture.view))
+    }
+
+    /// Раздаёт объектам цепочки (При изменении структуры) и
+    /// пересчитывает области по мировым трансформациям контейнеров
+    pub fn update(&mut self, ctx: &Context, store: &ObjectStore, textures: &TextureStore) {
+        let reassign = store.dirty || self.clip_of.len() != store.positions.len();
+
+        if reassign {
+            self.assign(store);
+        }
+
+        if reassign || self.viewport_dirty || store.has_changes() {
+            self.viewport_dirty = false;
+            self.layout(ctx, store);
+        }
+
+        self.resize_targets(ctx, textures);
+    }
+
+    fn assign(&mut self, store: &ObjectStore) {
+        self.chains.truncate(1);
+        self.clip_of.clear();
+        self.clip_of.resize(store.positions.len(), NO_CLIP);
+
+        if store.clips.is_empty() && store.masks.is_empty() {
+            return;
+        }
+
+        let mut chain_of: FxHashMap<usize, u32> = FxHashMap::default();
+
+        for index in 0..store.positions.len() {
+            self.clip_of[index] = self.enclosing(store, index, &mut chain_of);
+        }
+    }
+
+    /// Цепочка ближайшего предка с обрезкой или маской (Сам объект
+    /// не обрезается своей областью)
+    fn enclosing(&mut self, store: &ObjectStore, index: usize, chain_of: &mut FxHashMap<usize, u32>) -> u32 {
+        let mut current = store.parents[index];
+
+        while let Some(parent) = current {
+            let parent = parent.index();
+
+            if store.clips.contains_key(&parent) || store.masks.contains_key(&parent) {
+                return self.chain(store, parent, chain_of);
+            }
+
+            current = store.parents[parent];
+        }
+
+        NO_CLIP
+    }
+
+    fn chain(&mut self, store: &ObjectStore, container: usize, chain_of: &mut FxHashMap<usize, u32>) -> u32 {
+        if let Some(&chain) = chain_of.get(&container) {
+            return chain;
+        }
+
+        // Цепочка родителя всегда создаётся раньше, поэтому при
+        // раскладке она уже посчитана
+        let parent = self.enclosing(store, container, chain_of);
+        let chain = self.chains.len() as u32;
+
+        self.chains.push(ClipChain { container, parent, ..ClipChain::ROOT });
+        chain_of.insert(container, chain);
+
+        chain
+    }
+
+    fn layout(&mut self, ctx: &Context, store: &ObjectStore) {
+        self.shapes.clear();
+        self.rects.clear();
+        self.path_vertices.clear();
+        self.path_indices.clear();
+        self.layers.clear();
+        self.stencil = false;
+
+        // Буферы остаются на следующие кадры, без цепочек их просто
+        // никто не рисует
+        if self.chains.len() == 1 {
+            return;
+        }
+
+        self.shapes.push(ClipShape::Rect(0));
+        self.rects.push(RectInstance {
+            pos_size: [0.0, 0.0, self.viewport.x, self.viewport.y],
+            radii: [0.0; 4],
+            extra: [0.0, 0.0],
+            color: 0,
+            transform: [1.0, 1.0, 0.0, 0.0],
+        });
+
+        for index in 1..self.chains.len() {
+            let chain = self.chains[index];
+            let parent = self.chains[chain.parent as usize];
+
+            let mut min = parent.min;
+            let mut max = parent.max;
+            let mut layer = parent.layer;
+
+            // Фигуры предков идут первыми
+            let first = self.shapes.len();
+            let range = parent.first as usize..(parent.first + parent.count) as usize;
+            self.shapes.extend_from_within(range);
+
+            if let Some(&region) = store.clips.get(&chain.container) {
+                let (shape, region_min, region_max) = region_shape(store, chain.container, region);
+
+                min = min.max(region_min);
+                max = max.min(region_max);
+
+                if let Some(shape) = shape {
+                    self.push_rect(shape);
+                }
+            }
+
+            if let Some(&mask) = store.masks.get(&chain.container) {
+                let (mask_min, mask_max) = culling::world_bounds(store, mask.object.index());
+
+                min = min.max(mask_min);
+                max = max.min(mask_max);
+
+                // Слои не вкладываются друг в друга: альфа маска
+                // внутри группы со слоем работает как трафарет
+                if mask.mode == MaskMode::Alpha && layer == NO_CLIP && is_image(mask) {
+                    layer = index as u32;
+                    self.push_layer(store, layer, chain.container, mask);
+                } else {
+                    self.push_mask(store, mask);
+                }
+            }
+
+            let count = (self.shapes.len() - first) as u32;
+            self.stencil |= count > 0;
+
+            self.chains[index] = ClipChain {
+                layer,
+                min,
+                max,
+                first: first as u32,
+                count,
+                ..chain
+            };
+        }
+
+        // Фигуры меняются при любой анимации, поэтому буферы
+        // переиспользуются (Растут и ужимаются в Buffer::update)
+        match &mut self.rect_buffer {
+            Some(rects) => rects.update(ctx, &self.rects),
+            None => self.rect_buffer = Some(Buffer::vertex(ctx, &self.rects)),
+        }
+
+        if self.path_indices.is_empty() {
+            return;
+        }
+
+        match &mut self.path_vbo {
+            Some(vbo) => vbo.update(ctx, &self.path_vertices),
+            None => self.path_vbo = Some(Buffer::vertex(ctx, &self.path_vertices)),
+        }
+
+        match &mut self.path_ibo {
+            Some(ibo) => ibo.update(ctx, &self.path_indices),
+            None => self.path_ibo = Some(Buffer::<u32>::index(ctx, &self.path_indices)),
+        }
+    }
+
+    fn push_rect(&mut self, instance: RectInstance) {
+        self.shapes.push(ClipShape::Rect(self.rects.len() as u32));
+        self.rects.push(instance);
+    }
+
+    /// Фигура маски трафаретом. У изображения берётся его
+    /// прямоугольник (Альфа текстуры есть только в режиме Alpha)
+    fn push_mask(&mut self, store: &ObjectStore, mask: ObjectMask) {
+        let index = mask.object.index();
+
+        match (mask.object.get_type(), store.path_meshes.get(&index)) {
+            (Some(ObjectType::Path), Some(mesh)) => {
+                let base = self.path_vertices.len() as u32;
+                let first = self.path_indices.len() as u32;
+
+                self.path_vertices.extend(world_vertices(store, index, mesh));
+                self.path_indices.extend(mesh.indices.iter().map(|i| i + base));
+
+                self.shapes.push(ClipShape::Path { first, count: mesh.indices.len() as u32 });
+            },
+            (Some(ObjectType::Rect | ObjectType::Image), _) => self.push_rect(rect_instance(store, index)),
+            _ => {},
+        }
+    }
+
+    /// Слой рисуется квадом изображения маски на z группы
+    fn push_layer(&mut self, store: &ObjectStore, chain: u32, container: usize, mask: ObjectMask) {
+        let index = mask.object.index();
+        let mut instance = rect_instance(store, index);
+        instance.extra[0] = store.z_indices[container];
+
+        self.layers.push(Layer {
+            chain,
+            mask: store.image_textures[index],
+            instance: self.rects.len() as u32,
+        });
+        self.rects.push(instance);
+    }
+
+    /// Текстуры слоёв размером с поверхность и её формата
+    fn resize_targets(&mut self, ctx: &Context, textures: &TextureStore) {
+        let (width, height) = (ctx.config.width, ctx.config.height);
+
+        self.targets.retain(|(texture, _)| {
+            texture.width == width && texture.height == height && texture.format == ctx.config.format
+        });
+
+        while self.targets.len() < self.layers.len() {
+            let texture = Texture::create_render_target(ctx, width, height, ctx.config.format);
+            let group = textures.create_target_group(ctx, &texture);
+
+            self.targets.push((texture, group));
+        }
+    }
+
+    fn shape_pipelines<'a>(&self, res: Resources<'a>) -> Option<ShapePipelines<'a>> {
+        if !res.stencil {
+            return None;
+        }
+
+        Some(ShapePipelines {
+            write: res.shaders.get_pipeline(CLIP_WRITE)?,
+            intersect: res.shaders.get_pipeline(CLIP_INTERSECT)?,
+            path_write: res.shaders.get_pipeline(CLIP_PATH_WRITE)?,
+            path_intersect: res.shaders.get_pipeline(CLIP_PATH_INTERSECT)?,
+        })
+    }
+
+    /// Выставляет в проходе обрезку цепочки clip, если она ещё не
+    /// выставлена. surface это размер цели прохода в пикселях
+    pub fn apply<'a>(
+        &'a self,
+        pass: &mut RenderPass<'a>,
+        res: Resources<'a>,
+        cursor: &mut ClipCursor,
+        clip: u32,
+        surface: (u32, u32),
+    ) {
+        if cursor.active == clip {
+            return;
+        }
+
+        cursor.active = clip;
+
+        let chain = match self.chains.get(clip as usize) {
+            Some(chain) if clip != NO_CLIP => *chain,
+            _ => {
+                pass.set_scissor(0, 0, surface.0, surface.1);
+                pass.set_stencil_reference(0);
+                return;
+            },
+        };
+
+        let (x, y, w, h) = self.scissor(chain.min, chain.max, surface);
+
+        let pipelines = match self.shape_pipelines(res) {
+            Some(pipelines) if chain.count > 0 => pipelines,
+            _ => {
+                pass.set_scissor(x, y, w, h);
+                pass.set_stencil_reference(0);
+                return;
+            },
+        };
+
+        // Значения закончились: сбрасываем трафарет во всём кадре
+        if cursor.next + chain.count > MAX_STENCIL + 1 {
+            pass.set_scissor(0, 0, surface.0, surface.1);
+            pass.set_stencil_reference(0);
+            self.draw_shape(pass, pipelines.write, pipelines.path_write, ClipShape::Rect(0));
+
+            cursor.next = 1;
+        }
+
+        pass.set_scissor(x, y, w, h);
+
+        // Внешняя фигура записывает value, а i-я вложенная превращает
+        // value + i - 1 в value + i
+        let value = cursor.next;
+        let shapes = &self.shapes[chain.first as usize..(chain.first + chain.count) as usize];
+
+        for (i, &shape) in shapes.iter().enumerate() {
+            let i = i as u32;
+
+            if i == 0 {
+                pass.set_stencil_reference(value);
+                self.draw_shape(pass, pipelines.write, pipelines.path_write, shape);
+            } else {
+                pass.set_stencil_reference(value + i - 1);
+                self.draw_shape(pass, pipelines.intersect, pipelines.path_intersect, shape);
+            }
+        }
+
+        pass.set_stencil_reference(value + chain.count - 1);
+        cursor.next = value + chain.count;
+    }
+
+    fn draw_shape<'a>(&'a self, pass: &mut RenderPass<'a>, rect: &'a Pipeline, path: &'a Pipeline, shape: ClipShape) {
+        match shape {
+            ClipShape::Rect(slot) => {
+                if let Some(rects) = &self.rect_buffer {
+                    pass.set_pipeline(rect);
+                    pass.set_vertex_buffer(0, &self.static_vbo);
+                    pass.set_vertex_buffer(1, rects);
+                    pass.set_index_buffer(&self.static_ibo);
+                    pass.draw_indexed_instanced_range(6, slot, 1);
+                }
+            },
+            ClipShape::Path { first, count } => {
+                if let (Some(vbo), Some(ibo)) = (&self.path_vbo, &self.path_ibo) {
+                    pass.set_pipeline(path);
+                    pass.set_vertex_buffer(0, vbo);
+                    pass.set_index_buffer(ibo);
+                    pass.draw_indexed_range(first, count);
+                }
+            },
+        }
+    }
+
+    /// Рисует слой цепочки layer в проход, обрезанный внешними
+    /// областями группы
+    pub fn composite<'a>(
+        &'a self,
+        pass: &mut RenderPass<'a>,
+        res: Resources<'a>,
+        cursor: &mut ClipCursor,
+        layer: u32,
+        surface: (u32, u32),
+    ) {
+        let found = self.layers.iter()
+            .zip(self.targets.iter())
+            .find(|(entry, _)| entry.chain == layer);
+
+        let (entry, (_, layer_group)) = match found {
+            Some(found) => found,
+            None => return,
+        };
+
+        let parent = self.chains[layer as usize].parent;
+        self.apply(pass, res, cursor, parent, surface);
+
+        let pipeline = res.shaders.get_pipeline(MASK_COMPOSITE);
+        let mask_group = res.textures.get_group(entry.mask);
+
+        if let (Some(pipeline), Some(mask_group), Some(rects)) = (pipeline, mask_group, &self.rect_buffer) {
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(1, mask_group);
+            pass.set_bind_group(2, layer_group);
+            pass.set_vertex_buffer(0, &self.static_vbo);
+            pass.set_vertex_buffer(1, rects);
+            pass.set_index_buffer(&self.static_ibo);
+            pass.draw_indexed_instanced_range(6, entry.instance, 1);
+        }
+    }
+
+    /// Рамка в логических пикселях -> scissor в пикселях цели
+    fn scissor(&self, min: Vec2, max: Vec2, surface: (u32, u32)) -> (u32, u32, u32, u32) {
+        let size = Vec2::new(surface.0 as f32, surface.1 as f32);
+        let scale = size / self.viewport.max(Vec2::ONE);
+
+        let min = (min * scale).floor().clamp(Vec2::ZERO, size);
+        let max = (max * scale).ceil().clamp(Vec2::ZERO, size).max(min);
+
+        (min.x as u32, min.y as u32, (max.x - min.x) as u32, (max.y - min.y) as u32)
+    }
+}
+
+#[inline]
+fn is_image(mask: ObjectMask) -> bool {
+    mask.object.get_type() == Some(ObjectType::Image)
+}
+
+/// Фигура области в мире (Если её нельзя обрезать одним scissor) и
+/// её мировая рамка. Фигура трансформируется так же как контейнер:
+/// вращение и масштаб вокруг его точки опоры
+fn region_shape(store: &ObjectStore, container: usize, region: ClipRegion) -> (Option<RectInstance>, Vec2, Vec2) {
+    let offset = Vec2::new(region.rect.x, region.rect.y);
+    let size = Vec2::new(region.rect.z, region.rect.w);
+
+    let position = store.world_positions()[container];
+    let rotation = store.world_rotations()[container];
+    let scale = store.world_scales()[container];
+    let pivot = store.sizes[container] * store.pivots[container];
+
+    // Пустая область обрезает всё
+    if size.x <= 0.0 || size.y <= 0.0 {
+        return (None, position, position);
+    }
+
+    let turn = Vec2::from_angle(rotation);
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+
+    for corner in [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE] {
+        let local = (offset + corner * size - pivot) * scale;
+        let world = turn.rotate(local) + pivot + position;
+
+        min = min.min(world);
+        max = max.max(world);
+    }
+
+    if rotation.rem_euclid(TAU) == 0.0 && region.radii == Vec4::ZERO {
+        return (None, min, max);
+    }
+
+    let origin = position + offset;
+    let shape_pivot = (pivot - offset) / size;
+
+    let shape = RectInstance {
+        pos_size: [origin.x, origin.y, size.x, size.y],
+        radii: region.radii.to_array(),
+        extra: [0.0, rotation],
+        color: 0,
+        transform: [scale.x, scale.y, shape_pivot.x, shape_pivot.y],
+    };
+
+    (Some(shape), min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Контейнер 100x50 в (100, 100), отражённый по x вокруг центра
+    fn mirrored_container() -> (ObjectStore, usize) {
+        let mut store = ObjectStore::new();
+        let id = store.new_rect();
+
+        store.config_position(id, Vec2::new(100.0, 100.0));
+        store.config_size(id, Vec2::new(100.0, 50.0));
+        store.config_scale(id, Vec2::new(-1.0, 1.0));
+
+        (store, id.index())
+    }
+
+    #[test]
+    fn mirrored_region_bounds_flip_around_pivot() {
+        let (store, container) = mirrored_container();
+        let region = ClipRegion { rect: Vec4::new(0.0, 0.0, 50.0, 50.0), radii: Vec4::ZERO };
+
+        // Левая половина контейнера после отражения оказывается справа
+        let (shape, min, max) = region_shape(&store, container, region);
+
+        assert!(shape.is_none());
+        assert_eq!(min, Vec2::new(150.0, 100.0));
+        assert_eq!(max, Vec2::new(200.0, 150.0));
+    }
+
+    #[test]
+    fn mirrored_rounded_region_keeps_negative_scale() {
+        let (store, container) = mirrored_container();
+        let region = ClipRegion { rect: Vec4::new(0.0, 0.0, 50.0, 50.0), radii: Vec4::splat(10.0) };
+
+        let (shape, min, max) = region_shape(&store, container, region);
+        let shape = shape.expect("rounded region needs a stencil shape");
+
+        // Точка опоры фигуры совпадает с точкой опоры контейнера, а
+        // отрицательный масштаб разворачивает квад задней стороной
+        // (Трафарет для неё настроен так же, смотрите Mask)
+        assert_eq!(shape.pos_size, [100.0, 100.0, 50.0, 50.0]);
+        assert_eq!(shape.transform, [-1.0, 1.0, 1.0, 0.5]);
+        assert_eq!((min, max), (Vec2::new(150.0, 100.0), Vec2::new(200.0, 150.0)));
+    }
+}
\ No newline at end of file
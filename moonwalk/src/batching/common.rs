@@ -1,54 +1,85 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
-use bytemuck::{Pod, Zeroable};
+use std::cmp::Ordering;
+use std::ops::Range;
+use bytemuck::Pod;
 
 use crate::easy_gpu::{Buffer, Context};
+use crate::objects::ObjectId;
 
-/// Трейт, который должна реализовать любая структура инстанса
-/// чтобы её можно было сортировать
-pub trait SortableInstance: Pod + Zeroable {
-    fn get_z_index(&self) -> f32;
-}
+/// Объект не попал в батч (Другого типа или скрыт)
+pub const NO_SLOT: u32 = u32::MAX;
 
 /// Контейнер для батчинга
-pub struct BatchBuffer<T: SortableInstance> {
+pub struct BatchBuffer<T: Pod> {
     pub cpu_buffer: Vec<T>,
     pub gpu_buffer: Option<Buffer<T>>,
+
+    // Место инстанса в буфере для каждого объекта (По глобальному
+    // индексу), нужно чтобы переписать только изменённые инстансы
+    pub slots: Vec<u32>,
+
+    // Обратное отображение: глобальный индекс объекта для каждого
+    // места в буфере (Порядок отрисовки батча)
+    pub objects: Vec<usize>,
+
+    // Места инстансов изменённых после последней заливки
+    pending: Vec<u32>,
 }
 
-impl<T: SortableInstance> BatchBuffer<T> {
+impl<T: Pod> BatchBuffer<T> {
     pub fn new() -> Self {
         Self {
             cpu_buffer: Vec::with_capacity(1024),
             gpu_buffer: None,
+            slots: Vec::new(),
+            objects: Vec::new(),
+            pending: Vec::new(),
         }
     }
 
-    /// Эта функция нужна чтобы очистить cpu буфер перед новым кадром.
-    pub fn clear(&mut self) {
+    /// Эта функция нужна чтобы очистить cpu буфер перед полной
+    /// пересборкой. Принимает общее количество объектов в хранилище
+    pub fn clear(&mut self, objects: usize) {
         self.cpu_buffer.clear();
+        self.pending.clear();
+        self.objects.clear();
+        self.slots.clear();
+        self.slots.resize(objects, NO_SLOT);
     }
 
-    /// Эта функция добавляет элемент в буфер
-    #[inline]
-    pub fn push(&mut self, instance: T) {
-        self.cpu_buffer.push(instance);
+    /// Добавляет в конец буфера готовые инстансы объектов (Например
+    /// упакованные параллельно). objects и instances идут парами
+    pub fn extend(&mut self, objects: &[usize], instances: Vec<T>) {
+        for (slot, &index) in objects.iter().enumerate() {
+            self.slots[index] = (self.cpu_buffer.len() + slot) as u32;
+        }
+
+        self.objects.extend_from_slice(objects);
+        self.cpu_buffer.extend(instances);
     }
 
-    /// Отсортировать объекты по z индексу
-    pub fn sort(&mut self) {
-        // Здесь используется unstable сортировка так как она просто
-        // быстрее и не требует дополнительной памяти. Возможна нестабильность, но
-        // тесты показали жизнеспособность этого метода сортировки
-        self.cpu_buffer.sort_unstable_by(|a, b| {
-            a.get_z_index().total_cmp(&b.get_z_index())
-        });
+    /// Заменяет инстанс объекта на его месте. Возвращает false если
+    /// объекта нет в батче
+    #[inline]
+    pub fn replace(&mut self, index: usize, instance: T) -> bool {
+        let slot = match self.slots.get(index) {
+            Some(&slot) if slot != NO_SLOT => slot,
+            _ => return false,
+        };
+
+        self.cpu_buffer[slot as usize] = instance;
+        self.pending.push(slot);
+
+        true
     }
 
     // Заливаем процессорный буфер на видеокарту создавая вершинные буферы. Функция
     // вернёт true если в буфере есть данные для создания буферов gpu
     pub fn upload(&mut self, ctx: &Context) -> bool {
+        self.pending.clear();
+
         if self.cpu_buffer.is_empty() {
             return false;
         }
@@ -61,4 +92,236 @@ impl<T: SortableInstance> BatchBuffer<T> {
 
         true
     }
+
+    /// Переписывает инстансы изменённых объектов на их местах. opaque
+    /// это количество непрозрачных мест в начале буфера (Только в
+    /// режиме глубины). Возвращает false если объект стал непрозрачным
+    /// или наоборот, тогда он переезжает в другую часть буфера и батч
+    /// нужно пересобрать целиком
+    pub fn replace_changed(
+        &mut self,
+        changed: &[usize],
+        opaque: Option<u32>,
+        is_opaque: impl Fn(usize) -> bool,
+        instance: impl Fn(usize) -> T,
+    ) -> bool {
+        for &idx in changed.iter() {
+            let slot = self.slots.get(idx).copied().unwrap_or(NO_SLOT);
+
+            if let Some(opaque) = opaque {
+                if slot != NO_SLOT && (slot < opaque) != is_opaque(idx) {
+                    return false;
+                }
+            }
+
+            self.replace(idx, instance(idx));
+        }
+
+        true
+    }
+
+    /// Заливает только изменённые инстансы. Соседние места
+    /// склеиваются в один диапазон, чтобы не делать запись на
+    /// каждый инстанс
+    pub fn flush(&mut self, ctx: &Context) {
+        let buf = match &self.gpu_buffer {
+            Some(buf) => buf,
+            None => return,
+        };
+
+        let cpu_buffer = &self.cpu_buffer;
+        drain_ranges(&mut self.pending, |range| buf.write_at(ctx, range.start, &cpu_buffer[range]));
+    }
+}
+
+/// Сортирует места и отдаёт их склеенными диапазонами, после чего
+/// очищает список
+pub fn drain_ranges(pending: &mut Vec<u32>, mut write: impl FnMut(Range<usize>)) {
+    pending.sort_unstable();
+
+    let mut i = 0;
+    while i < pending.len() {
+        let start = pending[i] as usize;
+        let mut end = start + 1;
+        i += 1;
+
+        // Одно место могло измениться несколько раз
+        while i < pending.len() && pending[i] as usize <= end {
+            end = end.max(pending[i] as usize + 1);
+            i += 1;
+        }
+
+        write(start..end);
+    }
+
+    pending.clear();
+}
+
+/// Порядок отрисовки двух объектов: сначала по z индексу, а при
+/// равном z по порядку создания (Глобальный индекс растёт с каждым
+/// новым объектом). Так объекты с одинаковым z (Обычно весь UI
+/// на z = 0) не меняются местами между кадрами
+#[inline(always)]
+pub fn draw_cmp(z_indices: &[f32], a: usize, b: usize) -> Ordering {
+    z_indices[a].total_cmp(&z_indices[b]).then(a.cmp(&b))
+}
+
+/// Заново собирает порядок объектов и сортирует его по z индексу.
+/// Ключ сортировки уникален (z + индекс), поэтому unstable
+/// сортировка даёт тот же результат что и stable, но быстрее и
+/// без дополнительной памяти
+pub fn sort_by_z(order: &mut Vec<usize>, ids: &[ObjectId], z_indices: &[f32]) {
+    order.clear();
+    order.extend(ids.iter().map(|id| id.index()));
+    order.sort_unstable_by(|&a, &b| draw_cmp(z_indices, a, b));
+}
+
+/// Порядок непрозрачной части в режиме глубины: от ближних к
+/// дальним (По убыванию z), чтобы тест глубины отбрасывал закрытые
+/// пиксели до фрагментного шейдера. При равном z остаётся порядок
+/// создания, поэтому с GreaterEqual поверх рисуется объект созданный
+/// позже, как и при обычной сортировке
+pub fn sort_front_to_back(order: &mut Vec<usize>, ids: &[ObjectId], z_indices: &[f32]) {
+    order.clear();
+    order.extend(ids.iter().map(|id| id.index()));
+    order.sort_unstable_by(|&a, &b| z_indices[b].total_cmp(&z_indices[a]).then(a.cmp(&b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ObjectType;
+
+    fn rects(count: usize) -> Vec<ObjectId> {
+        (0..count).map(|i| ObjectId::new(ObjectType::Rect, i)).collect()
+    }
+
+    #[test]
+    fn equal_z_keeps_creation_order() {
+        let ids = rects(1000);
+        let z_indices = vec![0.0; ids.len()];
+        let mut order = Vec::new();
+
+        sort_by_z(&mut order, &ids, &z_indices);
+
+        assert_eq!(order, (0..ids.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn equal_z_order_is_stable_across_frames() {
+        let ids = rects(500);
+
+        // Три слоя с одинаковым z внутри каждого
+        let mut z_indices: Vec<f32> = (0..ids.len()).map(|i| (i % 3) as f32).collect();
+        let mut order = Vec::new();
+        sort_by_z(&mut order, &ids, &z_indices);
+        let first = order.clone();
+
+        for frame in 0..10 {
+            // Каждый кадр один объект меняет z и возвращается обратно,
+            // что вызывает пересортировку
+            let moved = frame * 37 % ids.len();
+            let old = z_indices[moved];
+
+            z_indices[moved] = 10.0;
+            sort_by_z(&mut order, &ids, &z_indices);
+            assert_eq!(order.last(), Some(&moved));
+
+            z_indices[moved] = old;
+            sort_by_z(&mut order, &ids, &z_indices);
+            assert_eq!(order, first);
+        }
+
+        // Внутри слоя порядок создания
+        for pair in first.windows(2) {
+            if z_indices[pair[0]] == z_indices[pair[1]] {
+                assert!(pair[0] < pair[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn opaque_order_is_front_to_back() {
+        let ids = rects(6);
+        let z_indices = [0.0, 5.0, -1.0, 5.0, 0.0, 2.0];
+        let mut order = Vec::new();
+
+        sort_front_to_back(&mut order, &ids, &z_indices);
+
+        // При равном z раньше созданный объект идёт первым
+        assert_eq!(order, vec![1, 3, 5, 0, 4, 2]);
+    }
+
+    /// Батч из count объектов, инстанс объекта это его индекс. Объекты
+    /// с индексами из hidden в батч не попадают
+    fn batch(count: usize, hidden: &[usize]) -> BatchBuffer<u32> {
+        let objects: Vec<usize> = (0..count).filter(|i| !hidden.contains(i)).collect();
+        let instances = objects.iter().map(|&i| i as u32).collect();
+
+        let mut batch = BatchBuffer::new();
+        batch.clear(count);
+        batch.extend(&objects, instances);
+        batch
+    }
+
+    fn ranges(batch: &mut BatchBuffer<u32>) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        drain_ranges(&mut batch.pending, |range| ranges.push(range));
+        ranges
+    }
+
+    #[test]
+    fn changed_instances_are_rewritten_in_place() {
+        let mut batch = batch(6, &[2]);
+
+        assert!(batch.replace_changed(&[4, 0], None, |_| true, |idx| idx as u32 + 100));
+
+        // Объект 2 скрыт, поэтому объект 4 лежит на месте 3
+        assert_eq!(batch.cpu_buffer, vec![100, 1, 3, 104, 5]);
+        assert_eq!(batch.objects, vec![0, 1, 3, 4, 5]);
+        assert_eq!(ranges(&mut batch), vec![0..1, 3..4]);
+    }
+
+    #[test]
+    fn adjacent_slots_are_coalesced() {
+        let mut batch = batch(10, &[]);
+
+        // Порядок изменений любой, одно место может меняться дважды
+        for idx in [7, 2, 1, 3, 6, 2, 9] {
+            assert!(batch.replace(idx, 0));
+        }
+
+        assert_eq!(ranges(&mut batch), vec![1..4, 6..8, 9..10]);
+        assert!(batch.pending.is_empty());
+        assert!(ranges(&mut batch).is_empty());
+    }
+
+    #[test]
+    fn objects_outside_the_batch_are_skipped() {
+        let mut batch = batch(4, &[1]);
+
+        // Скрытый объект и объект другого батча (Индекс за пределами)
+        assert!(!batch.replace(1, 0));
+        assert!(!batch.replace(10, 0));
+        assert!(batch.replace_changed(&[1, 10], Some(2), |_| false, |_| 0));
+
+        assert_eq!(batch.cpu_buffer, vec![0, 2, 3]);
+        assert!(ranges(&mut batch).is_empty());
+    }
+
+    #[test]
+    fn opaque_split_change_needs_rebuild() {
+        // Первые два места непрозрачные
+        let mut batch = batch(4, &[]);
+        let opaque = |idx: usize| idx < 2;
+
+        assert!(batch.replace_changed(&[0, 3], Some(2), opaque, |idx| idx as u32));
+
+        // Непрозрачный объект стал полупрозрачным и наоборот
+        assert!(!batch.replace_changed(&[1], Some(2), |_| false, |idx| idx as u32));
+        assert!(!batch.replace_changed(&[2], Some(2), |_| true, |idx| idx as u32));
+
+        // Без режима глубины частей нет
+        assert!(batch.replace_changed(&[1, 2], None, |_| true, |idx| idx as u32));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,468 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::Vec2;
+use rustc_hash::FxHashMap;
+
+use crate::objects::store::ObjectStore;
+
+/// Размер ячейки сетки в логических пикселях
+const CELL_SIZE: f32 = 256.0;
+
+/// Объекты которые накрывают больше ячеек не раскладываются по
+/// сетке, а проверяются всегда (Обычно это фоны во весь экран)
+const MAX_OBJECT_CELLS: i64 = 256;
+
+/// Ячейки которые накрывает объект (Включительно)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cells {
+    Range { min: (i32, i32), max: (i32, i32) },
+    Large,
+}
+
+/// Отсечение объектов за пределами экрана. Мировые рамки объектов
+/// (С учётом вращения, масштаба и точки опоры) раскладываются по
+/// равномерной сетке, которая обновляется только для изменённых
+/// объектов. При смене вьюпорта видимые объекты ищутся через сетку,
+/// а не перебором всех объектов
+pub struct Culler {
+    enabled: bool,
+    view_min: Vec2,
+    view_max: Vec2,
+    view_dirty: bool,
+
+    cells: FxHashMap<(i32, i32), Vec<usize>>,
+    large: Vec<usize>,
+    placement: Vec<Option<Cells>>,
+    bounds: Vec<(Vec2, Vec2)>,
+
+    in_view: Vec<bool>,
+
+    // Видимость до смены вьюпорта, чтобы понять изменилась ли она
+    previous_view: Vec<bool>,
+}
+
+impl Default for Culler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Culler {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            view_min: Vec2::ZERO,
+            view_max: Vec2::ZERO,
+            view_dirty: true,
+            cells: FxHashMap::default(),
+            large: Vec::new(),
+            placement: Vec::new(),
+            bounds: Vec::new(),
+            in_view: Vec::new(),
+            previous_view: Vec::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.view_dirty = true;
+        }
+    }
+
+    /// Прямоугольник экрана в мировых координатах
+    pub fn set_viewport(&mut self, min: Vec2, max: Vec2) {
+        if self.view_min != min || self.view_max != max {
+            self.view_min = min;
+            self.view_max = max;
+            self.view_dirty = true;
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn viewport(&self) -> (Vec2, Vec2) {
+        (self.view_min, self.view_max)
+    }
+
+    /// Нужно ли рисовать объект
+    #[inline(always)]
+    pub fn in_view(&self, index: usize) -> bool {
+        !self.enabled || self.in_view.get(index).copied().unwrap_or(true)
+    }
+
+    /// Обновляет сетку и видимость. Возвращает true если какой-то
+    /// объект зашёл на экран или ушёл с него (Тогда батчи нужно
+    /// пересобрать целиком)
+    pub fn update(&mut self, store: &ObjectStore) -> bool {
+        if !self.enabled {
+            // Сетку при выключенном отсечении не держим
+            let changed = self.view_dirty;
+            self.view_dirty = false;
+            self.clear();
+
+            return changed;
+        }
+
+        let count = store.positions.len();
+        let structure = store.dirty || self.placement.len() != count;
+
+        if structure {
+            self.clear();
+            self.placement.resize(count, None);
+            self.bounds.resize(count, (Vec2::ZERO, Vec2::ZERO));
+
+            for index in 0..count {
+                self.place(store, index);
+            }
+        } else {
+            for &index in store.changed.iter() {
+                self.place(store, index);
+            }
+        }
+
+        if structure || self.view_dirty {
+            self.view_dirty = false;
+            std::mem::swap(&mut self.in_view, &mut self.previous_view);
+            self.query_view();
+
+            // Вьюпорт мог сдвинуться так, что ни один объект не зашёл
+            // на экран и не ушёл с него
+            return structure || self.in_view != self.previous_view;
+        }
+
+        // Сетка нужна только для полного поиска, изменённые объекты
+        // проверяем напрямую
+        let mut changed = false;
+        for &index in store.changed.iter() {
+            let visible = self.intersects(index);
+
+            if self.in_view[index] != visible {
+                self.in_view[index] = visible;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    fn clear(&mut self) {
+        self.cells.clear();
+        self.large.clear();
+        self.placement.clear();
+        self.bounds.clear();
+        self.in_view.clear();
+    }
+
+    /// Ищет видимые объекты через ячейки, которые накрывает экран.
+    /// Если ячеек экрана больше чем объектов (Сильно отдалённая
+    /// камера), быстрее проверить все рамки подряд
+    fn query_view(&mut self) {
+        self.in_view.clear();
+        self.in_view.resize(self.placement.len(), false);
+
+        let (min, max) = cell_range(self.view_min, self.view_max);
+
+        if cell_area(min, max) > self.bounds.len() as i64 {
+            for index in 0..self.bounds.len() {
+                self.in_view[index] = self.intersects(index);
+            }
+
+            return;
+        }
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(objects) = self.cells.get(&(x, y)) {
+                    for &index in objects.iter() {
+                        self.in_view[index] = self.bounds[index].0.cmple(self.view_max).all()
+                            && self.bounds[index].1.cmpge(self.view_min).all();
+                    }
+                }
+            }
+        }
+
+        for i in 0..self.large.len() {
+            let index = self.large[i];
+            self.in_view[index] = self.intersects(index);
+        }
+    }
+
+    #[inline(always)]
+    fn intersects(&self, index: usize) -> bool {
+        let (min, max) = self.bounds[index];
+        min.cmple(self.view_max).all() && max.cmpge(self.view_min).all()
+    }
+
+    /// Пересчитывает рамку объекта и перекладывает его в сетке если
+    /// изменились накрытые ячейки
+    fn place(&mut self, store: &ObjectStore, index: usize) {
+        let (min, max) = world_bounds(store, index);
+        self.bounds[index] = (min, max);
+
+        let (cell_min, cell_max) = cell_range(min, max);
+        let cells = if cell_area(cell_min, cell_max) > MAX_OBJECT_CELLS {
+            Cells::Large
+        } else {
+            Cells::Range { min: cell_min, max: cell_max }
+        };
+
+        let old = self.placement[index];
+        if old == Some(cells) {
+            return;
+        }
+
+        if let Some(old) = old {
+            self.remove(index, old);
+        }
+
+        match cells {
+            Cells::Large => self.large.push(index),
+            Cells::Range { min, max } => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        self.cells.entry((x, y)).or_default().push(index);
+                    }
+                }
+            },
+        }
+
+        self.placement[index] = Some(cells);
+    }
+
+    fn remove(&mut self, index: usize, cells: Cells) {
+        match cells {
+            Cells::Large => self.large.retain(|&i| i != index),
+            Cells::Range { min, max } => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        if let Some(objects) = self.cells.get_mut(&(x, y)) {
+                            if let Some(position) = objects.iter().position(|&i| i == index) {
+                                objects.swap_remove(position);
+                            }
+
+                            if objects.is_empty() {
+                                self.cells.remove(&(x, y));
+                            }
+                        }
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Ячейки, которые накрывает прямоугольник
+fn cell_range(min: Vec2, max: Vec2) -> ((i32, i32), (i32, i32)) {
+    let min = (min / CELL_SIZE).floor();
+    let max = (max / CELL_SIZE).floor();
+
+    ((min.x as i32, min.y as i32), (max.x as i32, max.y as i32))
+}
+
+/// Количество ячеек в диапазоне. Стороны считаются в i64, чтобы
+/// огромные рамки не переполняли i32, а площадь насыщается
+fn cell_area(min: (i32, i32), max: (i32, i32)) -> i64 {
+    (max.0 as i64 - min.0 as i64 + 1).saturating_mul(max.1 as i64 - min.1 as i64 + 1)
+}
+
+/// Мировая рамка объекта (AABB). Углы считаются так же как в
+/// шейдере: масштаб и вращение вокруг точки опоры
+pub fn world_bounds(store: &ObjectStore, index: usize) -> (Vec2, Vec2) {
+    let position = store.world_positions()[index];
+    let rotation = Vec2::from_angle(store.world_rotations()[index]);
+    let scale = store.world_scales()[index];
+    let size = store.sizes[index];
+    let pivot = size * store.pivots[index];
+
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+
+    for corner in [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE] {
+        let local = (corner * size - pivot) * scale;
+        let world = rotation.rotate(local) + pivot + position;
+
+        min = min.min(world);
+        max = max.max(world);
+    }
+
+    (min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::ObjectId;
+
+    fn assert_bounds(actual: (Vec2, Vec2), min: Vec2, max: Vec2) {
+        assert!((actual.0 - min).length() < 1e-3 && (actual.1 - max).length() < 1e-3, "{:?} != {:?}", actual, (min, max));
+    }
+
+    /// Хранилище с прямоугольником 100x100 в (x, y) и включённое
+    /// отсечение по экрану 800x600. Флаги хранилища уже сняты
+    fn scene(x: f32, y: f32) -> (ObjectStore, Culler, ObjectId) {
+        let mut store = ObjectStore::new();
+        let id = store.new_rect();
+        store.config_position(id, Vec2::new(x, y));
+
+        let mut culler = Culler::new();
+        culler.set_enabled(true);
+        culler.set_viewport(Vec2::ZERO, Vec2::new(800.0, 600.0));
+        assert!(culler.update(&store));
+        store.clear_changes();
+
+        (store, culler, id)
+    }
+
+    fn cell_objects(culler: &Culler, cell: (i32, i32)) -> Vec<usize> {
+        culler.cells.get(&cell).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn bounds_follow_scale_and_rotation() {
+        let mut store = ObjectStore::new();
+        let id = store.new_rect();
+        store.config_position(id, Vec2::new(100.0, 100.0));
+        store.config_size(id, Vec2::new(100.0, 50.0));
+
+        assert_bounds(world_bounds(&store, id.index()), Vec2::new(100.0, 100.0), Vec2::new(200.0, 150.0));
+
+        // Масштаб растягивает рамку от точки опоры (Центра)
+        store.config_scale(id, Vec2::new(2.0, 1.0));
+        assert_bounds(world_bounds(&store, id.index()), Vec2::new(50.0, 100.0), Vec2::new(250.0, 150.0));
+
+        // Поворот на 90 градусов меняет ширину и высоту местами
+        store.config_rotation(id, std::f32::consts::FRAC_PI_2);
+        assert_bounds(world_bounds(&store, id.index()), Vec2::new(125.0, 25.0), Vec2::new(175.0, 225.0));
+
+        // Поворот на 45 градусов расширяет рамку до диагонали
+        store.config_scale(id, Vec2::ONE);
+        store.config_size(id, Vec2::splat(100.0));
+        store.config_rotation(id, std::f32::consts::FRAC_PI_4);
+        let half = 50.0 * std::f32::consts::SQRT_2;
+        assert_bounds(world_bounds(&store, id.index()), Vec2::splat(150.0 - half), Vec2::splat(150.0 + half));
+    }
+
+    #[test]
+    fn moved_object_changes_cells() {
+        let (mut store, mut culler, id) = scene(10.0, 10.0);
+        let index = id.index();
+
+        assert!(culler.in_view(index));
+        assert_eq!(cell_objects(&culler, (0, 0)), vec![index]);
+
+        // Из ячейки (0, 0) в ячейки (7, 7)..(8, 8) и за экран
+        store.config_position(id, Vec2::new(2000.0, 2000.0));
+        assert!(culler.update(&store));
+        store.clear_changes();
+
+        assert!(!culler.in_view(index));
+        assert!(!culler.cells.contains_key(&(0, 0)));
+        assert_eq!(culler.placement[index], Some(Cells::Range { min: (7, 7), max: (8, 8) }));
+        assert_eq!(cell_objects(&culler, (8, 8)), vec![index]);
+
+        // Новый вьюпорт находит объект
+        culler.set_viewport(Vec2::splat(1900.0), Vec2::splat(2500.0));
+        assert!(culler.update(&store));
+        assert!(culler.in_view(index));
+
+        // Движение внутри тех же ячеек не перекладывает объект
+        store.config_position(id, Vec2::new(2010.0, 2010.0));
+        assert!(!culler.update(&store));
+        assert_eq!(cell_objects(&culler, (7, 7)), vec![index]);
+    }
+
+    #[test]
+    fn large_objects_bypass_the_grid() {
+        let (mut store, mut culler, id) = scene(0.0, 0.0);
+        let index = id.index();
+
+        // 40x40 ячеек больше MAX_OBJECT_CELLS
+        store.config_size(id, Vec2::splat(10000.0));
+        assert!(!culler.update(&store));
+        store.clear_changes();
+
+        assert_eq!(culler.placement[index], Some(Cells::Large));
+        assert_eq!(culler.large, vec![index]);
+        assert!(culler.cells.is_empty());
+
+        culler.set_viewport(Vec2::splat(9000.0), Vec2::splat(9500.0));
+        assert!(!culler.update(&store));
+        assert!(culler.in_view(index));
+
+        culler.set_viewport(Vec2::splat(20000.0), Vec2::splat(20500.0));
+        assert!(culler.update(&store));
+        assert!(!culler.in_view(index));
+
+        // Уменьшенный объект возвращается в сетку
+        store.config_size(id, Vec2::splat(100.0));
+        assert!(!culler.update(&store));
+        assert!(culler.large.is_empty());
+        assert_eq!(cell_objects(&culler, (0, 0)), vec![index]);
+    }
+
+    #[test]
+    fn viewport_moves_report_visibility_changes_only() {
+        let (store, mut culler, id) = scene(10.0, 10.0);
+
+        // Тот же вьюпорт
+        culler.set_viewport(Vec2::ZERO, Vec2::new(800.0, 600.0));
+        assert!(!culler.update(&store));
+
+        // Сдвиг при котором объект остаётся на экране
+        culler.set_viewport(Vec2::splat(50.0), Vec2::new(850.0, 650.0));
+        assert!(!culler.update(&store));
+        assert!(culler.in_view(id.index()));
+
+        // Объект уходит с экрана и возвращается
+        culler.set_viewport(Vec2::splat(500.0), Vec2::new(1300.0, 1100.0));
+        assert!(culler.update(&store));
+        assert!(!culler.in_view(id.index()));
+
+        culler.set_viewport(Vec2::ZERO, Vec2::new(800.0, 600.0));
+        assert!(culler.update(&store));
+        assert!(culler.in_view(id.index()));
+
+        // Без отсечения рисуется всё
+        culler.set_enabled(false);
+        culler.set_viewport(Vec2::splat(500.0), Vec2::new(1300.0, 1100.0));
+        assert!(culler.update(&store));
+        assert!(!culler.update(&store));
+        assert!(culler.in_view(id.index()));
+    }
+
+    #[test]
+    fn huge_viewports_scan_bounds_directly() {
+        let mut store = ObjectStore::new();
+        let ids: Vec<ObjectId> = (0..20).map(|i| {
+            let id = store.new_rect();
+            store.config_position(id, Vec2::new(i as f32 * 300.0, 0.0));
+            id
+        }).collect();
+
+        let visible = |culler: &Culler| -> Vec<usize> {
+            ids.iter().map(|id| id.index()).filter(|&index| culler.in_view(index)).collect()
+        };
+
+        // 4x3 ячейки экрана меньше количества объектов, поиск по сетке
+        let mut culler = Culler::new();
+        culler.set_enabled(true);
+        culler.set_viewport(Vec2::ZERO, Vec2::new(800.0, 600.0));
+        assert!(culler.update(&store));
+        store.clear_changes();
+        assert_eq!(visible(&culler), vec![0, 1, 2]);
+
+        // Ячеек экрана больше чем i32 вмещает, объекты проверяются
+        // напрямую
+        culler.set_viewport(Vec2::splat(-1e12), Vec2::splat(1e12));
+        assert!(culler.update(&store));
+        assert_eq!(visible(&culler).len(), ids.len());
+
+        culler.set_viewport(Vec2::splat(1e11), Vec2::splat(1e12));
+        assert!(culler.update(&store));
+        assert!(visible(&culler).is_empty());
+    }
+}
\ No newline at end of file
@@ -3,20 +3,114 @@
 
 use crate::easy_gpu::Context;
 use crate::batching::shapes::rect::RectBatch;
-use crate::objects::store::ObjectStore;
+use crate::batching::shapes::image::ImageBatch;
+use crate::batching::shapes::path::PathBatch;
+use crate::batching::shapes::effect::EffectBatch;
+use crate::batching::order::DrawOrder;
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Submiter};
+use crate::batching::parallel;
+use crate::rendering::pipeline::ShaderStore;
+use crate::rendering::texture::TextureStore;
+use crate::error::MoonWalkError;
 
+/// Место батча прямоугольников в реестре (Его можно заменить на
+/// GpuRectBatch). Пользовательские батчи добавляются после
+/// встроенных
+pub const RECTS: usize = 0;
+
+/// Реестр батчей. Рендер проходит по нему целиком: готовит каждый
+/// батч, а потом рисует их отрезками в общем порядке по z
 pub struct BatchGroup {
-    pub rects: RectBatch,
+    pub submiters: Vec<Box<dyn Submiter>>,
+
+    // Общий порядок отрисовки между батчами
+    pub order: DrawOrder,
 }
 
 impl BatchGroup {
     pub fn new(ctx: &Context) -> Self {
         Self {
-            rects: RectBatch::new(ctx),
+            submiters: vec![
+                Box::new(RectBatch::new(ctx)),
+                Box::new(ImageBatch::new(ctx)),
+                Box::new(PathBatch::new()),
+                Box::new(EffectBatch::new(ctx)),
+            ],
+            order: DrawOrder::new(),
+        }
+    }
+
+    /// Пересобирает конвейеры всех батчей под проход рендера. Батч с
+    /// ошибкой не мешает собрать остальные, возвращается первая ошибка
+    pub fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        let mut result = Ok(());
+
+        for submiter in self.submiters.iter_mut() {
+            result = result.and(submiter.configure(ctx, shaders, textures, target));
+        }
+
+        result
+    }
+
+    /// Пересобирает конвейеры только тех батчей, чьи шейдеры есть в
+    /// changed. Каждая ошибка пишется в лог, остальные батчи всё
+    /// равно пересобираются
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    pub fn reload(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        textures: &TextureStore,
+        target: &RenderTarget,
+        changed: &[&str],
+    ) {
+        for submiter in self.submiters.iter_mut() {
+            if !submiter.shader_files().iter().any(|file| changed.contains(file)) {
+                continue;
+            }
+
+            if let Err(e) = submiter.configure(ctx, shaders, textures, target) {
+                log::error!("Shader reload failed: {}", e);
+            }
         }
     }
 
-    pub fn prepare(&mut self, ctx: &Context, store: &ObjectStore) {
-        self.rects.prepare(ctx, store);
+    /// Заменяет батч в реестре (Например прямоугольники на CPU на
+    /// прямоугольники на GPU). Общий порядок пересоберётся, так как
+    /// новый батч вернёт из prepare true
+    pub fn replace(&mut self, index: usize, submiter: Box<dyn Submiter>) {
+        self.submiters[index] = submiter;
+    }
+
+    /// В режиме глубины в общий порядок попадают только
+    /// полупрозрачные (И обрезанные) объекты
+    pub fn prepare(&mut self, frame: &Frame) {
+        // Батчи не зависят друг от друга, поэтому с фичей parallel
+        // готовятся одновременно. Общий порядок собирается после
+        let rebuilt = parallel::map_mut(&mut self.submiters, |submiter| submiter.prepare(frame));
+
+        // Порядок меняется только вместе со структурой батчей
+        if frame.store.dirty || rebuilt.iter().any(|&rebuilt| rebuilt) {
+            let lists: Vec<DrawItems> = self.submiters.iter()
+                .map(|submiter| submiter.items())
+                .collect();
+
+            self.order.rebuild(&frame.store.z_indices, frame.clipper.clips(), &lists);
+
+            for (batch, submiter) in self.submiters.iter_mut().enumerate() {
+                let runs: Vec<(u32, u32)> = self.order.runs.iter()
+                    .filter(|run| run.batch == batch)
+                    .map(|run| (run.start, run.count))
+                    .collect();
+
+                submiter.set_runs(frame.ctx, &runs);
+            }
+        }
     }
 }
\ No newline at end of file
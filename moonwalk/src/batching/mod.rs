@@ -4,4 +4,8 @@
 pub mod shapes;
 pub mod submiter;
 pub mod group;
-pub mod common;
\ No newline at end of file
+pub mod common;
+pub mod order;
+pub mod culling;
+pub mod clipping;
+pub mod parallel;
\ No newline at end of file
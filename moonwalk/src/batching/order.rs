@@ -0,0 +1,191 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::cmp::Ordering;
+
+use crate::batching::submiter::DrawItems;
+use crate::batching::clipping::NO_CLIP;
+
+/// Отрезок подряд идущих элементов одного батча (Инстансов или
+/// путей, в порядке батча), которые рисуются одним конвейером.
+/// batch это номер батча в реестре, clip это цепочка обрезки всех
+/// элементов отрезка (Смотрите Clipper)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawRun {
+    pub batch: usize,
+    pub start: u32,
+    pub count: u32,
+    pub clip: u32,
+}
+
+/// Общий порядок отрисовки для всех батчей. Каждый батч уже
+/// отсортирован по z внутри себя, поэтому тут они сливаются как
+/// отсортированные списки. Подряд идущие элементы одного батча
+/// склеиваются в один отрезок, так что при объектах на разных
+/// z (Прямоугольник -> изображение -> прямоугольник) получается
+/// столько вызовов, сколько реально нужно, а не по одному на объект
+pub struct DrawOrder {
+    pub runs: Vec<DrawRun>,
+}
+
+/// Ключ сортировки элемента: z и порядок создания. У слоёв порядок
+/// максимальный, поэтому при равном z они рисуются поверх объектов
+#[inline(always)]
+fn item_key(z_indices: &[f32], items: &DrawItems, position: usize) -> Option<(f32, usize)> {
+    match *items {
+        DrawItems::Objects(objects) => objects.get(position).map(|&index| (z_indices[index], index)),
+        DrawItems::Layer { z, count } => (position < count as usize).then_some((z, usize::MAX)),
+    }
+}
+
+/// Цепочка обрезки элемента. Слои не обрезаются
+#[inline(always)]
+fn item_clip(clips: &[u32], items: &DrawItems, position: usize) -> u32 {
+    match *items {
+        DrawItems::Objects(objects) => clips.get(objects[position]).copied().unwrap_or(NO_CLIP),
+        DrawItems::Layer { .. } => NO_CLIP,
+    }
+}
+
+#[inline(always)]
+fn key_cmp(a: (f32, usize), b: (f32, usize)) -> Ordering {
+    a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+}
+
+impl DrawOrder {
+    pub fn new() -> Self {
+        Self {
+            runs: Vec::new(),
+        }
+    }
+
+    /// Пересобирает отрезки. lists это элементы каждого батча в
+    /// порядке отрисовки (В порядке реестра). При равном z раньше
+    /// рисуется объект, созданный раньше (Независимо от типа). clips
+    /// это цепочка обрезки каждого объекта, отрезок не смешивает
+    /// объекты с разной обрезкой
+    pub fn rebuild(&mut self, z_indices: &[f32], clips: &[u32], lists: &[DrawItems]) {
+        self.runs.clear();
+
+        let mut cursors = vec![0usize; lists.len()];
+
+        // Следующий элемент батча, который ещё не нарисован
+        let head = |cursors: &[usize], list: usize| item_key(z_indices, &lists[list], cursors[list]);
+
+        loop {
+            // Выбираем батч, чей следующий элемент должен
+            // рисоваться раньше всех
+            let best = (0..lists.len())
+                .filter_map(|list| head(&cursors, list).map(|key| (list, key)))
+                .min_by(|a, b| key_cmp(a.1, b.1));
+
+            let list = match best {
+                Some((list, _)) => list,
+                None => break,
+            };
+
+            // Граница отрезка это самый ранний следующий элемент
+            // среди остальных батчей
+            let limit = (0..lists.len())
+                .filter(|&other| other != list)
+                .filter_map(|other| head(&cursors, other))
+                .min_by(|&a, &b| key_cmp(a, b));
+
+            let start = cursors[list];
+            let clip = item_clip(clips, &lists[list], start);
+            let mut end = start + 1;
+
+            while let Some(key) = item_key(z_indices, &lists[list], end) {
+                if let Some(limit) = limit {
+                    if key_cmp(key, limit) == Ordering::Greater {
+                        break;
+                    }
+                }
+
+                if item_clip(clips, &lists[list], end) != clip {
+                    break;
+                }
+
+                end += 1;
+            }
+
+            cursors[list] = end;
+            self.runs.push(DrawRun {
+                batch: list,
+                start: start as u32,
+                count: (end - start) as u32,
+                clip,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(batch: usize, start: u32, count: u32, clip: u32) -> DrawRun {
+        DrawRun { batch, start, count, clip }
+    }
+
+    // Прямоугольники 0, 2, 3 и изображения 1, 4, 5 вперемешку по z
+    const Z: [f32; 6] = [0.0, 1.0, 1.0, 1.0, 2.0, 2.0];
+    const RECTS: [usize; 3] = [0, 2, 3];
+    const IMAGES: [usize; 3] = [1, 4, 5];
+
+    #[test]
+    fn interleaved_batches_merge_by_z() {
+        let mut order = DrawOrder::new();
+        let clips = vec![NO_CLIP; Z.len()];
+
+        order.rebuild(&Z, &clips, &[DrawItems::Objects(&RECTS), DrawItems::Objects(&IMAGES)]);
+
+        // При равном z раньше рисуется объект созданный раньше
+        assert_eq!(order.runs, vec![
+            run(0, 0, 1, NO_CLIP),
+            run(1, 0, 1, NO_CLIP),
+            run(0, 1, 2, NO_CLIP),
+            run(1, 1, 2, NO_CLIP),
+        ]);
+    }
+
+    #[test]
+    fn clip_changes_split_runs() {
+        let mut order = DrawOrder::new();
+        let mut clips = vec![NO_CLIP; Z.len()];
+        clips[3] = 2;
+        clips[5] = 2;
+
+        order.rebuild(&Z, &clips, &[DrawItems::Objects(&RECTS), DrawItems::Objects(&IMAGES)]);
+
+        // Одинаковая обрезка в разных батчах не склеивает отрезки
+        assert_eq!(order.runs, vec![
+            run(0, 0, 1, NO_CLIP),
+            run(1, 0, 1, NO_CLIP),
+            run(0, 1, 1, NO_CLIP),
+            run(0, 2, 1, 2),
+            run(1, 1, 1, NO_CLIP),
+            run(1, 2, 1, 2),
+        ]);
+    }
+
+    #[test]
+    fn layers_draw_over_objects_with_equal_z() {
+        let mut order = DrawOrder::new();
+        let clips = vec![NO_CLIP; Z.len()];
+
+        order.rebuild(&Z, &clips, &[
+            DrawItems::Layer { z: 1.0, count: 3 },
+            DrawItems::Objects(&RECTS),
+            DrawItems::Objects(&IMAGES),
+        ]);
+
+        assert_eq!(order.runs, vec![
+            run(1, 0, 1, NO_CLIP),
+            run(2, 0, 1, NO_CLIP),
+            run(1, 1, 2, NO_CLIP),
+            run(0, 0, 3, NO_CLIP),
+            run(2, 1, 2, NO_CLIP),
+        ]);
+    }
+}
\ No newline at end of file
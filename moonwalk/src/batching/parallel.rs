@@ -0,0 +1,91 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Параллельная подготовка батчей. С фичей parallel работа делится
+// на пул потоков rayon, без неё (Однопоточные платформы, wasm)
+// всё выполняется на текущем потоке тем же кодом.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Сколько объектов упаковывает один поток за раз. Меньше не имеет
+/// смысла: накладные расходы пула съедят выигрыш
+#[cfg(any(feature = "parallel", test))]
+pub const CHUNK_SIZE: usize = 4096;
+
+/// Упаковывает данные объектов (Инстансы, вершины) отрезками по
+/// CHUNK_SIZE. Каждый отрезок пишет в свой вектор, а потом они
+/// склеиваются по порядку отрезков, поэтому результат всегда
+/// совпадает с последовательной упаковкой objects
+pub fn pack_chunks<T, F>(objects: &[usize], pack: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize, &mut Vec<T>) + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        if objects.len() > CHUNK_SIZE {
+            let chunks: Vec<Vec<T>> = objects.par_chunks(CHUNK_SIZE)
+                .map(|chunk| {
+                    let mut out = Vec::with_capacity(chunk.len());
+                    for &index in chunk {
+                        pack(index, &mut out);
+                    }
+
+                    out
+                })
+                .collect();
+
+            return chunks.into_iter().flatten().collect();
+        }
+    }
+
+    let mut out = Vec::with_capacity(objects.len());
+    for &index in objects {
+        pack(index, &mut out);
+    }
+
+    out
+}
+
+/// Вызывает f для каждого элемента (Параллельно если включена фича
+/// parallel). Результаты идут в порядке элементов
+pub fn map_mut<T, R, F>(items: &mut [T], f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(&mut T) -> R + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.par_iter_mut().map(&f).collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter_mut().map(f).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_keep_object_order() {
+        // Несколько отрезков и разное число элементов на объект
+        let objects: Vec<usize> = (0..CHUNK_SIZE * 3 + 17).rev().collect();
+
+        let packed = pack_chunks(&objects, |idx, out| {
+            for i in 0..idx % 3 {
+                out.push((idx, i));
+            }
+        });
+
+        let expected: Vec<(usize, usize)> = objects.iter()
+            .flat_map(|&idx| (0..idx % 3).map(move |i| (idx, i)))
+            .collect();
+
+        assert_eq!(packed, expected);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,184 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use crate::easy_gpu::{Buffer, Context, RenderPass};
+use crate::rendering::vertex::{EffectInstance, QuadVertex};
+use crate::rendering::pipeline::{ShaderStore, EFFECT_SHADER};
+use crate::rendering::texture::TextureStore;
+use crate::objects::store::ObjectStore;
+use crate::objects::{ObjectId, ShaderId};
+use crate::batching::common::{self, BatchBuffer};
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Resources, Submiter};
+use crate::batching::parallel;
+use crate::error::MoonWalkError;
+use crate::batching::shapes::rect::rect_instance;
+
+/// Отрезок инстансов с одним и тем же эффектом. Каждый отрезок это
+/// отдельный вызов отрисовки
+struct EffectRun {
+    shader: ShaderId,
+    start: u32,
+    count: u32,
+}
+
+/// Батч прямоугольников с пользовательским эффектом. Вершинная
+/// стадия у всех эффектов общая, поэтому инстансы лежат в одном
+/// буфере, а между отрезками меняется только конвейер.
+///     [!] В режиме буфера глубины эффекты всегда рисуются как
+///         полупрозрачные
+pub struct EffectBatch {
+    static_vbo: Buffer<QuadVertex>,
+    static_ibo: Buffer<u32>,
+    batch: BatchBuffer<EffectInstance>,
+    runs: Vec<EffectRun>,
+
+    // Индексы прямоугольников с эффектом отсортированные по z
+    order: Vec<usize>,
+}
+
+impl EffectBatch {
+    pub fn new(ctx: &Context) -> Self {
+        let static_vbo = Buffer::vertex(ctx, &QuadVertex::QUAD);
+        let static_ibo = Buffer::<u32>::index(ctx, &QuadVertex::INDICES);
+
+        Self {
+            static_vbo,
+            static_ibo,
+            batch: BatchBuffer::new(),
+            runs: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl Submiter for EffectBatch {
+    /// Конвейеры эффектов хранятся в ShaderStore под своими ID,
+    /// поэтому тут они только пересобираются
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        _textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        shaders.rebuild_effects(ctx, target)
+    }
+
+    fn shader_files(&self) -> &[&'static str] {
+        &[EFFECT_SHADER.name]
+    }
+
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        let Frame { ctx, store, culler, .. } = *frame;
+
+        // Эффект и порядок меняются только с полной пересборкой,
+        // поэтому отрезки остаются прежними
+        if !store.dirty {
+            self.update_changed(ctx, store);
+            return false;
+        }
+
+        self.batch.clear(store.positions.len());
+        self.runs.clear();
+
+        // Объектов с эффектами обычно немного, поэтому сортируем их
+        // при каждой пересборке
+        let effects: Vec<ObjectId> = store.rect_ids.iter()
+            .copied()
+            .filter(|id| store.effects.contains_key(&id.index()))
+            .collect();
+
+        common::sort_by_z(&mut self.order, &effects, &store.z_indices);
+
+        let visible = store.world_visible();
+        let drawn: Vec<usize> = self.order.iter()
+            .copied()
+            .filter(|&idx| visible[idx] && culler.in_view(idx))
+            .collect();
+
+        self.push_all(store, &drawn);
+
+        self.batch.upload(ctx);
+        true
+    }
+
+    /// Глобальные индексы объектов в порядке отрисовки
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.batch.objects)
+    }
+
+    /// Отрезки с одним эффектом обрезаются по границам диапазона
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32) {
+        let inst_buf = match &self.batch.gpu_buffer {
+            Some(inst_buf) => inst_buf,
+            None => return,
+        };
+
+        if count == 0 {
+            return;
+        }
+
+        pass.set_vertex_buffer(0, &self.static_vbo);
+        pass.set_vertex_buffer(1, inst_buf);
+        pass.set_index_buffer(&self.static_ibo);
+
+        let end = start + count;
+
+        for run in self.runs.iter() {
+            let from = run.start.max(start);
+            let to = (run.start + run.count).min(end);
+
+            if from >= to {
+                continue;
+            }
+
+            if let Some(pipeline) = res.shaders.get_pipeline(run.shader) {
+                pass.set_pipeline(pipeline);
+                pass.draw_indexed_instanced_range(6, from, to - from);
+            }
+        }
+    }
+}
+
+impl EffectBatch {
+    fn update_changed(&mut self, ctx: &Context, store: &ObjectStore) {
+        for &idx in store.changed.iter() {
+            self.batch.replace(idx, effect_instance(store, idx));
+        }
+
+        self.batch.flush(ctx);
+    }
+
+    /// Отрезки эффектов собираются по порядку, а инстансы
+    /// упаковываются отдельно (Параллельно если включена фича parallel)
+    fn push_all(&mut self, store: &ObjectStore, objects: &[usize]) {
+        for (slot, &idx) in objects.iter().enumerate() {
+            let shader = store.effects[&idx];
+
+            // Соседние объекты с одним эффектом рисуются одним вызовом
+            match self.runs.last_mut() {
+                Some(run) if run.shader == shader => {
+                    run.count += 1;
+                },
+                _ => self.runs.push(EffectRun { shader, start: slot as u32, count: 1 }),
+            }
+        }
+
+        let instances = parallel::pack_chunks(objects, |idx, out| out.push(effect_instance(store, idx)));
+        self.batch.extend(objects, instances);
+    }
+}
+
+/// Инстанс прямоугольника с параметрами эффекта объекта (Нули, если
+/// параметры не заданы)
+#[inline]
+fn effect_instance(store: &ObjectStore, idx: usize) -> EffectInstance {
+    let params = store.effect_params.get(&idx)
+        .map(|params| [params[0].to_array(), params[1].to_array()])
+        .unwrap_or_default();
+
+    EffectInstance {
+        rect: rect_instance(store, idx),
+        params,
+    }
+}
\ No newline at end of file
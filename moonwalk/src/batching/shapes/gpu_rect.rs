@@ -0,0 +1,461 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::ops::Range;
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+
+use crate::easy_gpu::{Buffer, ComputePipeline, Context, RenderPass};
+use crate::rendering::vertex::{QuadVertex, RectInstance};
+use crate::rendering::pipeline::{BuiltinShader, DepthMode, ShaderStore, RECT_SHADER};
+use crate::rendering::texture::TextureStore;
+use crate::objects::store::ObjectStore;
+use crate::objects::ShaderId;
+use crate::batching::common;
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Resources, Submiter};
+use crate::error::MoonWalkError;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Compute шейдер подготовки. Вершинных буферов у него нет
+pub(crate) const PREPARE_SHADER: BuiltinShader = BuiltinShader {
+    name: "rect_prepare.wgsl",
+    source: include_str!("../../shaders/rect_prepare.wgsl"),
+    layouts: &[],
+};
+
+/// Точки входа проходов подготовки в порядке запуска
+pub(crate) const PREPARE_ENTRIES: [&str; 3] = ["cs_cull", "cs_scan", "cs_main"];
+
+/// Параметры compute шейдера (Смотрите rect_prepare.wgsl)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct PrepareParams {
+    count: u32,
+    capacity: u32,
+    cull: u32,
+    runs: u32,
+    view_min: [f32; 2],
+    view_max: [f32; 2],
+}
+
+/// Данные объекта в том виде, в котором они уже залиты на GPU. По
+/// ним при структурных изменениях ищутся объекты, которые реально
+/// поменялись, чтобы не заливать все массивы заново
+#[derive(Clone, Copy, Default, PartialEq)]
+struct RectData {
+    position: Vec2,
+    size: Vec2,
+    scale: Vec2,
+    pivot: Vec2,
+    color: Vec4,
+    radii: Vec4,
+    rotation: f32,
+    z: f32,
+    opacity: f32,
+    visible: f32,
+}
+
+impl RectData {
+    fn of(store: &ObjectStore, idx: usize) -> Self {
+        Self {
+            position: store.world_positions()[idx],
+            size: store.sizes[idx],
+            scale: store.world_scales()[idx],
+            pivot: store.pivots[idx],
+            color: store.colors[idx],
+            radii: store.rect_radii[idx],
+            rotation: store.world_rotations()[idx],
+            z: store.z_indices[idx],
+            opacity: store.world_opacities()[idx],
+            visible: visible_flag(store, idx),
+        }
+    }
+}
+
+/// Буферы на GPU. Буферы объектов пересоздаются вместе при росте
+/// количества объектов, буферы отрезков растут отдельно
+struct GpuBuffers {
+    capacity: usize,
+    vec2s: Buffer<Vec2>,
+    vec4s: Buffer<Vec4>,
+    scalars: Buffer<f32>,
+    order: Buffer<u32>,
+    instances: Buffer<RectInstance>,
+    scan: Buffer<u32>,
+    ranges: Buffer<[u32; 2]>,
+    indirect: Buffer<u32>,
+}
+
+impl GpuBuffers {
+    fn new(ctx: &Context, capacity: usize, runs: usize) -> Self {
+        let storage = wgpu::BufferUsages::STORAGE;
+
+        // Отметки объектов и смещения групп (Плюс общее количество)
+        let scan = capacity + capacity.div_ceil(WORKGROUP_SIZE as usize) + 1;
+
+        Self {
+            capacity,
+            vec2s: Buffer::with_capacity(ctx, capacity * 4, storage, "Rect Vec2 Data"),
+            vec4s: Buffer::with_capacity(ctx, capacity * 2, storage, "Rect Vec4 Data"),
+            scalars: Buffer::with_capacity(ctx, capacity * 4, storage, "Rect Scalar Data"),
+            order: Buffer::with_capacity(ctx, capacity, storage, "Rect Order"),
+            instances: Buffer::with_capacity(ctx, capacity, storage | wgpu::BufferUsages::VERTEX, "Rect Instances"),
+            scan: Buffer::with_capacity(ctx, scan, storage, "Rect Visibility Scan"),
+            ranges: Buffer::with_capacity(ctx, runs, storage, "Rect Runs"),
+            indirect: Buffer::with_capacity(ctx, runs * 5, storage | wgpu::BufferUsages::INDIRECT, "Rect Indirect"),
+        }
+    }
+
+    /// Сколько отрезков вмещают буферы отрезков
+    fn runs(&self) -> usize {
+        self.ranges.capacity()
+    }
+
+    fn grow_runs(&mut self, ctx: &Context, runs: usize) {
+        let storage = wgpu::BufferUsages::STORAGE;
+
+        self.ranges = Buffer::with_capacity(ctx, runs, storage, "Rect Runs");
+        self.indirect = Buffer::with_capacity(ctx, runs * 5, storage | wgpu::BufferUsages::INDIRECT, "Rect Indirect");
+    }
+}
+
+/// Батч прямоугольников, инстансы которого собирает GPU. Массивы
+/// хранилища объектов копируются в storage буферы (Только изменённые
+/// диапазоны), а compute шейдер отсекает объекты за экраном, сжимает
+/// видимые внутри каждого отрезка общего порядка и пишет количество
+/// инстансов каждого отрезка для косвенной отрисовки. Процессор
+/// только сортирует индексы по z (Когда z грязные) и заливает
+/// изменения.
+///     [!] В режиме буфера глубины все прямоугольники считаются
+///         полупрозрачными
+pub struct GpuRectBatch {
+    static_vbo: Buffer<QuadVertex>,
+    static_ibo: Buffer<u32>,
+    cull: ComputePipeline,
+    scan: ComputePipeline,
+    pack: ComputePipeline,
+    params: Buffer<PrepareParams>,
+    settings: PrepareParams,
+    layout: wgpu::BindGroupLayout,
+    buffers: Option<GpuBuffers>,
+    bind_group: Option<wgpu::BindGroup>,
+
+    // Индексы прямоугольников отсортированные по z
+    order: Vec<usize>,
+
+    // Отрезки батча в общем порядке (Начало и количество)
+    runs: Vec<[u32; 2]>,
+
+    // Что уже залито на GPU и объекты, которые нужно залить
+    uploaded: Vec<RectData>,
+    pending: Vec<u32>,
+
+    shader: ShaderId,
+}
+
+impl GpuRectBatch {
+    pub fn new(ctx: &Context) -> Self {
+        let storage = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let layout = ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Rect Prepare Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, true),
+                storage(3, true),
+                storage(4, true),
+                storage(5, false),
+                storage(6, false),
+                storage(7, true),
+                storage(8, false),
+            ],
+        });
+
+        let source = PREPARE_SHADER.load();
+        let [cull, scan, pack] = PREPARE_ENTRIES.map(|entry| ComputePipeline::new(ctx, &source, entry, &[&layout]));
+
+        Self {
+            static_vbo: Buffer::vertex(ctx, &QuadVertex::QUAD),
+            static_ibo: Buffer::<u32>::index(ctx, &QuadVertex::INDICES),
+            cull,
+            scan,
+            pack,
+            params: Buffer::uniform(ctx, &PrepareParams::zeroed()),
+            settings: PrepareParams::zeroed(),
+            layout,
+            buffers: None,
+            bind_group: None,
+            order: Vec::new(),
+            runs: Vec::new(),
+            uploaded: Vec::new(),
+            pending: Vec::new(),
+            shader: ShaderId::default(),
+        }
+    }
+
+    /// Пересобирает compute конвейеры из шейдера на диске. Шейдер с
+    /// ошибкой оставляет прежние конвейеры
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    fn reload_prepare(&mut self, ctx: &Context) {
+        let source = PREPARE_SHADER.load();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let [cull, scan, pack] = PREPARE_ENTRIES.map(|entry| ComputePipeline::new(ctx, &source, entry, &[&self.layout]));
+
+        match pollster::block_on(ctx.device.pop_error_scope()) {
+            Some(error) => log::error!("Shader reload failed, keeping the previous pipeline: {}", error),
+            None => {
+                self.cull = cull;
+                self.scan = scan;
+                self.pack = pack;
+            }
+        }
+    }
+
+    fn bind(&mut self, ctx: &Context) {
+        let buffers = match &self.buffers {
+            Some(buffers) => buffers,
+            None => return,
+        };
+
+        self.bind_group = Some(ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Rect Prepare Bind Group"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.params.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: buffers.vec2s.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: buffers.vec4s.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: buffers.scalars.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: buffers.order.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 5, resource: buffers.instances.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 6, resource: buffers.scan.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 7, resource: buffers.ranges.raw.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 8, resource: buffers.indirect.raw.as_entire_binding() },
+            ],
+        }));
+    }
+
+    /// Отмечает объекты, чьи данные на GPU устарели. При структурных
+    /// изменениях неизвестно, что поменялось, поэтому сравниваются
+    /// все объекты, но заливаются только отличающиеся
+    fn collect_changes(&mut self, store: &ObjectStore, everything: bool) {
+        let objects = store.positions.len();
+        let known = self.uploaded.len().min(objects);
+        self.uploaded.resize(objects, RectData::default());
+
+        let mut check = |idx: usize| {
+            let data = RectData::of(store, idx);
+
+            if idx >= known || self.uploaded[idx] != data {
+                self.uploaded[idx] = data;
+                self.pending.push(idx as u32);
+            }
+        };
+
+        if everything {
+            (0..objects).for_each(&mut check);
+        } else {
+            store.changed.iter().copied().filter(|&idx| idx < objects).for_each(check);
+        }
+    }
+
+    /// Заливает отрезки и, если их стало больше, растит их буферы
+    fn upload_runs(&mut self, ctx: &Context) {
+        let grow = match &mut self.buffers {
+            Some(buffers) if self.runs.len() > buffers.runs() => {
+                buffers.grow_runs(ctx, self.runs.len().next_power_of_two());
+                true
+            }
+            Some(_) => false,
+            None => return,
+        };
+
+        if grow {
+            self.bind(ctx);
+        }
+
+        if let Some(buffers) = &self.buffers {
+            if !self.runs.is_empty() {
+                buffers.ranges.write_at(ctx, 0, &self.runs);
+            }
+        }
+
+        self.settings.runs = self.runs.len() as u32;
+        self.params.update_one(ctx, &self.settings);
+    }
+}
+
+impl Submiter for GpuRectBatch {
+    /// Конвейер рендера общий с RectBatch, но без непрозрачной части
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        _textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        let depth = if target.depth { DepthMode::Translucent } else { DepthMode::None };
+        self.shader = shaders.create_default_rect(ctx, target, depth)?;
+
+        // Compute конвейер не зависит от прохода, его пересобираем
+        // только ради горячей перезагрузки
+        #[cfg(all(feature = "hot-reload", debug_assertions))]
+        self.reload_prepare(ctx);
+
+        Ok(())
+    }
+
+    fn shader_files(&self) -> &[&'static str] {
+        &[RECT_SHADER.name, PREPARE_SHADER.name]
+    }
+
+    /// Заливает изменения и параметры. Возвращает true если
+    /// изменился порядок (Тогда нужно пересобрать общий порядок)
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        let Frame { ctx, store, culler, .. } = *frame;
+
+        let objects = store.positions.len();
+
+        // Буферы растут в два раза, чтобы не пересоздавать их на
+        // каждый новый объект
+        let grown = match &self.buffers {
+            Some(buffers) => objects > buffers.capacity,
+            None => objects > 0,
+        };
+
+        if grown {
+            let runs = self.runs.len().next_power_of_two().max(WORKGROUP_SIZE as usize);
+            self.buffers = Some(GpuBuffers::new(ctx, objects.next_power_of_two().max(1024), runs));
+            self.uploaded.clear();
+            self.bind(ctx);
+        }
+
+        if self.buffers.is_none() {
+            return false;
+        }
+
+        if store.dirty || grown || !store.changed.is_empty() {
+            self.collect_changes(store, store.dirty || grown);
+        }
+
+        let buffers = match &self.buffers {
+            Some(buffers) => buffers,
+            None => return false,
+        };
+
+        // Соседние изменённые объекты заливаются одним диапазоном
+        common::drain_ranges(&mut self.pending, |range| upload_range(ctx, buffers, store, range));
+
+        let reordered = grown || store.dirty || store.z_dirty || self.order.len() != store.rect_ids.len();
+        if reordered {
+            common::sort_by_z(&mut self.order, &store.rect_ids, &store.z_indices);
+
+            let order: Vec<u32> = self.order.iter().map(|&i| i as u32).collect();
+            buffers.order.write_at(ctx, 0, &order);
+        }
+
+        let (view_min, view_max) = culler.viewport();
+        self.settings = PrepareParams {
+            count: self.order.len() as u32,
+            capacity: buffers.capacity as u32,
+            cull: culler.enabled() as u32,
+            runs: self.runs.len() as u32,
+            view_min: view_min.to_array(),
+            view_max: view_max.to_array(),
+        };
+        self.params.update_one(ctx, &self.settings);
+
+        reordered
+    }
+
+    /// Шейдер сжимает инстансы внутри каждого отрезка, поэтому ему
+    /// нужны их границы
+    fn set_runs(&mut self, ctx: &Context, runs: &[(u32, u32)]) {
+        self.runs.clear();
+        self.runs.extend(runs.iter().map(|&(start, count)| [start, count]));
+        self.upload_runs(ctx);
+    }
+
+    /// Записывает compute проходы. Вызывается до прохода рендера
+    fn dispatch(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(bind_group) = &self.bind_group {
+            if !self.order.is_empty() && !self.runs.is_empty() {
+                let groups = (self.order.len() as u32).div_ceil(WORKGROUP_SIZE);
+
+                self.cull.dispatch(encoder, bind_group, groups);
+                self.scan.dispatch(encoder, bind_group, 1);
+                self.pack.dispatch(encoder, bind_group, groups);
+            }
+        }
+    }
+
+    /// Глобальные индексы объектов в порядке отрисовки
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.order)
+    }
+
+    /// Рисует отрезок. Видимые инстансы отрезка лежат подряд с его
+    /// начала, а их количество берётся из косвенного буфера
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32) {
+        if let (Some(buffers), Some(pipeline)) = (&self.buffers, res.shaders.get_pipeline(self.shader)) {
+            let run = match self.runs.binary_search_by_key(&start, |run| run[0]) {
+                Ok(run) if self.runs[run][1] == count => run,
+                _ => return,
+            };
+
+            pass.set_pipeline(pipeline);
+            pass.set_vertex_buffer(0, &self.static_vbo);
+            pass.set_vertex_buffer_from(1, &buffers.instances, start);
+            pass.set_index_buffer(&self.static_ibo);
+            pass.draw_indexed_indirect_at(&buffers.indirect, run as u32 * 5);
+        }
+    }
+}
+
+/// Видимость объекта для шейдера (bool нельзя отправить на GPU как
+/// есть). Прямоугольники с эффектом рисует батч эффектов, поэтому
+/// для шейдера они скрыты
+fn visible_flag(store: &ObjectStore, idx: usize) -> f32 {
+    (store.world_visible()[idx] && !store.effects.contains_key(&idx)) as u32 as f32
+}
+
+/// Заливает объекты range из каждого массива в его секцию
+fn upload_range(ctx: &Context, buffers: &GpuBuffers, store: &ObjectStore, range: Range<usize>) {
+    let capacity = buffers.capacity;
+    let start = range.start;
+
+    buffers.vec2s.write_at(ctx, start, &store.world_positions()[range.clone()]);
+    buffers.vec2s.write_at(ctx, capacity + start, &store.sizes[range.clone()]);
+    buffers.vec2s.write_at(ctx, capacity * 2 + start, &store.world_scales()[range.clone()]);
+    buffers.vec2s.write_at(ctx, capacity * 3 + start, &store.pivots[range.clone()]);
+
+    buffers.vec4s.write_at(ctx, start, &store.colors[range.clone()]);
+    buffers.vec4s.write_at(ctx, capacity + start, &store.rect_radii[range.clone()]);
+
+    buffers.scalars.write_at(ctx, start, &store.world_rotations()[range.clone()]);
+    buffers.scalars.write_at(ctx, capacity + start, &store.z_indices[range.clone()]);
+    buffers.scalars.write_at(ctx, capacity * 2 + start, &store.world_opacities()[range.clone()]);
+
+    let flags: Vec<f32> = range.map(|idx| visible_flag(store, idx)).collect();
+    buffers.scalars.write_at(ctx, capacity * 3 + start, &flags);
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::collections::hash_map::Entry;
+
+use glam::Vec4;
+use rustc_hash::FxHashMap;
+
+use crate::easy_gpu::{Buffer, Context, RenderPass, SamplerOptions};
+use crate::rendering::vertex::{QuadVertex, RectInstance};
+use crate::rendering::pipeline::{DepthMode, ShaderStore, IMAGE_SHADER};
+use crate::rendering::texture::TextureStore;
+use crate::objects::store::ObjectStore;
+use crate::objects::{ShaderId, TextureId};
+use crate::batching::common::{self, BatchBuffer};
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Resources, Submiter};
+use crate::batching::parallel;
+use crate::batching::clipping::Clipper;
+use crate::error::MoonWalkError;
+use crate::batching::shapes::rect::rect_instance;
+
+/// Отрезок инстансов с одной и той же текстурой и сэмплером.
+/// Каждый отрезок это отдельный вызов отрисовки
+struct ImageRun {
+    texture: TextureId,
+    sampler: Option<SamplerOptions>,
+    start: u32,
+    count: u32,
+}
+
+pub struct ImageBatch {
+    static_vbo: Buffer<QuadVertex>,
+    static_ibo: Buffer<u32>,
+    batch: BatchBuffer<RectInstance>,
+    runs: Vec<ImageRun>,
+
+    // Индексы изображений отсортированные по z. Текстура привязана
+    // к объекту, а не к инстансу, поэтому сортируем индексы, а не
+    // сами инстансы
+    order: Vec<usize>,
+
+    // Все объекты от ближних к дальним для непрозрачной части в
+    // режиме глубины. Сортируется только когда z индексы грязные,
+    // появились новые объекты или режим глубины только включили
+    opaque_order: Vec<usize>,
+
+    // Количество непрозрачных инстансов в начале буфера и был ли
+    // order собран в режиме глубины
+    opaque: u32,
+    sorted_depth: bool,
+
+    // Бинд группы изображений с собственным сэмплером (Текстура
+    // хранилища + сэмплер объекта)
+    sampler_groups: FxHashMap<(TextureId, SamplerOptions), wgpu::BindGroup>,
+
+    shader: ShaderId,
+    opaque_shader: Option<ShaderId>,
+}
+
+impl ImageBatch {
+    pub fn new(ctx: &Context) -> Self {
+        let static_vbo = Buffer::vertex(ctx, &QuadVertex::QUAD);
+        let static_ibo = Buffer::<u32>::index(ctx, &QuadVertex::INDICES);
+
+        Self {
+            static_vbo,
+            static_ibo,
+            batch: BatchBuffer::new(),
+            runs: Vec::new(),
+            order: Vec::new(),
+            opaque_order: Vec::new(),
+            opaque: 0,
+            sorted_depth: false,
+            sampler_groups: FxHashMap::default(),
+            shader: ShaderId::default(),
+            opaque_shader: None,
+        }
+    }
+}
+
+impl Submiter for ImageBatch {
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        let layout = &textures.layout;
+
+        if target.depth {
+            self.shader = shaders.create_default_image(ctx, target, layout, DepthMode::Translucent)?;
+            self.opaque_shader = Some(shaders.create_default_image(ctx, target, layout, DepthMode::Opaque)?);
+        } else {
+            self.shader = shaders.create_default_image(ctx, target, layout, DepthMode::None)?;
+            self.opaque_shader = None;
+        }
+
+        Ok(())
+    }
+
+    fn shader_files(&self) -> &[&'static str] {
+        &[IMAGE_SHADER.name]
+    }
+
+    /// Режим буфера глубины работает так же как у RectBatch
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        let Frame { ctx, store, textures, culler, clipper, depth } = *frame;
+
+        // Текстура и сэмплер меняются только с полной пересборкой,
+        // поэтому отрезки остаются прежними
+        if !store.dirty && self.update_changed(ctx, store, textures, clipper, depth) {
+            return false;
+        }
+
+        self.batch.clear(store.positions.len());
+        self.runs.clear();
+        let visible = store.world_visible();
+
+        if depth {
+            if store.z_dirty || !self.sorted_depth || self.opaque_order.len() != store.image_ids.len() {
+                common::sort_front_to_back(&mut self.opaque_order, &store.image_ids, &store.z_indices);
+            }
+
+            let opaque: Vec<usize> = self.opaque_order.iter()
+                .copied()
+                .filter(|&idx| visible[idx] && culler.in_view(idx) && is_opaque(store, textures, clipper, idx))
+                .collect();
+
+            self.push_all(ctx, store, textures, &opaque);
+
+            let translucent: Vec<_> = store.image_ids.iter()
+                .copied()
+                .filter(|id| !is_opaque(store, textures, clipper, id.index()))
+                .collect();
+
+            common::sort_by_z(&mut self.order, &translucent, &store.z_indices);
+        } else if store.z_dirty || self.sorted_depth || self.order.len() != store.image_ids.len() {
+            common::sort_by_z(&mut self.order, &store.image_ids, &store.z_indices);
+        }
+
+        self.opaque = self.batch.cpu_buffer.len() as u32;
+        self.sorted_depth = depth;
+
+        let drawn: Vec<usize> = self.order.iter()
+            .copied()
+            .filter(|&idx| visible[idx] && culler.in_view(idx))
+            .collect();
+
+        self.push_all(ctx, store, textures, &drawn);
+
+        self.batch.upload(ctx);
+        true
+    }
+
+    /// Рисует непрозрачную часть батча (Только в режиме глубины)
+    fn render_opaque<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>) {
+        if let Some(shader) = self.opaque_shader {
+            self.draw_slots(pass, res, shader, 0, self.opaque);
+        }
+    }
+
+    /// Глобальные индексы полупрозрачных (Всех без режима глубины)
+    /// объектов в порядке отрисовки
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.batch.objects[self.opaque as usize..])
+    }
+
+    /// Рисует часть инстансов (start..start + count) из items()
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32) {
+        self.draw_slots(pass, res, self.shader, self.opaque + start, count);
+    }
+}
+
+impl ImageBatch {
+    fn update_changed(&mut self, ctx: &Context, store: &ObjectStore, textures: &TextureStore, clipper: &Clipper, depth: bool) -> bool {
+        let opaque = depth.then_some(self.opaque);
+        let replaced = self.batch.replace_changed(
+            &store.changed,
+            opaque,
+            |idx| is_opaque(store, textures, clipper, idx),
+            |idx| rect_instance(store, idx),
+        );
+
+        if !replaced {
+            return false;
+        }
+
+        self.batch.flush(ctx);
+        true
+    }
+
+    /// Добавляет изображения в конец батча. Отрезки текстур и бинд
+    /// группы сэмплеров собираются по порядку, а инстансы
+    /// упаковываются отдельно (Параллельно если включена фича parallel)
+    fn push_all(&mut self, ctx: &Context, store: &ObjectStore, textures: &TextureStore, objects: &[usize]) {
+        let first = self.batch.cpu_buffer.len();
+
+        for (i, &idx) in objects.iter().enumerate() {
+            let slot = (first + i) as u32;
+            let texture = store.image_textures[idx];
+            let sampler = store.image_samplers.get(&idx).copied();
+
+            if let Some(options) = sampler {
+                if let Entry::Vacant(entry) = self.sampler_groups.entry((texture, options)) {
+                    if let Some(group) = textures.create_sampler_group(ctx, texture, options) {
+                        entry.insert(group);
+                    }
+                }
+            }
+
+            // Соседние изображения с одной текстурой рисуются
+            // одним вызовом
+            match self.runs.last_mut() {
+                Some(run) if run.texture == texture && run.sampler == sampler => {
+                    run.count += 1;
+                },
+                _ => self.runs.push(ImageRun { texture, sampler, start: slot, count: 1 }),
+            }
+        }
+
+        let instances = parallel::pack_chunks(objects, |idx, out| out.push(rect_instance(store, idx)));
+        self.batch.extend(objects, instances);
+    }
+
+    /// Отрезки с одной текстурой обрезаются по границам диапазона
+    fn draw_slots<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, shader: ShaderId, start: u32, count: u32) {
+        if let (Some(inst_buf), Some(pipeline)) = (&self.batch.gpu_buffer, res.shaders.get_pipeline(shader)) {
+            if self.runs.is_empty() || count == 0 {
+                return;
+            }
+
+            pass.set_pipeline(pipeline);
+            pass.set_vertex_buffer(0, &self.static_vbo);
+            pass.set_vertex_buffer(1, inst_buf);
+            pass.set_index_buffer(&self.static_ibo);
+
+            let end = start + count;
+
+            for run in self.runs.iter() {
+                let from = run.start.max(start);
+                let to = (run.start + run.count).min(end);
+
+                if from >= to {
+                    continue;
+                }
+
+                // Свой сэмплер объекта или сэмплер текстуры
+                let group = match run.sampler {
+                    Some(options) => self.sampler_groups.get(&(run.texture, options)),
+                    None => res.textures.get_group(run.texture),
+                };
+
+                if let Some(group) = group {
+                    pass.set_bind_group(1, group);
+                    pass.draw_indexed_instanced_range(6, from, to - from);
+                }
+            }
+        }
+    }
+}
+
+/// Изображение непрозрачно если непрозрачны и цвет, и все пиксели
+/// текстуры, а углы не скруглены. Обрезанные изображения рисуются в
+/// общем порядке, как и у RectBatch
+#[inline]
+fn is_opaque(store: &ObjectStore, textures: &TextureStore, clipper: &Clipper, idx: usize) -> bool {
+    store.final_color(idx).w >= 1.0
+        && store.rect_radii[idx] == Vec4::ZERO
+        && textures.is_opaque(store.image_textures[idx])
+        && !clipper.is_clipped(idx)
+}
\ No newline at end of file
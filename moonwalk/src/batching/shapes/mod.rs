@@ -1,4 +1,8 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
-pub mod rect;
\ No newline at end of file
+pub mod rect;
+pub mod image;
+pub mod path;
+pub mod gpu_rect;
+pub mod effect;
\ No newline at end of file
@@ -0,0 +1,290 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::ops::Range;
+use glam::Vec2;
+use rustc_hash::FxHashMap;
+
+use crate::easy_gpu::{Buffer, Context, RenderPass};
+use crate::rendering::vertex::{PathVertex, RectInstance};
+use crate::rendering::pipeline::{DepthMode, ShaderStore, PATH_SHADER};
+use crate::rendering::texture::TextureStore;
+use crate::objects::store::ObjectStore;
+use crate::objects::path::PathMesh;
+use crate::objects::ShaderId;
+use crate::batching::common;
+use crate::batching::culling::Culler;
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Resources, Submiter};
+use crate::batching::parallel;
+use crate::error::MoonWalkError;
+
+/// Место пути в общих буферах вершин и индексов
+struct PathRange {
+    vertices: Range<usize>,
+    indices: Range<usize>,
+}
+
+/// Батч векторных путей. В отличии от прямоугольников тут нет
+/// инстансов: сетки всех путей переводятся в мировые координаты
+/// и склеиваются в один индексированный буфер, который рисуется
+/// одним вызовом. Тесселяция тут не происходит, сетки уже готовы
+/// в хранилище объектов
+pub struct PathBatch {
+    vertices: Vec<PathVertex>,
+    indices: Vec<u32>,
+    vbo: Option<Buffer<PathVertex>>,
+    ibo: Option<Buffer<u32>>,
+
+    // Индексы путей отсортированные по z
+    order: Vec<usize>,
+
+    // Диапазоны вершин и индексов каждого пути в буфере, чтобы
+    // переписать только изменённые пути
+    ranges: FxHashMap<usize, PathRange>,
+
+    // Нарисованные пути в порядке отрисовки и начало индексов
+    // каждого из них (Последний элемент это общее число индексов)
+    drawn: Vec<usize>,
+    index_offsets: Vec<u32>,
+
+    shader: ShaderId,
+}
+
+impl PathBatch {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            vbo: None,
+            ibo: None,
+            order: Vec::new(),
+            ranges: FxHashMap::default(),
+            drawn: Vec::new(),
+            index_offsets: Vec::new(),
+            shader: ShaderId::default(),
+        }
+    }
+}
+
+impl Submiter for PathBatch {
+    /// У путей нет непрозрачной части, в режиме глубины они всегда
+    /// рисуются как полупрозрачные
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        _textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        let depth = if target.depth { DepthMode::Translucent } else { DepthMode::None };
+        self.shader = shaders.create_default_path(ctx, target, depth)?;
+
+        Ok(())
+    }
+
+    fn shader_files(&self) -> &[&'static str] {
+        &[PATH_SHADER.name]
+    }
+
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        let Frame { ctx, store, culler, .. } = *frame;
+
+        if !store.dirty {
+            // Сетка пути сменила размер: пересобираются только буферы
+            // путей. Сами пути и их порядок остались прежними
+            if !self.update_changed(ctx, store) {
+                self.rebuild(ctx, store, culler);
+            }
+
+            return false;
+        }
+
+        if store.z_dirty || self.order.len() != store.path_ids.len() {
+            common::sort_by_z(&mut self.order, &store.path_ids, &store.z_indices);
+        }
+
+        self.rebuild(ctx, store, culler);
+        true
+    }
+
+    /// Глобальные индексы путей в порядке отрисовки
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.drawn)
+    }
+
+    /// Рисует пути start..start + count (По порядку отрисовки)
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32) {
+        let pipeline = match res.shaders.get_pipeline(self.shader) {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+
+        if let (Some(vbo), Some(ibo)) = (&self.vbo, &self.ibo) {
+            let first = self.index_offsets.get(start as usize);
+            let last = self.index_offsets.get((start + count) as usize);
+
+            if let (Some(&first), Some(&last)) = (first, last) {
+                if last > first {
+                    pass.set_pipeline(pipeline);
+                    pass.set_vertex_buffer(0, vbo);
+                    pass.set_index_buffer(ibo);
+                    pass.draw_indexed_range(first, last - first);
+                }
+            }
+        }
+    }
+}
+
+impl PathBatch {
+    /// Раскладывает все видимые пути в буферах заново. Порядок
+    /// путей по z уже должен быть отсортирован
+    fn rebuild(&mut self, ctx: &Context, store: &ObjectStore, culler: &Culler) {
+        self.indices.clear();
+        self.ranges.clear();
+        self.drawn.clear();
+        self.index_offsets.clear();
+
+        let mut vertex_count = 0;
+        for i in 0..self.order.len() {
+            let idx = self.order[i];
+
+            if culler.in_view(idx) {
+                vertex_count = self.push_path(store, idx, vertex_count);
+            }
+        }
+
+        self.index_offsets.push(self.indices.len() as u32);
+
+        // Места вершин уже известны, поэтому переводить сетки в
+        // мировые координаты можно параллельно
+        self.vertices = parallel::pack_chunks(&self.drawn, |idx, out| {
+            if let Some(mesh) = store.path_meshes.get(&idx) {
+                out.extend(world_vertices(store, idx, mesh));
+            }
+        });
+
+        if self.indices.is_empty() {
+            return;
+        }
+
+        match &mut self.vbo {
+            Some(vbo) => vbo.update(ctx, &self.vertices),
+            None => self.vbo = Some(Buffer::vertex(ctx, &self.vertices)),
+        }
+
+        match &mut self.ibo {
+            Some(ibo) => ibo.update(ctx, &self.indices),
+            None => self.ibo = Some(Buffer::<u32>::index(ctx, &self.indices)),
+        }
+    }
+
+    /// Переписывает вершины изменённых путей на тех же местах. Если
+    /// у пути сменилась сетка, то его индексы тоже переписываются.
+    /// Возвращает false если сетка какого-то пути сменила размер
+    /// (Тогда места в буфере не хватит и буферы путей нужно собрать
+    /// заново)
+    fn update_changed(&mut self, ctx: &Context, store: &ObjectStore) -> bool {
+        let fits = store.changed.iter().all(|idx| {
+            match (self.ranges.get(idx), store.path_meshes.get(idx)) {
+                (Some(range), Some(mesh)) => {
+                    range.vertices.len() == mesh.vertices.len()
+                        && range.indices.len() == mesh.indices.len()
+                }
+                _ => true,
+            }
+        });
+
+        if !fits {
+            return false;
+        }
+
+        let (vbo, ibo) = match (&self.vbo, &self.ibo) {
+            (Some(vbo), Some(ibo)) => (vbo, ibo),
+            _ => return true,
+        };
+
+        for &idx in store.changed.iter() {
+            let (range, mesh) = match (self.ranges.get(&idx), store.path_meshes.get(&idx)) {
+                (Some(range), Some(mesh)) => (range, mesh),
+                _ => continue,
+            };
+
+            let target = &mut self.vertices[range.vertices.clone()];
+            for (slot, vertex) in target.iter_mut().zip(world_vertices(store, idx, mesh)) {
+                *slot = vertex;
+            }
+
+            vbo.write_at(ctx, range.vertices.start, &self.vertices[range.vertices.clone()]);
+
+            // Индексы меняются только вместе с сеткой, при обычном
+            // перемещении пути они совпадают
+            let base = range.vertices.start as u32;
+            let indices = &mut self.indices[range.indices.clone()];
+            if indices.iter().zip(mesh.indices.iter()).any(|(&index, &local)| index != local + base) {
+                for (slot, &local) in indices.iter_mut().zip(mesh.indices.iter()) {
+                    *slot = local + base;
+                }
+
+                ibo.write_at(ctx, range.indices.start, indices);
+            }
+        }
+
+        true
+    }
+
+    /// Раскладывает путь в буфере начиная с вершины base. Сами
+    /// вершины заполняются потом, тут только индексы и диапазоны.
+    /// Возвращает начало для следующего пути
+    fn push_path(&mut self, store: &ObjectStore, idx: usize, base: usize) -> usize {
+        let mesh = match store.path_meshes.get(&idx) {
+            Some(mesh) if store.world_visible()[idx] => mesh,
+            _ => return base,
+        };
+
+        let first = self.indices.len();
+        self.drawn.push(idx);
+        self.index_offsets.push(first as u32);
+
+        let end = base + mesh.vertices.len();
+        let offset = base as u32;
+        self.indices.extend(mesh.indices.iter().map(|i| i + offset));
+
+        self.ranges.insert(idx, PathRange {
+            vertices: base..end,
+            indices: first..self.indices.len(),
+        });
+
+        end
+    }
+}
+
+/// Переводит сетку пути в мировые координаты. Путь растягивается
+/// от естественного размера до размера объекта, а затем
+/// масштабируется и вращается вокруг точки опоры (Так же как
+/// прямоугольник в шейдере)
+pub fn world_vertices<'a>(store: &ObjectStore, idx: usize, mesh: &'a PathMesh) -> impl Iterator<Item = PathVertex> + 'a {
+    let size = store.sizes[idx];
+    let natural = mesh.natural_size;
+    let stretch = Vec2::new(
+        if natural.x > 0.0 { size.x / natural.x } else { 1.0 },
+        if natural.y > 0.0 { size.y / natural.y } else { 1.0 },
+    );
+
+    let pivot = size * store.pivots[idx];
+    let object_scale = store.world_scales()[idx];
+    let rotation = Vec2::from_angle(store.world_rotations()[idx]);
+    let offset = store.world_positions()[idx] + pivot;
+    let tint = store.final_color(idx);
+    let z = store.z_indices[idx];
+
+    mesh.vertices.iter().map(move |vertex| {
+        let local = (vertex.position * stretch - pivot) * object_scale;
+        let world = rotation.rotate(local) + offset;
+
+        PathVertex {
+            position: world.to_array(),
+            z,
+            color: RectInstance::pack_color((vertex.color * tint).to_array()),
+        }
+    })
+}
\ No newline at end of file
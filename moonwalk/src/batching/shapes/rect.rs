@@ -1,15 +1,43 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
+use glam::Vec4;
+
 use crate::easy_gpu::{Buffer, Context, RenderPass};
 use crate::rendering::vertex::{QuadVertex, RectInstance};
+use crate::rendering::pipeline::{DepthMode, ShaderStore, RECT_SHADER};
+use crate::rendering::texture::TextureStore;
 use crate::objects::store::ObjectStore;
-use crate::batching::common::BatchBuffer;
+use crate::objects::ShaderId;
+use crate::batching::common::{self, BatchBuffer};
+use crate::batching::submiter::{DrawItems, Frame, RenderTarget, Resources, Submiter};
+use crate::batching::parallel;
+use crate::batching::clipping::Clipper;
+use crate::error::MoonWalkError;
 
 pub struct RectBatch {
     static_vbo: Buffer<QuadVertex>,
     static_ibo: Buffer<u32>,
     batch: BatchBuffer<RectInstance>,
+
+    // Индексы прямоугольников отсортированные по z. Сортируется
+    // только когда z индексы грязные или появились новые объекты
+    order: Vec<usize>,
+
+    // Все объекты от ближних к дальним для непрозрачной части в
+    // режиме глубины. Сортируется только когда z индексы грязные,
+    // появились новые объекты или режим глубины только включили
+    opaque_order: Vec<usize>,
+
+    // Количество непрозрачных инстансов в начале буфера и был ли
+    // order собран в режиме глубины (Тогда в нём не все объекты)
+    opaque: u32,
+    sorted_depth: bool,
+
+    // Конвейер для общего порядка и для непрозрачной части (Есть
+    // только в режиме глубины)
+    shader: ShaderId,
+    opaque_shader: Option<ShaderId>,
 }
 
 impl RectBatch {
@@ -21,60 +49,207 @@ impl RectBatch {
             static_vbo,
             static_ibo,
             batch: BatchBuffer::new(),
+            order: Vec::new(),
+            opaque_order: Vec::new(),
+            opaque: 0,
+            sorted_depth: false,
+            shader: ShaderId::default(),
+            opaque_shader: None,
         }
     }
+}
 
-    pub fn prepare(&mut self, ctx: &Context, store: &ObjectStore) {
-        if !store.dirty {
-            return;
+impl Submiter for RectBatch {
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        _textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        if target.depth {
+            self.shader = shaders.create_default_rect(ctx, target, DepthMode::Translucent)?;
+            self.opaque_shader = Some(shaders.create_default_rect(ctx, target, DepthMode::Opaque)?);
+        } else {
+            self.shader = shaders.create_default_rect(ctx, target, DepthMode::None)?;
+            self.opaque_shader = None;
         }
 
-        self.batch.clear();
-        
-        for &global_id in store.rect_ids.iter() {
-            let idx = global_id.index();
-
-            self.batch.push(RectInstance {
-                // Упаковываем позицию и размер в один вектор
-                // для оптимизации
-                pos_size: [
-                    store.positions[idx].x,
-                    store.positions[idx].y,
-                    store.sizes[idx].x,
-                    store.sizes[idx].y,
-                ],
-
-                radii: store.rect_radii[idx].to_array(),
-
-                // Упаковываем z индекс и вращение
-                extra: [
-                    store.z_indices[idx],
-                    store.rotations[idx],
-                ],
-
-                color: RectInstance::pack_color(store.colors[idx].to_array()),
-            });
+        Ok(())
+    }
+
+    fn shader_files(&self) -> &[&'static str] {
+        &[RECT_SHADER.name]
+    }
+
+    /// В режиме буфера глубины непрозрачные прямоугольники идут в
+    /// начало буфера от ближних к дальним, а общий порядок нужен
+    /// только полупрозрачным. Возвращает true если батч был пересобран
+    /// целиком (Тогда меняется и общий порядок)
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        let Frame { ctx, store, culler, clipper, depth, .. } = *frame;
+
+        // Структура не менялась, переписываем только изменённые
+        // инстансы на их местах
+        if !store.dirty && self.update_changed(ctx, store, clipper, depth) {
+            return false;
         }
-        
-        // Сортировка объектов по Z идексу толкьо если Z индексы
-        // грязные (Проверяем флаг в хранилище объектов) 
-        if store.z_dirty { 
-            self.batch.sort();
+
+        self.batch.clear(store.positions.len());
+        let visible = store.world_visible();
+
+        if depth {
+            if store.z_dirty || !self.sorted_depth || self.opaque_order.len() != store.rect_ids.len() {
+                common::sort_front_to_back(&mut self.opaque_order, &store.rect_ids, &store.z_indices);
+            }
+
+            let opaque: Vec<usize> = self.opaque_order.iter()
+                .copied()
+                .filter(|&idx| visible[idx] && culler.in_view(idx) && !has_effect(store, idx) && is_opaque(store, clipper, idx))
+                .collect();
+
+            self.push_all(store, &opaque);
+
+            // Набор полупрозрачных мог поменяться без изменения z,
+            // поэтому сортируем его всегда (Обычно он маленький)
+            let translucent: Vec<_> = store.rect_ids.iter()
+                .copied()
+                .filter(|id| !is_opaque(store, clipper, id.index()))
+                .collect();
+
+            common::sort_by_z(&mut self.order, &translucent, &store.z_indices);
+        } else if store.z_dirty || self.sorted_depth || self.order.len() != store.rect_ids.len() {
+            // Сортировка объектов по Z идексу толкьо если Z индексы
+            // грязные (Проверяем флаг в хранилище объектов)
+            common::sort_by_z(&mut self.order, &store.rect_ids, &store.z_indices);
         }
 
+        self.opaque = self.batch.cpu_buffer.len() as u32;
+        self.sorted_depth = depth;
+
+        // Скрытые объекты и объекты за экраном вообще не
+        // попадают в батч. Прямоугольники с эффектом рисует
+        // батч эффектов
+        let drawn: Vec<usize> = self.order.iter()
+            .copied()
+            .filter(|&idx| visible[idx] && culler.in_view(idx) && !has_effect(store, idx))
+            .collect();
+
+        self.push_all(store, &drawn);
+
         self.batch.upload(ctx);
+        true
+    }
+
+    /// Рисует непрозрачную часть батча (Только в режиме глубины)
+    fn render_opaque<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>) {
+        if let Some(shader) = self.opaque_shader {
+            self.draw_slots(pass, res, shader, 0, self.opaque);
+        }
     }
 
-    pub fn render<'a>(&'a self, pass: &mut RenderPass<'a>) {
-        if let Some(inst_buf) = &self.batch.gpu_buffer {
-            let count = self.batch.cpu_buffer.len() as u32;
-            
+    /// Глобальные индексы полупрозрачных (Всех без режима глубины)
+    /// объектов в порядке отрисовки
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.batch.objects[self.opaque as usize..])
+    }
+
+    /// Рисует часть инстансов (start..start + count) из items().
+    /// Вызывается для каждого отрезка из общего порядка отрисовки
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32) {
+        self.draw_slots(pass, res, self.shader, self.opaque + start, count);
+    }
+}
+
+impl RectBatch {
+    /// Упаковывает инстансы объектов (Параллельно если включена
+    /// фича parallel) и добавляет их в конец батча по порядку
+    fn push_all(&mut self, store: &ObjectStore, objects: &[usize]) {
+        let instances = parallel::pack_chunks(objects, |idx, out| out.push(rect_instance(store, idx)));
+        self.batch.extend(objects, instances);
+    }
+
+    /// Переписывает изменённые инстансы. Возвращает false если
+    /// объект стал непрозрачным или наоборот (Тогда нужна полная
+    /// пересборка, так как он переезжает в другую часть буфера)
+    fn update_changed(&mut self, ctx: &Context, store: &ObjectStore, clipper: &Clipper, depth: bool) -> bool {
+        let opaque = depth.then_some(self.opaque);
+        let replaced = self.batch.replace_changed(
+            &store.changed,
+            opaque,
+            |idx| is_opaque(store, clipper, idx),
+            |idx| rect_instance(store, idx),
+        );
+
+        if !replaced {
+            return false;
+        }
+
+        self.batch.flush(ctx);
+        true
+    }
+
+    fn draw_slots<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, shader: ShaderId, start: u32, count: u32) {
+        if let (Some(inst_buf), Some(pipeline)) = (&self.batch.gpu_buffer, res.shaders.get_pipeline(shader)) {
             if count > 0 {
+                pass.set_pipeline(pipeline);
                 pass.set_vertex_buffer(0, &self.static_vbo);
                 pass.set_vertex_buffer(1, inst_buf);
                 pass.set_index_buffer(&self.static_ibo);
-                pass.draw_indexed_instanced(6, count);
+                pass.draw_indexed_instanced_range(6, start, count);
             }
         }
     }
+}
+
+/// Собирает инстанс прямоугольника (И изображения, у них общий
+/// формат) из мировых трансформаций объекта
+#[inline]
+pub fn rect_instance(store: &ObjectStore, idx: usize) -> RectInstance {
+    let position = store.world_positions()[idx];
+    let rotation = store.world_rotations()[idx];
+    let scale = store.world_scales()[idx];
+
+    RectInstance {
+        // Упаковываем позицию и размер в один вектор
+        // для оптимизации
+        pos_size: [
+            position.x,
+            position.y,
+            store.sizes[idx].x,
+            store.sizes[idx].y,
+        ],
+
+        radii: store.rect_radii[idx].to_array(),
+
+        // Упаковываем z индекс и вращение
+        extra: [
+            store.z_indices[idx],
+            rotation,
+        ],
+
+        color: RectInstance::pack_color(store.final_color(idx).to_array()),
+
+        // Упаковываем масштаб и точку опоры
+        transform: [
+            scale.x,
+            scale.y,
+            store.pivots[idx].x,
+            store.pivots[idx].y,
+        ],
+    }
+}
+
+/// Можно ли рисовать прямоугольник без смешивания. Скруглённые углы
+/// сглаживаются альфой, поэтому они всегда полупрозрачные. Обрезанные
+/// объекты рисуются в общем порядке (Обрезка выставляется между
+/// отрезками), поэтому тоже считаются полупрозрачными
+#[inline]
+fn is_opaque(store: &ObjectStore, clipper: &Clipper, idx: usize) -> bool {
+    store.final_color(idx).w >= 1.0 && store.rect_radii[idx] == Vec4::ZERO && !clipper.is_clipped(idx)
+}
+
+#[inline]
+fn has_effect(store: &ObjectStore, idx: usize) -> bool {
+    store.effects.contains_key(&idx)
 }
\ No newline at end of file
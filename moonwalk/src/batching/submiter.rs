@@ -3,13 +3,116 @@
 
 use crate::easy_gpu::{Context, RenderPass};
 use crate::objects::store::ObjectStore;
+use crate::batching::culling::Culler;
+use crate::batching::clipping::Clipper;
+use crate::rendering::pipeline::ShaderStore;
+use crate::rendering::texture::TextureStore;
+use crate::error::MoonWalkError;
+
+/// Параметры прохода рендера, под которые батч собирает свои
+/// конвейеры. Меняются редко (Например при включении буфера
+/// глубины), тогда у всех батчей снова вызывается configure
+#[derive(Debug, Clone, Copy)]
+pub struct RenderTarget {
+    /// Формат цвета поверхности
+    pub format: wgpu::TextureFormat,
+
+    /// Есть ли в проходе буфер глубины. Если есть, то конвейеру
+    /// нужен depth_stencil (PipelineBuilder::with_depth)
+    pub depth: bool,
+
+    /// Количество сэмплов MSAA (1 если выключено). Конвейер должен
+    /// собираться с тем же количеством (PipelineBuilder::with_samples)
+    pub samples: u32,
+
+    /// Есть ли в проходе трафарет обрезки (Включается сам, когда у
+    /// какого-то контейнера скруглённая или повёрнутая обрезка).
+    /// Если есть, то конвейеру нужен тест трафарета
+    /// (PipelineBuilder::with_stencil(Mask::read_at_least())), иначе
+    /// объекты батча не обрезаются фигурами
+    pub stencil: bool,
+}
+
+/// Данные кадра для подготовки батчей. Всё доступно только для
+/// чтения, поэтому батчи могут готовиться параллельно
+pub struct Frame<'a> {
+    pub ctx: &'a Context,
+    pub store: &'a ObjectStore,
+    pub textures: &'a TextureStore,
+    pub culler: &'a Culler,
+    pub clipper: &'a Clipper,
+    pub depth: bool,
+}
+
+/// Общие ресурсы во время рендера
+#[derive(Clone, Copy)]
+pub struct Resources<'a> {
+    pub shaders: &'a ShaderStore,
+    pub textures: &'a TextureStore,
+    pub depth: bool,
+    pub stencil: bool,
+}
+
+/// Что батч рисует в общем порядке отрисовки
+pub enum DrawItems<'a> {
+    /// Объекты хранилища (Глобальные индексы) в порядке отрисовки
+    /// батча. Они смешиваются с объектами других батчей по z
+    Objects(&'a [usize]),
+
+    /// Собственные элементы батча (Не объекты хранилища), которые
+    /// рисуются одним слоем на z. При равном z слой рисуется
+    /// поверх объектов
+    Layer { z: f32, count: u32 },
+}
+
+/// Интерфейс который обязан реализовать любой модуль батчинга.
+/// Батчи хранятся в общем реестре (BatchGroup), каждый владеет
+/// своими буферами, конвейерами и бинд группами. Через этот же
+/// интерфейс можно добавить свой примитив снаружи движка
+/// (MoonWalk::add_submiter).
+///
+/// Бинд группа 0 (Матрица проекции, лайаут ShaderStore::proj_layout)
+/// уже выставлена в проходе до вызова render_range
+pub trait Submiter: Send {
+    /// Собирает конвейеры под проход рендера. Вызывается при
+    /// добавлении батча и при смене параметров прохода
+    fn configure(
+        &mut self,
+        ctx: &Context,
+        shaders: &mut ShaderStore,
+        textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError>;
+
+    /// Встроенные шейдеры (Имена файлов из shaders/), из которых
+    /// батч собирает конвейеры. При горячей перезагрузке configure
+    /// вызывается только у батчей, чьи шейдеры изменились
+    fn shader_files(&self) -> &[&'static str] {
+        &[]
+    }
 
-/// Интерфейс который обязан реализовать любой модуль батчинга 
-pub trait Submiter {
     /// Читает данные из стора (Хранилще объектов) сортирует их
-    /// и заливает в gpu буферы
-    fn prepare(&mut self, ctx: &Context, store: &ObjectStore);
+    /// и заливает в gpu буферы. Возвращает true если изменился
+    /// состав или порядок элементов (Тогда общий порядок
+    /// отрисовки собирается заново)
+    fn prepare(&mut self, frame: &Frame) -> bool;
+
+    /// Элементы в порядке отрисовки батча
+    fn items(&self) -> DrawItems<'_>;
+
+    /// Отрезки батча в общем порядке (Начало и количество элементов
+    /// из items()), по возрастанию начала. Вызывается после каждой
+    /// пересборки общего порядка, до dispatch и render_range
+    fn set_runs(&mut self, _ctx: &Context, _runs: &[(u32, u32)]) {}
+
+    /// Записывает compute проходы до прохода рендера
+    fn dispatch(&self, _encoder: &mut wgpu::CommandEncoder) {}
+
+    /// Рисует элементы вне общего порядка, до всех остальных. Так
+    /// рисуются непрозрачные объекты в режиме буфера глубины
+    fn render_opaque<'a>(&'a self, _pass: &mut RenderPass<'a>, _res: Resources<'a>) {}
 
-    /// Записывает команды отрисовки в RenderPass
-    fn render<'a>(&'a self, pass: &mut RenderPass<'a>);
+    /// Рисует элементы start..start + count из items(). Батч сам
+    /// выставляет свой конвейер
+    fn render_range<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, start: u32, count: u32);
 }
\ No newline at end of file
@@ -7,9 +7,21 @@ use bytemuck::Pod;
 
 use crate::easy_gpu::context::Context;
 
+/// Меньше этого буфер не создаётся при росте и не ужимается
+const MIN_CAPACITY: usize = 64;
+
+/// Буфер ужимается если данных в нём меньше чем 1 / SHRINK_RATIO
+/// от вместимости SHRINK_AFTER обновлений подряд. Так разовый
+/// провал количества объектов не приводит к пересозданию туда-обратно
+const SHRINK_RATIO: usize = 4;
+const SHRINK_AFTER: u32 = 120;
+
 pub struct Buffer<T: Pod> {
     pub raw: wgpu::Buffer,
     pub count: u32,
+
+    // Сколько обновлений подряд буфер был почти пустым
+    underused: u32,
     _marker: PhantomData<T>,
 }
 
@@ -24,7 +36,7 @@ impl<T: Pod> Buffer<T> {
             contents: bytemuck::cast_slice(data),
             usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
         });
-        Buffer { raw, count: data.len() as u32, _marker: PhantomData }
+        Buffer { raw, count: data.len() as u32, underused: 0, _marker: PhantomData }
     }
 
     pub fn uniform(ctx: &Context, data: &T) -> Self {
@@ -35,31 +47,80 @@ impl<T: Pod> Buffer<T> {
         Self::create(ctx, data, wgpu::BufferUsages::STORAGE, "Storage Buffer")
     }
 
+    /// Пустой буфер на capacity элементов. Нужен когда данные пишутся
+    /// по частям или самим GPU (Например из compute шейдера)
+    pub fn with_capacity(ctx: &Context, capacity: usize, usage: wgpu::BufferUsages, label: &str) -> Self {
+        let size = (capacity.max(1) * std::mem::size_of::<T>()) as u64;
+        let raw = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { raw, count: 0, underused: 0, _marker: PhantomData }
+    }
+
     fn create(ctx: &Context, data: &[T], usage: wgpu::BufferUsages, label: &str) -> Self {
         let raw = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some(label),
             contents: bytemuck::cast_slice(data),
             usage: usage | wgpu::BufferUsages::COPY_DST,
         });
-        Self { raw, count: data.len() as u32, _marker: PhantomData }
+        Self { raw, count: data.len() as u32, underused: 0, _marker: PhantomData }
+    }
+
+    /// Сколько элементов вмещает буфер
+    pub fn capacity(&self) -> usize {
+        (self.raw.size() / std::mem::size_of::<T>() as u64) as usize
     }
 
+    /// Заменяет всё содержимое буфера. При нехватке места буфер растёт
+    /// в два раза (Или сразу до нужного размера), а если долго
+    /// остаётся почти пустым, то ужимается
     pub fn update(&mut self, ctx: &Context, data: &[T]) {
-        if data.len() as u64 > self.raw.size() / std::mem::size_of::<T>() as u64 {
-            let usage = self.raw.usage();
-            self.raw = ctx.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Resized Buffer"),
-                contents: bytemuck::cast_slice(data),
-                usage,
-            });
+        let capacity = self.capacity();
+
+        if data.len() > capacity {
+            let grown = data.len().max(capacity * 2).next_power_of_two().max(MIN_CAPACITY);
+            self.reallocate(ctx, grown);
+        } else if capacity > MIN_CAPACITY && data.len() * SHRINK_RATIO < capacity {
+            self.underused += 1;
+
+            if self.underused >= SHRINK_AFTER {
+                // Оставляем запас в два раза, чтобы сразу не расти снова
+                let shrunk = (data.len() * 2).next_power_of_two().max(MIN_CAPACITY);
+                self.reallocate(ctx, shrunk);
+            }
         } else {
-            ctx.queue.write_buffer(&self.raw, 0, bytemuck::cast_slice(data));
+            self.underused = 0;
         }
+
+        ctx.write_buffer(&self.raw, 0, bytemuck::cast_slice(data));
         self.count = data.len() as u32;
     }
     
+    /// Перезаписывает часть буфера начиная с элемента offset. Буфер
+    /// должен уже вмещать эти данные
+    pub fn write_at(&self, ctx: &Context, offset: usize, data: &[T]) {
+        let byte_offset = (offset * std::mem::size_of::<T>()) as u64;
+        ctx.write_buffer(&self.raw, byte_offset, bytemuck::cast_slice(data));
+    }
+
     pub fn update_one(&self, ctx: &Context, data: &T) {
-        ctx.queue.write_buffer(&self.raw, 0, bytemuck::cast_slice(&[*data]));
+        ctx.write_buffer(&self.raw, 0, bytemuck::cast_slice(&[*data]));
+    }
+
+    /// Пересоздаёт буфер на capacity элементов с тем же назначением
+    fn reallocate(&mut self, ctx: &Context, capacity: usize) {
+        self.raw = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Resized Buffer"),
+            size: (capacity * std::mem::size_of::<T>()) as u64,
+            usage: self.raw.usage(),
+            mapped_at_creation: false,
+        });
+
+        self.underused = 0;
     }
 
     pub fn instance(ctx: &Context, data: &[T]) -> Self {
@@ -0,0 +1,341 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use ktx2::{Format, Reader};
+
+use crate::easy_gpu::context::Context;
+use crate::easy_gpu::mipmap::MipmapGenerator;
+use crate::easy_gpu::sampler::SamplerOptions;
+use crate::easy_gpu::texture::Texture;
+
+/// Таблица блоков ASTC в том порядке, в котором они идут в
+/// спецификации Vulkan (И в KTX2). Каждый блок имеет UNORM и
+/// SRGB вариант, поэтому номер формата растёт на 2
+const ASTC_BLOCKS: [(u32, u32, wgpu::AstcBlock); 14] = [
+    (4, 4, wgpu::AstcBlock::B4x4),
+    (5, 4, wgpu::AstcBlock::B5x4),
+    (5, 5, wgpu::AstcBlock::B5x5),
+    (6, 5, wgpu::AstcBlock::B6x5),
+    (6, 6, wgpu::AstcBlock::B6x6),
+    (8, 5, wgpu::AstcBlock::B8x5),
+    (8, 6, wgpu::AstcBlock::B8x6),
+    (8, 8, wgpu::AstcBlock::B8x8),
+    (10, 5, wgpu::AstcBlock::B10x5),
+    (10, 6, wgpu::AstcBlock::B10x6),
+    (10, 8, wgpu::AstcBlock::B10x8),
+    (10, 10, wgpu::AstcBlock::B10x10),
+    (12, 10, wgpu::AstcBlock::B12x10),
+    (12, 12, wgpu::AstcBlock::B12x12),
+];
+
+/// Кодек блочного сжатия
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    // Десктопные форматы
+    Bc1,
+    Bc1a,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc7,
+
+    // Мобильные форматы
+    Etc2Rgb,
+    Etc2Rgba1,
+    Etc2Rgba8,
+    Astc(usize), // Индекс в таблице ASTC_BLOCKS
+}
+
+/// Сжатый формат текстуры из KTX2 файла
+#[derive(Debug, Clone, Copy)]
+struct CompressedFormat {
+    codec: Codec,
+    srgb: bool,
+}
+
+impl CompressedFormat {
+    fn from_ktx2(format: Format) -> Option<Self> {
+        let (codec, srgb) = match format {
+            Format::BC1_RGB_UNORM_BLOCK => (Codec::Bc1, false),
+            Format::BC1_RGB_SRGB_BLOCK => (Codec::Bc1, true),
+            Format::BC1_RGBA_UNORM_BLOCK => (Codec::Bc1a, false),
+            Format::BC1_RGBA_SRGB_BLOCK => (Codec::Bc1a, true),
+            Format::BC2_UNORM_BLOCK => (Codec::Bc2, false),
+            Format::BC2_SRGB_BLOCK => (Codec::Bc2, true),
+            Format::BC3_UNORM_BLOCK => (Codec::Bc3, false),
+            Format::BC3_SRGB_BLOCK => (Codec::Bc3, true),
+            Format::BC4_UNORM_BLOCK => (Codec::Bc4, false),
+            Format::BC5_UNORM_BLOCK => (Codec::Bc5, false),
+            Format::BC7_UNORM_BLOCK => (Codec::Bc7, false),
+            Format::BC7_SRGB_BLOCK => (Codec::Bc7, true),
+            Format::ETC2_R8G8B8_UNORM_BLOCK => (Codec::Etc2Rgb, false),
+            Format::ETC2_R8G8B8_SRGB_BLOCK => (Codec::Etc2Rgb, true),
+            Format::ETC2_R8G8B8A1_UNORM_BLOCK => (Codec::Etc2Rgba1, false),
+            Format::ETC2_R8G8B8A1_SRGB_BLOCK => (Codec::Etc2Rgba1, true),
+            Format::ETC2_R8G8B8A8_UNORM_BLOCK => (Codec::Etc2Rgba8, false),
+            Format::ETC2_R8G8B8A8_SRGB_BLOCK => (Codec::Etc2Rgba8, true),
+            _ => {
+                let first = Format::ASTC_4x4_UNORM_BLOCK.value();
+                let offset = format.value().checked_sub(first)? as usize;
+
+                if offset / 2 >= ASTC_BLOCKS.len() {
+                    return None;
+                }
+
+                (Codec::Astc(offset / 2), offset % 2 == 1)
+            },
+        };
+
+        Some(Self { codec, srgb })
+    }
+
+    fn wgpu_format(&self) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat as F;
+
+        match (self.codec, self.srgb) {
+            (Codec::Bc1 | Codec::Bc1a, false) => F::Bc1RgbaUnorm,
+            (Codec::Bc1 | Codec::Bc1a, true) => F::Bc1RgbaUnormSrgb,
+            (Codec::Bc2, false) => F::Bc2RgbaUnorm,
+            (Codec::Bc2, true) => F::Bc2RgbaUnormSrgb,
+            (Codec::Bc3, false) => F::Bc3RgbaUnorm,
+            (Codec::Bc3, true) => F::Bc3RgbaUnormSrgb,
+            (Codec::Bc4, _) => F::Bc4RUnorm,
+            (Codec::Bc5, _) => F::Bc5RgUnorm,
+            (Codec::Bc7, false) => F::Bc7RgbaUnorm,
+            (Codec::Bc7, true) => F::Bc7RgbaUnormSrgb,
+            (Codec::Etc2Rgb, false) => F::Etc2Rgb8Unorm,
+            (Codec::Etc2Rgb, true) => F::Etc2Rgb8UnormSrgb,
+            (Codec::Etc2Rgba1, false) => F::Etc2Rgb8A1Unorm,
+            (Codec::Etc2Rgba1, true) => F::Etc2Rgb8A1UnormSrgb,
+            (Codec::Etc2Rgba8, false) => F::Etc2Rgba8Unorm,
+            (Codec::Etc2Rgba8, true) => F::Etc2Rgba8UnormSrgb,
+            (Codec::Astc(index), srgb) => F::Astc {
+                block: ASTC_BLOCKS[index].2,
+                channel: if srgb {
+                    wgpu::AstcChannel::UnormSrgb
+                } else {
+                    wgpu::AstcChannel::Unorm
+                },
+            },
+        }
+    }
+
+    /// Формат в который распаковывается текстура на процессоре.
+    /// texture2ddecoder пишет пиксели в порядке BGRA
+    fn fallback_format(&self) -> wgpu::TextureFormat {
+        if self.srgb {
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Bgra8Unorm
+        }
+    }
+
+    /// Распаковывает один мип уровень в BGRA8
+    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+        let (w, h) = (width as usize, height as usize);
+        let mut pixels = vec![0u32; w * h];
+
+        let result = match self.codec {
+            Codec::Bc1 => texture2ddecoder::decode_bc1(data, w, h, &mut pixels),
+            Codec::Bc1a => texture2ddecoder::decode_bc1a(data, w, h, &mut pixels),
+            Codec::Bc2 => texture2ddecoder::decode_bc2(data, w, h, &mut pixels),
+            Codec::Bc3 => texture2ddecoder::decode_bc3(data, w, h, &mut pixels),
+            Codec::Bc4 => texture2ddecoder::decode_bc4(data, w, h, &mut pixels),
+            Codec::Bc5 => texture2ddecoder::decode_bc5(data, w, h, &mut pixels),
+            Codec::Bc7 => texture2ddecoder::decode_bc7(data, w, h, &mut pixels),
+            Codec::Etc2Rgb => texture2ddecoder::decode_etc2_rgb(data, w, h, &mut pixels),
+            Codec::Etc2Rgba1 => texture2ddecoder::decode_etc2_rgba1(data, w, h, &mut pixels),
+            Codec::Etc2Rgba8 => texture2ddecoder::decode_etc2_rgba8(data, w, h, &mut pixels),
+            Codec::Astc(index) => {
+                let (block_w, block_h, _) = ASTC_BLOCKS[index];
+                texture2ddecoder::decode_astc(
+                    data, w, h,
+                    block_w as usize, block_h as usize,
+                    &mut pixels,
+                )
+            },
+        };
+
+        result.map_err(|e| format!("Failed to decode {:?}: {}", self.codec, e))?;
+
+        // Каждый пиксель это u32 с байтами [b, g, r, a] в little endian
+        Ok(pixels.iter().flat_map(|p| p.to_le_bytes()).collect())
+    }
+}
+
+/// Несжатые форматы, которые можно загрузить из KTX2 как есть
+fn uncompressed_format(format: Format) -> Option<wgpu::TextureFormat> {
+    match format {
+        Format::R8G8B8A8_UNORM => Some(wgpu::TextureFormat::Rgba8Unorm),
+        Format::R8G8B8A8_SRGB => Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+        Format::B8G8R8A8_UNORM => Some(wgpu::TextureFormat::Bgra8Unorm),
+        Format::B8G8R8A8_SRGB => Some(wgpu::TextureFormat::Bgra8UnormSrgb),
+        _ => None,
+    }
+}
+
+/// Загружает текстуру из KTX2 файла. Сжатые форматы (BC7, ETC2, ASTC
+/// и другие) загружаются на GPU как есть, если устройство поддерживает
+/// соответствующую wgpu::Features. Иначе каждый мип уровень
+/// распаковывается на процессоре в BGRA8. Цепочка мип уровней берётся
+/// из файла, а если в файле только 1 уровень, то мипмапы генерируются
+/// (Только для распакованных текстур, в сжатые рендерить нельзя).
+///     [!] Суперсжатие (zstd, Basis Universal), кубмапы и массивы
+///         текстур не поддерживаются
+pub fn load_ktx2(
+    ctx: &Context,
+    data: &[u8],
+    sampler: &SamplerOptions,
+    mipmaps: &mut MipmapGenerator,
+) -> Result<Texture, String> {
+    let reader = Reader::new(data).map_err(|e| format!("Invalid KTX2 file: {}", e))?;
+    let header = reader.header();
+
+    if header.supercompression_scheme.is_some() {
+        return Err("Supercompressed KTX2 files are not supported".to_string());
+    }
+
+    if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count > 1 {
+        return Err("Only 2D KTX2 textures are supported".to_string());
+    }
+
+    let format = header.format.ok_or_else(||
+        "KTX2 file has no format (Basis Universal is not supported)".to_string()
+    )?;
+
+    let width = header.pixel_width;
+    let height = header.pixel_height.max(1);
+    let levels: Vec<&[u8]> = reader.levels().map(|level| level.data).collect();
+
+    if let Some(wgpu_format) = uncompressed_format(format) {
+        return upload_levels(ctx, &levels, width, height, wgpu_format, sampler, mipmaps);
+    }
+
+    let compressed = CompressedFormat::from_ktx2(format).ok_or_else(||
+        format!("KTX2 format {:?} is not supported", format)
+    )?;
+
+    let gpu_format = compressed.wgpu_format();
+    let supported = ctx.device.features().contains(gpu_format.required_features());
+
+    let reason = match fallback_reason(gpu_format, supported, width, height) {
+        Some(reason) => reason,
+        None => return Texture::from_levels(ctx, &levels, width, height, gpu_format, sampler),
+    };
+
+    log::info!("KTX2 format {:?} is decoded on CPU: {}", format, reason);
+
+    let mut decoded = Vec::with_capacity(levels.len());
+    for (level, level_data) in levels.iter().enumerate() {
+        let level_width = (width >> level).max(1);
+        let level_height = (height >> level).max(1);
+
+        decoded.push(compressed.decode(level_data, level_width, level_height)?);
+    }
+
+    let decoded: Vec<&[u8]> = decoded.iter().map(|level| level.as_slice()).collect();
+    upload_levels(ctx, &decoded, width, height, compressed.fallback_format(), sampler, mipmaps)
+}
+
+/// Почему сжатую текстуру нельзя загрузить на GPU как есть. None
+/// если устройство умеет этот формат и размер подходит
+fn fallback_reason(format: wgpu::TextureFormat, supported: bool, width: u32, height: u32) -> Option<String> {
+    if !supported {
+        return Some("the format is not supported by the device".to_string());
+    }
+
+    // wgpu требует, чтобы размер сжатой текстуры был кратен блоку
+    let (block_w, block_h) = format.block_dimensions();
+    if !width.is_multiple_of(block_w) || !height.is_multiple_of(block_h) {
+        return Some(format!("{}x{} is not a multiple of the {}x{} block", width, height, block_w, block_h));
+    }
+
+    None
+}
+
+/// Загружает несжатые уровни. Если уровень один, то мипмапы
+/// генерируются на GPU как у обычной текстуры
+fn upload_levels(
+    ctx: &Context,
+    levels: &[&[u8]],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    sampler: &SamplerOptions,
+    mipmaps: &mut MipmapGenerator,
+) -> Result<Texture, String> {
+    if levels.len() == 1 {
+        Texture::from_bytes(ctx, levels[0], width, height, format, sampler, Some(mipmaps))
+    } else {
+        Texture::from_levels(ctx, levels, width, height, format, sampler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ktx2_formats_map_to_blocks() {
+        let table = [
+            (Format::BC1_RGB_UNORM_BLOCK.value(), Some(((4, 4), 8, false))),
+            (Format::BC1_RGBA_SRGB_BLOCK.value(), Some(((4, 4), 8, true))),
+            (Format::BC3_UNORM_BLOCK.value(), Some(((4, 4), 16, false))),
+            (Format::BC4_UNORM_BLOCK.value(), Some(((4, 4), 8, false))),
+            (Format::BC7_SRGB_BLOCK.value(), Some(((4, 4), 16, true))),
+            (Format::ETC2_R8G8B8_UNORM_BLOCK.value(), Some(((4, 4), 8, false))),
+            (Format::ETC2_R8G8B8A8_SRGB_BLOCK.value(), Some(((4, 4), 16, true))),
+            (Format::ASTC_4x4_UNORM_BLOCK.value(), Some(((4, 4), 16, false))),
+            (Format::ASTC_4x4_SRGB_BLOCK.value(), Some(((4, 4), 16, true))),
+            (Format::ASTC_5x4_UNORM_BLOCK.value(), Some(((5, 4), 16, false))),
+            (Format::ASTC_8x6_SRGB_BLOCK.value(), Some(((8, 6), 16, true))),
+            (Format::ASTC_10x5_UNORM_BLOCK.value(), Some(((10, 5), 16, false))),
+            (Format::ASTC_12x10_UNORM_BLOCK.value(), Some(((12, 10), 16, false))),
+            (Format::ASTC_12x12_SRGB_BLOCK.value(), Some(((12, 12), 16, true))),
+
+            // Сразу после таблицы ASTC, HDR ASTC и несжатые форматы
+            (Format::ASTC_12x12_SRGB_BLOCK.value() + 1, None),
+            (Format::ASTC_4x4_SFLOAT_BLOCK.value(), None),
+            (Format::R8G8B8A8_UNORM.value(), None),
+        ];
+
+        for (value, expected) in table {
+            let format = Format::new(value).expect("Format value must not be zero");
+            let actual = CompressedFormat::from_ktx2(format).map(|compressed| {
+                let gpu_format = compressed.wgpu_format();
+                (gpu_format.block_dimensions(), gpu_format.block_copy_size(None).unwrap_or(0), compressed.srgb)
+            });
+
+            assert_eq!(actual, expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn every_astc_block_is_mapped() {
+        for (index, &(width, height, block)) in ASTC_BLOCKS.iter().enumerate() {
+            let value = Format::ASTC_4x4_UNORM_BLOCK.value() + index as u32 * 2;
+            let format = Format::new(value).map(CompressedFormat::from_ktx2);
+
+            match format.flatten().map(|f| f.wgpu_format()) {
+                Some(wgpu::TextureFormat::Astc { block: actual, .. }) => assert_eq!(actual, block),
+                other => panic!("ASTC {}x{} mapped to {:?}", width, height, other),
+            }
+        }
+    }
+
+    #[test]
+    fn fallback_reports_the_real_reason() {
+        let bc7 = wgpu::TextureFormat::Bc7RgbaUnorm;
+
+        assert_eq!(fallback_reason(bc7, true, 256, 128), None);
+        assert_eq!(
+            fallback_reason(bc7, false, 256, 128).as_deref(),
+            Some("the format is not supported by the device"),
+        );
+        assert_eq!(
+            fallback_reason(bc7, true, 30, 18).as_deref(),
+            Some("30x18 is not a multiple of the 4x4 block"),
+        );
+    }
+}
\ No newline at end of file
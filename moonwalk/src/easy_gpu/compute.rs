@@ -0,0 +1,60 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::borrow::Cow;
+
+use crate::easy_gpu::context::Context;
+
+/// Вычислительный конвейер (Compute shader)
+pub struct ComputePipeline {
+    pub raw: wgpu::ComputePipeline,
+}
+
+impl ComputePipeline {
+    pub fn new(
+        ctx: &Context,
+        shader_src: &str,
+        entry_point: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> Self {
+        let shader = ctx.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Compute Shader Module"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_src)),
+        });
+
+        let layout = ctx.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute Pipeline Layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        let raw = ctx.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some(entry_point),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self { raw }
+    }
+
+    /// Есть ли у устройства вычислительные шейдеры (На старом
+    /// OpenGL ES их нет)
+    pub fn supported(ctx: &Context) -> bool {
+        ctx.adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+    }
+
+    /// Записывает вычислительный проход с одной бинд группой
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, bind_group: &wgpu::BindGroup, workgroups: u32) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("EasyGPU Compute Pass"),
+            timestamp_writes: None,
+        });
+
+        pass.set_pipeline(&self.raw);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups(workgroups, 1, 1);
+    }
+}
\ No newline at end of file
@@ -4,6 +4,8 @@
 use std::sync::Arc;
 use wgpu::rwh::{HasDisplayHandle, HasWindowHandle};
 
+use crate::easy_gpu::upload::UploadBelt;
+
 #[derive(Clone)]
 pub struct Context {
     pub device: Arc<wgpu::Device>,
@@ -13,6 +15,7 @@ pub struct Context {
     pub adapter_info: wgpu::AdapterInfo,
     pub instance: Arc<wgpu::Instance>,
     pub adapter: Arc<wgpu::Adapter>,
+    pub uploads: Arc<UploadBelt>,
 }
 
 impl Context {
@@ -29,9 +32,18 @@ impl Context {
             force_fallback_adapter: false,
         }).await.expect("No suitable GPU adapter found");
 
+        // Сжатые форматы текстур включаем только если адаптер их
+        // поддерживает. Если нет, то сжатые текстуры распаковываются
+        // на процессоре при загрузке
+        let compression = adapter.features() & (
+            wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC
+        );
+
         let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
             label: Some("EasyGPU Device"),
-            required_features: wgpu::Features::empty(),
+            required_features: compression,
             required_limits: wgpu::Limits::default(),
             memory_hints: wgpu::MemoryHints::Performance,
         }, None).await.expect("Failed to create device");
@@ -63,6 +75,7 @@ impl Context {
             adapter_info: adapter.get_info(),
             instance: Arc::new(instance),
             adapter: Arc::new(adapter),
+            uploads: Arc::new(UploadBelt::new()),
         };
         
         ctx.configure_surface();
@@ -89,7 +102,20 @@ impl Context {
         })
     }
 
+    /// Записывает данные в буфер через кольцо промежуточных буферов
+    pub fn write_buffer(&self, target: &wgpu::Buffer, offset: wgpu::BufferAddress, data: &[u8]) {
+        self.uploads.write(&self.device, target, offset, data);
+    }
+
+    /// Отправляет команды. Накопленные заливки данных идут первыми
     pub fn submit(&self, encoder: wgpu::CommandEncoder) {
-        self.queue.submit(std::iter::once(encoder.finish()));
+        let commands = encoder.finish();
+
+        match self.uploads.finish() {
+            Some(uploads) => self.queue.submit([uploads, commands]),
+            None => self.queue.submit(std::iter::once(commands)),
+        };
+
+        self.uploads.recall();
     }
 }
\ No newline at end of file
@@ -1,34 +1,68 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
+/// Состояния трафарета для конвейеров. Опорное значение задаётся в
+/// проходе (RenderPass::set_stencil_reference)
 pub struct Mask;
 
 impl Mask {
+    /// Записывает опорное значение везде, где рисует конвейер
     pub fn write() -> wgpu::StencilState {
-        wgpu::StencilState {
-            front: wgpu::StencilFaceState {
-                compare: wgpu::CompareFunction::Always,
-                fail_op: wgpu::StencilOperation::Keep,
-                depth_fail_op: wgpu::StencilOperation::Keep,
-                pass_op: wgpu::StencilOperation::Replace,
-            },
-            back: wgpu::StencilFaceState::default(),
-            read_mask: 0xFF,
-            write_mask: 0xFF,
-        }
+        both_faces(wgpu::CompareFunction::Always, wgpu::StencilOperation::Replace, 0xFF)
     }
 
+    /// Рисует только там, где в трафарете опорное значение
     pub fn read_equal() -> wgpu::StencilState {
-        wgpu::StencilState {
-            front: wgpu::StencilFaceState {
-                compare: wgpu::CompareFunction::Equal,
-                fail_op: wgpu::StencilOperation::Keep,
-                depth_fail_op: wgpu::StencilOperation::Keep,
-                pass_op: wgpu::StencilOperation::Keep,
-            },
-            back: wgpu::StencilFaceState::default(),
-            read_mask: 0xFF,
-            write_mask: 0x00,
+        both_faces(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep, 0x00)
+    }
+
+    /// Увеличивает значение на 1 там, где оно равно опорному. Так
+    /// вложенные фигуры пересекаются: значение ref + 1 остаётся
+    /// только внутри обеих
+    pub fn increment_equal() -> wgpu::StencilState {
+        both_faces(wgpu::CompareFunction::Equal, wgpu::StencilOperation::IncrementClamp, 0xFF)
+    }
+
+    /// Рисует там, где значение в трафарете не меньше опорного. С
+    /// опорным 0 проходит везде, поэтому один конвейер рисует и
+    /// обрезанные, и обычные объекты
+    pub fn read_at_least() -> wgpu::StencilState {
+        // Сравнивается опорное значение со значением в трафарете:
+        // ref <= stencil
+        both_faces(wgpu::CompareFunction::LessEqual, wgpu::StencilOperation::Keep, 0x00)
+    }
+}
+
+/// Одинаковое состояние для обеих сторон. Конвейеры рисуются без
+/// отсечения граней, а объект с отрицательным масштабом разворачивается
+/// к камере задней стороной, и трафарет должен работать для него так же
+fn both_faces(compare: wgpu::CompareFunction, pass_op: wgpu::StencilOperation, write_mask: u32) -> wgpu::StencilState {
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+
+    wgpu::StencilState {
+        front: face,
+        back: face,
+        read_mask: 0xFF,
+        write_mask,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrored_faces_use_the_same_stencil_state() {
+        let states = [Mask::write(), Mask::read_equal(), Mask::increment_equal(), Mask::read_at_least()];
+
+        for state in states {
+            assert_eq!(state.front, state.back);
+            assert_ne!(state.back, wgpu::StencilFaceState::default());
         }
     }
 }
\ No newline at end of file
@@ -1,9 +1,13 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec3, Vec4};
 use bytemuck::{Pod, Zeroable};
 
+/// Граница z индексов объектов в режиме буфера глубины. Объекты за
+/// ней отсекаются
+pub const Z_RANGE: f32 = 10_000.0;
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct MatrixUniform {
@@ -17,6 +21,9 @@ pub struct MatrixStack {
     pub view: Mat4,
     pub model: Mat4,
     stack: Vec<Mat4>,
+
+    // Попадает ли z в глубину проекции (Смотрите set_depth)
+    depth: bool,
 }
 
 impl MatrixStack {
@@ -26,11 +33,25 @@ impl MatrixStack {
             view: Mat4::IDENTITY,
             model: Mat4::IDENTITY,
             stack: Vec::with_capacity(16),
+            depth: false,
         }
     }
 
+    /// Ортографическая проекция. Глубина зависит от z только в
+    /// режиме буфера глубины (Смотрите set_depth)
     pub fn set_ortho(&mut self, width: f32, height: f32) {
-        self.projection = Mat4::orthographic_lh(0.0, width, height, 0.0, -1.0, 1.0);
+        self.projection = Mat4::orthographic_lh(0.0, width, height, 0.0, -Z_RANGE, Z_RANGE);
+        self.projection.z_axis = depth_axis(self.depth);
+    }
+
+    /// В режиме буфера глубины z индексы объектов попадают в
+    /// глубину, поэтому диапазон z это -Z_RANGE..Z_RANGE (Больший z
+    /// ближе к зрителю), а объекты за ним отсекаются. Без него
+    /// глубина всех вершин одна и та же (0.5), порядок задаёт только
+    /// сортировка и z может быть любым
+    pub fn set_depth(&mut self, enabled: bool) {
+        self.depth = enabled;
+        self.projection.z_axis = depth_axis(enabled);
     }
 
     pub fn set_identity(&mut self) {
@@ -66,4 +87,48 @@ impl MatrixStack {
             projection: self.projection.to_cols_array_2d(),
         }
     }
+}
+
+/// Столбец проекции, который переводит z в глубину. Он не зависит
+/// от размеров экрана
+fn depth_axis(depth: bool) -> Vec4 {
+    if depth {
+        Mat4::orthographic_lh(0.0, 1.0, 1.0, 0.0, -Z_RANGE, Z_RANGE).z_axis
+    } else {
+        Vec4::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth_of(stack: &MatrixStack, z: f32) -> f32 {
+        let clip = stack.projection * Vec4::new(100.0, 100.0, z, 1.0);
+        clip.z / clip.w
+    }
+
+    #[test]
+    fn z_range_applies_only_with_depth() {
+        let mut stack = MatrixStack::new();
+        stack.set_ortho(800.0, 600.0);
+
+        // Без буфера глубины любой z остаётся внутри глубины
+        for z in [-1e6, 0.0, Z_RANGE * 2.0, 1e6] {
+            assert_eq!(depth_of(&stack, z), 0.5);
+        }
+
+        stack.set_depth(true);
+        assert!(depth_of(&stack, -Z_RANGE).abs() < 1e-6);
+        assert!((depth_of(&stack, Z_RANGE) - 1.0).abs() < 1e-6);
+        assert!(depth_of(&stack, Z_RANGE * 2.0) > 1.0);
+
+        // Смена размера экрана сохраняет режим
+        stack.set_ortho(1024.0, 768.0);
+        assert!((depth_of(&stack, 0.0) - 0.5).abs() < 1e-6);
+        assert!(depth_of(&stack, Z_RANGE * 2.0) > 1.0);
+
+        stack.set_depth(false);
+        assert_eq!(depth_of(&stack, Z_RANGE * 2.0), 0.5);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::collections::HashMap;
+
+use crate::easy_gpu::context::Context;
+use crate::easy_gpu::pipeline::{Pipeline, PipelineBuilder};
+use crate::easy_gpu::texture::Texture;
+
+/// Генератор мипмапов на GPU. Каждый следующий мип уровень рисуется
+/// из предыдущего через blit пайплайн с линейной фильтрацией.
+/// Пайплайны кэшируются по формату текстуры, поэтому генератор
+/// нужно создавать один раз и переиспользовать.
+pub struct MipmapGenerator {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    pipelines: HashMap<wgpu::TextureFormat, Pipeline>,
+}
+
+impl MipmapGenerator {
+    pub fn new(ctx: &Context) -> Self {
+        let layout = ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Mipmap Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            layout,
+            sampler,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Можно ли сгенерировать мипмапы для формата. Формат должен
+    /// поддерживать фильтрацию и рендеринг в него
+    pub fn supports(ctx: &Context, format: wgpu::TextureFormat) -> bool {
+        let features = ctx.adapter.get_texture_format_features(format);
+
+        features.allowed_usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            && features.flags.contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+    }
+
+    /// Заполняет все мип уровни текстуры начиная с первого. Нулевой
+    /// уровень уже должен быть загружен. Текстура должна быть создана
+    /// с флагом RENDER_ATTACHMENT
+    pub fn generate(&mut self, ctx: &Context, texture: &Texture) {
+        if texture.mip_levels <= 1 {
+            return;
+        }
+
+        let format = texture.format;
+        if !self.pipelines.contains_key(&format) {
+            let pipeline = PipelineBuilder::new(ctx, include_str!("../shaders/blit.wgsl"))
+                .no_blend()
+                .build(format, &[&self.layout]);
+
+            self.pipelines.insert(format, pipeline);
+        }
+
+        let pipeline = &self.pipelines[&format];
+        let mut encoder = ctx.create_encoder();
+
+        for level in 1..texture.mip_levels {
+            let source = texture.mip_view(level - 1);
+            let target = texture.mip_view(level);
+
+            let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Bind Group"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(&pipeline.raw);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        ctx.submit(encoder);
+    }
+}
+
+/// Количество мип уровней для полной цепочки (до 1x1)
+pub fn full_mip_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
\ No newline at end of file
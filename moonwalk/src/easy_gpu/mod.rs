@@ -9,12 +9,21 @@ pub mod matrix;
 pub mod framebuffer;
 pub mod pass;
 pub mod mask;
+pub mod sampler;
+pub mod mipmap;
+pub mod compressed;
+pub mod compute;
+pub mod upload;
 
 pub use context::Context;
 pub use buffer::Buffer;
 pub use texture::Texture;
-pub use pipeline::{Pipeline, PipelineBuilder};
+pub use pipeline::{Pipeline, PipelineBuilder, DEPTH_FORMAT};
 pub use matrix::{MatrixStack, MatrixUniform};
 pub use framebuffer::Framebuffer;
 pub use pass::RenderPass;
-pub use mask::Mask;
\ No newline at end of file
+pub use mask::Mask;
+pub use sampler::{SamplerOptions, SamplerPreset, AddressMode};
+pub use mipmap::MipmapGenerator;
+pub use compressed::load_ktx2;
+pub use compute::ComputePipeline;
\ No newline at end of file
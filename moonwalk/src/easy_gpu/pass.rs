@@ -12,6 +12,40 @@ pub struct RenderPass<'a> {
 
 impl<'a> RenderPass<'a> {
     pub fn new(encoder: &'a mut wgpu::CommandEncoder, view: &'a wgpu::TextureView, clear_color: Option<wgpu::Color>) -> Self {
+        Self::with_depth(encoder, view, clear_color, None)
+    }
+
+    /// Проход с буфером глубины и трафарета. Глубина очищается в 0
+    /// (Дальше всего), трафарет тоже в 0
+    pub fn with_depth(
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        depth_view: Option<&'a wgpu::TextureView>,
+    ) -> Self {
+        Self::begin(encoder, view, None, clear_color, depth_view)
+    }
+
+    /// Проход с MSAA. Рисование идёт в мультисэмпловый msaa_view,
+    /// который в конце прохода сводится в view (Например текстуру
+    /// поверхности). Буфер глубины должен иметь столько же сэмплов
+    pub fn multisampled(
+        encoder: &'a mut wgpu::CommandEncoder,
+        msaa_view: &'a wgpu::TextureView,
+        view: &'a wgpu::TextureView,
+        clear_color: Option<wgpu::Color>,
+        depth_view: Option<&'a wgpu::TextureView>,
+    ) -> Self {
+        Self::begin(encoder, msaa_view, Some(view), clear_color, depth_view)
+    }
+
+    fn begin(
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+        resolve_target: Option<&'a wgpu::TextureView>,
+        clear_color: Option<wgpu::Color>,
+        depth_view: Option<&'a wgpu::TextureView>,
+    ) -> Self {
         let load_op = if let Some(color) = clear_color {
             wgpu::LoadOp::Clear(color)
         } else {
@@ -22,13 +56,29 @@ impl<'a> RenderPass<'a> {
             label: Some("EasyGPU Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view,
-                resolve_target: None,
+                resolve_target,
                 ops: wgpu::Operations {
                     load: load_op,
-                    store: wgpu::StoreOp::Store,
+
+                    // После сведения сэмплы больше не нужны
+                    store: if resolve_target.is_some() {
+                        wgpu::StoreOp::Discard
+                    } else {
+                        wgpu::StoreOp::Store
+                    },
                 },
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: depth_view.map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+            }),
             timestamp_writes: None,
             occlusion_query_set: None,
         });
@@ -50,6 +100,13 @@ impl<'a> RenderPass<'a> {
         self.raw.set_vertex_buffer(slot, buffer.raw.slice(..));
     }
 
+    /// Вершинный буфер начиная с элемента first. Так инстансы
+    /// отрезка читаются с его начала без first_instance
+    pub fn set_vertex_buffer_from<T: Pod>(&mut self, slot: u32, buffer: &'a Buffer<T>, first: u32) {
+        let offset = first as u64 * std::mem::size_of::<T>() as u64;
+        self.raw.set_vertex_buffer(slot, buffer.raw.slice(offset..));
+    }
+
     pub fn set_index_buffer(&mut self, buffer: &'a Buffer<u32>) {
         self.raw.set_index_buffer(buffer.raw.slice(..), wgpu::IndexFormat::Uint32);
     }
@@ -58,6 +115,11 @@ impl<'a> RenderPass<'a> {
         self.raw.set_scissor_rect(x, y, w, h);
     }
 
+    /// Опорное значение для теста и записи трафарета (Смотрите Mask)
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.raw.set_stencil_reference(reference);
+    }
+
     pub fn draw(&mut self, vertex_count: u32) {
         self.raw.draw(0..vertex_count, 0..1);
     }
@@ -66,6 +128,23 @@ impl<'a> RenderPass<'a> {
         self.raw.draw_indexed(0..index_count, 0, 0..1);
     }
 
+    /// Рисует с параметрами из буфера (DrawIndexedIndirectArgs),
+    /// которые заполнил GPU
+    pub fn draw_indexed_indirect(&mut self, indirect: &'a Buffer<u32>) {
+        self.raw.draw_indexed_indirect(&indirect.raw, 0);
+    }
+
+    /// Как draw_indexed_indirect, но параметры лежат в буфере
+    /// начиная со слова offset
+    pub fn draw_indexed_indirect_at(&mut self, indirect: &'a Buffer<u32>, offset: u32) {
+        self.raw.draw_indexed_indirect(&indirect.raw, offset as u64 * 4);
+    }
+
+    /// Рисует часть индексов из буфера (first..first + count)
+    pub fn draw_indexed_range(&mut self, first_index: u32, index_count: u32) {
+        self.raw.draw_indexed(first_index..first_index + index_count, 0, 0..1);
+    }
+
     pub fn draw_instanced(&mut self, vertex_count: u32, instance_count: u32) {
         self.raw.draw(0..vertex_count, 0..instance_count);
     }
@@ -73,4 +152,9 @@ impl<'a> RenderPass<'a> {
     pub fn draw_indexed_instanced(&mut self, index_count: u32, instance_count: u32) {
         self.raw.draw_indexed(0..index_count, 0, 0..instance_count);
     }
+
+    /// Рисует часть инстансов из буфера (first..first + count)
+    pub fn draw_indexed_instanced_range(&mut self, index_count: u32, first_instance: u32, instance_count: u32) {
+        self.raw.draw_indexed(0..index_count, 0, first_instance..first_instance + instance_count);
+    }
 }
\ No newline at end of file
@@ -5,6 +5,10 @@ use std::borrow::Cow;
 
 use crate::easy_gpu::context::Context;
 
+/// Формат буфера глубины и трафарета. Один и тот же для всех
+/// конвейеров, чтобы их можно было смешивать в одном проходе
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
 pub struct Pipeline {
     pub raw: wgpu::RenderPipeline,
 }
@@ -12,10 +16,18 @@ pub struct Pipeline {
 pub struct PipelineBuilder<'a> {
     ctx: &'a Context,
     shader_src: &'a str,
+    vertex_entry: &'a str,
+    fragment_entry: &'a str,
     vertex_layouts: Vec<wgpu::VertexBufferLayout<'a>>,
     topology: wgpu::PrimitiveTopology,
     blend: Option<wgpu::BlendState>,
+    write_mask: wgpu::ColorWrites,
+    // Дополнительные цвета (@location(1) и дальше) со своим смешиванием
+    extra_targets: Vec<(wgpu::TextureFormat, Option<wgpu::BlendState>)>,
     stencil: Option<wgpu::StencilState>,
+    depth: Option<(bool, wgpu::CompareFunction)>,
+    depth_format: wgpu::TextureFormat,
+    samples: u32,
 }
 
 impl<'a> PipelineBuilder<'a> {
@@ -23,13 +35,27 @@ impl<'a> PipelineBuilder<'a> {
         Self {
             ctx,
             shader_src,
+            vertex_entry: "vs_main",
+            fragment_entry: "fs_main",
             vertex_layouts: Vec::new(),
             topology: wgpu::PrimitiveTopology::TriangleList,
             blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+            extra_targets: Vec::new(),
             stencil: None,
+            depth: None,
+            depth_format: DEPTH_FORMAT,
+            samples: 1,
         }
     }
 
+    /// Точки входа шейдера. По умолчанию vs_main и fs_main
+    pub fn with_entry_points(mut self, vertex: &'a str, fragment: &'a str) -> Self {
+        self.vertex_entry = vertex;
+        self.fragment_entry = fragment;
+        self
+    }
+
     pub fn add_layout(mut self, layout: wgpu::VertexBufferLayout<'a>) -> Self {
         self.vertex_layouts.push(layout);
         self
@@ -45,11 +71,53 @@ impl<'a> PipelineBuilder<'a> {
         self
     }
 
+    /// Включает тест глубины. write это запись глубины (Для
+    /// непрозрачных объектов), compare это функция сравнения
+    pub fn with_depth(mut self, write: bool, compare: wgpu::CompareFunction) -> Self {
+        self.depth = Some((write, compare));
+        self
+    }
+
+    /// Формат буфера глубины и трафарета. Должен совпадать с
+    /// форматом вложения прохода (По умолчанию DEPTH_FORMAT)
+    pub fn with_depth_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.depth_format = format;
+        self
+    }
+
+    /// Количество сэмплов на пиксель (MSAA). Должно совпадать с
+    /// количеством сэмплов у целей прохода
+    pub fn with_samples(mut self, count: u32) -> Self {
+        self.samples = count;
+        self
+    }
+
+    /// Смешивание основного цвета (@location(0)). None выключает
+    /// смешивание
+    pub fn with_blend(mut self, blend: Option<wgpu::BlendState>) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Какие каналы основного цвета записываются. Пустая маска
+    /// нужна конвейерам, которые пишут только в трафарет
+    pub fn with_color_writes(mut self, mask: wgpu::ColorWrites) -> Self {
+        self.write_mask = mask;
+        self
+    }
+
     pub fn no_blend(mut self) -> Self {
         self.blend = None;
         self
     }
 
+    /// Добавляет ещё один цвет после основного. Цели идут по порядку
+    /// вызовов: первая добавленная это @location(1) и так далее
+    pub fn add_color_target(mut self, format: wgpu::TextureFormat, blend: Option<wgpu::BlendState>) -> Self {
+        self.extra_targets.push((format, blend));
+        self
+    }
+
     pub fn build(self, target_format: wgpu::TextureFormat, bind_group_layouts: &[&wgpu::BindGroupLayout]) -> Pipeline {
         let shader = self.ctx.device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader Module"),
@@ -62,23 +130,44 @@ impl<'a> PipelineBuilder<'a> {
             push_constant_ranges: &[],
         });
 
+        // Буфер глубины нужен если включена глубина или трафарет
+        let depth_stencil = if self.depth.is_some() || self.stencil.is_some() {
+            let (write, compare) = self.depth.unwrap_or((false, wgpu::CompareFunction::Always));
+
+            Some(wgpu::DepthStencilState {
+                format: self.depth_format,
+                depth_write_enabled: write,
+                depth_compare: compare,
+                stencil: self.stencil.clone().unwrap_or_default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+        } else {
+            None
+        };
+
+        // Основной цвет всегда первый, его формат передаётся в build
+        let targets: Vec<Option<wgpu::ColorTargetState>> = std::iter::once((target_format, self.blend, self.write_mask))
+            .chain(self.extra_targets.iter().map(|&(format, blend)| (format, blend, wgpu::ColorWrites::ALL)))
+            .map(|(format, blend, write_mask)| Some(wgpu::ColorTargetState {
+                format,
+                blend,
+                write_mask,
+            }))
+            .collect();
+
         let raw = self.ctx.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Render Pipeline"),
             layout: Some(&layout),
             vertex: wgpu::VertexState {
                 module: &shader,
-                entry_point: Some("vs_main"),
+                entry_point: Some(self.vertex_entry),
                 buffers: &self.vertex_layouts,
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: target_format,
-                    blend: self.blend,
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
+                entry_point: Some(self.fragment_entry),
+                targets: &targets,
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
@@ -90,14 +179,12 @@ impl<'a> PipelineBuilder<'a> {
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: self.stencil.map(|s| wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth24PlusStencil8,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: s,
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState::default(),
+            depth_stencil,
+            multisample: wgpu::MultisampleState {
+                count: self.samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
             multiview: None,
             cache: None,
         });
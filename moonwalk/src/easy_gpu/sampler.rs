@@ -0,0 +1,75 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use crate::easy_gpu::context::Context;
+
+/// Пресет фильтрации для сэмплера текстуры
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SamplerPreset {
+    Nearest, // Без сглаживания, подходит для пиксель-арта
+
+    #[default]
+    Linear, // Трилинейная фильтрация (С учётом мипмапов)
+
+    Anisotropic, // Анизотропная фильтрация x16, для текстур под углом и сильного уменьшения
+}
+
+/// Режим адресации текстуры за пределами UV координат 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressMode {
+    #[default]
+    Clamp, // Растягиваем крайний пиксель
+
+    Repeat, // Повторяем текстуру
+    Mirror, // Повторяем текстуру с отражением
+}
+
+/// Настройки сэмплера текстуры. Используется как ключ для кэша
+/// сэмплеров, поэтому реализует Hash и Eq
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SamplerOptions {
+    pub preset: SamplerPreset,
+    pub address: AddressMode,
+}
+
+impl SamplerOptions {
+    pub fn new(preset: SamplerPreset, address: AddressMode) -> Self {
+        Self { preset, address }
+    }
+
+    /// Нужны ли мипмапы для этого сэмплера. Пресет Nearest
+    /// используется для пиксель-арта, поэтому мипмапы ему не нужны
+    pub fn wants_mipmaps(&self) -> bool {
+        self.preset != SamplerPreset::Nearest
+    }
+
+    /// Создаёт сэмплер wgpu на основе настроек
+    pub fn create_sampler(&self, ctx: &Context) -> wgpu::Sampler {
+        let address_mode = match self.address {
+            AddressMode::Clamp => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::Mirror => wgpu::AddressMode::MirrorRepeat,
+        };
+
+        let (filter, anisotropy_clamp) = match self.preset {
+            SamplerPreset::Nearest => (wgpu::FilterMode::Nearest, 1),
+            SamplerPreset::Linear => (wgpu::FilterMode::Linear, 1),
+
+            // [!] wgpu требует чтобы при анизотропии все фильтры
+            // были линейными
+            SamplerPreset::Anisotropic => (wgpu::FilterMode::Linear, 16),
+        };
+
+        ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture Sampler"),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            anisotropy_clamp,
+            ..Default::default()
+        })
+    }
+}
\ No newline at end of file
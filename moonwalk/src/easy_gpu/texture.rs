@@ -2,6 +2,9 @@
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
 use crate::easy_gpu::context::Context;
+use crate::easy_gpu::mipmap::{self, MipmapGenerator};
+use crate::easy_gpu::sampler::SamplerOptions;
+use crate::easy_gpu::pipeline::DEPTH_FORMAT;
 
 pub struct Texture {
     pub texture: wgpu::Texture,
@@ -10,22 +13,24 @@ pub struct Texture {
     pub width: u32,
     pub height: u32,
     pub format: wgpu::TextureFormat,
+    pub mip_levels: u32,
 }
 
 impl Texture {
+    /// Загружает текстуру из байтов. Если передан генератор мипмапов,
+    /// формат это позволяет и пресет сэмплера их использует, то
+    /// создаётся полная цепочка мип уровней которая заполняется на
+    /// GPU сразу после загрузки. Иначе у текстуры будет 1 уровень
     pub fn from_bytes(
         ctx: &Context,
         data: &[u8],
         width: u32,
         height: u32,
-        format: wgpu::TextureFormat
+        format: wgpu::TextureFormat,
+        sampler: &SamplerOptions,
+        mipmaps: Option<&mut MipmapGenerator>,
     ) -> Result<Self, String> {
-        let block_size = get_block_size(format).ok_or_else(|| 
-            format!("Format {:?} is not supported for auto-upload", format)
-        )?;
-
-        let bytes_per_row = width * block_size;
-        let expected_size = (bytes_per_row * height) as usize;
+        let expected_size = level_layout(format, width, height)?.2;
 
         if data.len() != expected_size {
             return Err(format!(
@@ -40,10 +45,70 @@ impl Texture {
             depth_or_array_layers: 1,
         };
 
+        // Мипмапы генерируются рендерингом в каждый уровень, поэтому
+        // текстуре нужен флаг RENDER_ATTACHMENT
+        let generate_mips = mipmaps.is_some()
+            && sampler.wants_mipmaps()
+            && MipmapGenerator::supports(ctx, format);
+
+        let (mip_levels, usage) = if generate_mips {
+            (
+                mipmap::full_mip_count(width, height),
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+        } else {
+            (1, wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
+        };
+
         let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Texture"),
             size,
-            mip_level_count: 1,
+            mip_level_count: mip_levels,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        });
+
+        write_level(ctx, &texture, 0, data, width, height, format)?;
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = sampler.create_sampler(ctx);
+
+        let result = Self { texture, view, sampler, width, height, format, mip_levels };
+
+        if let Some(generator) = mipmaps {
+            generator.generate(ctx, &result);
+        }
+
+        Ok(result)
+    }
+
+    /// Загружает текстуру с готовой цепочкой мип уровней (Например из
+    /// KTX2 файла). Уровень 0 это полный размер, каждый следующий в
+    /// 2 раза меньше. Работает и для сжатых форматов (BCn/ETC2/ASTC),
+    /// в этом случае размер строки считается в блоках
+    pub fn from_levels(
+        ctx: &Context,
+        levels: &[&[u8]],
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        sampler: &SamplerOptions,
+    ) -> Result<Self, String> {
+        if levels.is_empty() {
+            return Err("Texture has no mip levels".to_string());
+        }
+
+        let mip_levels = levels.len() as u32;
+
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: mip_levels,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
@@ -51,31 +116,72 @@ impl Texture {
             view_formats: &[],
         });
 
-        ctx.queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            data,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(bytes_per_row),
-                rows_per_image: Some(height),
-            },
-            size,
-        );
+        for (level, data) in levels.iter().enumerate() {
+            let level_width = (width >> level).max(1);
+            let level_height = (height >> level).max(1);
+
+            write_level(ctx, &texture, level as u32, data, level_width, level_height, format)?;
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
+        let sampler = sampler.create_sampler(ctx);
+
+        Ok(Self { texture, view, sampler, width, height, format, mip_levels })
+    }
+
+    /// Создаёт view на один мип уровень текстуры
+    pub fn mip_view(&self, level: u32) -> wgpu::TextureView {
+        self.texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Mip View"),
+            base_mip_level: level,
+            mip_level_count: Some(1),
             ..Default::default()
+        })
+    }
+
+    /// Пересоздаёт сэмплер текстуры с новыми настройками
+    pub fn set_sampler(&mut self, ctx: &Context, options: &SamplerOptions) {
+        self.sampler = options.create_sampler(ctx);
+    }
+
+    /// Создаёт буфер глубины (И трафарета) размером с холст. samples
+    /// должен совпадать с количеством сэмплов цвета в проходе
+    pub fn create_depth_target(ctx: &Context, width: u32, height: u32, samples: u32) -> wgpu::TextureView {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Создаёт мультисэмпловый цвет размером с холст. В него рисует
+    /// проход, а в конце прохода он сводится в текстуру поверхности
+    pub fn create_msaa_target(
+        ctx: &Context,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) -> wgpu::TextureView {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: samples,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
         });
 
-        Ok(Self { texture, view, sampler, width, height, format })
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
     pub fn create_render_target(
@@ -105,20 +211,109 @@ impl Texture {
             ..Default::default()
         });
 
-        Self { texture, view, sampler, width, height, format }
+        Self { texture, view, sampler, width, height, format, mip_levels: 1 }
     }
 }
 
-const fn get_block_size(format: wgpu::TextureFormat) -> Option<u32> {
-    use wgpu::TextureFormat::*;
-    match format {
-        R8Unorm | R8Snorm | R8Uint | R8Sint => Some(1),
-        R16Uint | R16Sint | R16Float | Rg8Unorm | Rg8Snorm | Rg8Uint | Rg8Sint => Some(2),
-        R32Uint | R32Sint | R32Float | Rg16Uint | Rg16Sint | Rg16Float | 
-        Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb | 
-        Rgba8Snorm | Rgba8Uint | Rgba8Sint | Rgb10a2Unorm => Some(4),
-        Rg32Uint | Rg32Sint | Rg32Float | Rgba16Uint | Rgba16Sint | Rgba16Float => Some(8),
-        Rgba32Uint | Rgba32Sint | Rgba32Float => Some(16),
-        _ => None,
+/// Возвращает размер строки в байтах, количество строк блоков и
+/// ожидаемый размер данных для уровня текстуры. Для обычных форматов
+/// блок это 1 пиксель, для сжатых (BCn/ETC2/ASTC) блок это 4x4 и
+/// больше пикселей, поэтому размеры округляются вверх до целого блока
+fn level_layout(format: wgpu::TextureFormat, width: u32, height: u32) -> Result<(u32, u32, usize), String> {
+    let block_bytes = format.block_copy_size(None).ok_or_else(||
+        format!("Format {:?} is not supported for auto-upload", format)
+    )?;
+
+    let (block_width, block_height) = format.block_dimensions();
+    let blocks_x = width.div_ceil(block_width);
+    let blocks_y = height.div_ceil(block_height);
+
+    let bytes_per_row = blocks_x * block_bytes;
+    Ok((bytes_per_row, blocks_y, (bytes_per_row * blocks_y) as usize))
+}
+
+/// Загружает один мип уровень текстуры
+fn write_level(
+    ctx: &Context,
+    texture: &wgpu::Texture,
+    level: u32,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> Result<(), String> {
+    let (bytes_per_row, rows, expected_size) = level_layout(format, width, height)?;
+
+    if data.len() < expected_size {
+        return Err(format!(
+            "Mip level {} is too small. Expected {}, got {}.",
+            level, expected_size, data.len()
+        ));
+    }
+
+    // Для сжатых форматов копируется целое число блоков, даже если
+    // мип уровень меньше блока (Например 2x2 при блоке 4x4)
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 }
+        .physical_size(format);
+
+    ctx.queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: level,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &data[..expected_size],
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: Some(rows),
+        },
+        size,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Байты каждого мип уровня для текстуры width x height
+    fn level_sizes(format: wgpu::TextureFormat, width: u32, height: u32, levels: u32) -> Vec<usize> {
+        (0..levels)
+            .map(|level| {
+                let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+                level_layout(format, w, h).map(|layout| layout.2).unwrap_or(0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unaligned_levels_round_up_to_blocks() {
+        use wgpu::TextureFormat as F;
+
+        let astc_10x8 = F::Astc { block: wgpu::AstcBlock::B10x8, channel: wgpu::AstcChannel::Unorm };
+
+        // 30x18 -> 15x9 -> 7x4 -> 3x2 -> 1x1. Неполный блок занимает
+        // столько же байт, сколько полный
+        let table = [
+            (F::Rgba8Unorm, vec![2160, 540, 112, 24, 4]),
+            (F::Bc1RgbaUnorm, vec![320, 96, 16, 8, 8]),
+            (F::Bc7RgbaUnorm, vec![640, 192, 32, 16, 16]),
+            (F::Etc2Rgb8Unorm, vec![320, 96, 16, 8, 8]),
+            (astc_10x8, vec![144, 64, 16, 16, 16]),
+        ];
+
+        for (format, expected) in table {
+            assert_eq!(level_sizes(format, 30, 18, 5), expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn rows_are_counted_in_blocks() {
+        assert_eq!(level_layout(wgpu::TextureFormat::Bc7RgbaUnorm, 30, 18), Ok((128, 5, 640)));
+        assert_eq!(level_layout(wgpu::TextureFormat::Rgba8Unorm, 30, 18), Ok((120, 18, 2160)));
+        assert!(level_layout(wgpu::TextureFormat::Depth24Plus, 4, 4).is_err());
     }
 }
\ No newline at end of file
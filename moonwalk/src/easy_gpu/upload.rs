@@ -0,0 +1,69 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use std::sync::Mutex;
+use wgpu::util::StagingBelt;
+
+/// Размер одного куска промежуточной памяти. Записи больше него
+/// получают отдельный кусок, который потом тоже переиспользуется
+const CHUNK_SIZE: wgpu::BufferAddress = 1 << 20;
+
+struct UploadState {
+    belt: StagingBelt,
+    encoder: Option<wgpu::CommandEncoder>,
+}
+
+/// Кольцо промежуточных буферов для заливки данных на видеокарту.
+/// Данные копируются в уже отображённый в память кусок, а команда
+/// копирования записывается в свой энкодер, который отправляется
+/// перед кадром. Куски возвращаются в кольцо когда GPU закончил с
+/// ними работать, поэтому кадры в полёте не ждут друг друга, как
+/// это бывает с queue.write_buffer на больших объёмах
+pub struct UploadBelt {
+    state: Mutex<UploadState>,
+}
+
+impl UploadBelt {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(UploadState {
+                belt: StagingBelt::new(CHUNK_SIZE),
+                encoder: None,
+            }),
+        }
+    }
+
+    /// Записывает данные в буфер начиная с байта offset. Запись
+    /// выполнится при следующей отправке команд (Смотрите finish)
+    pub fn write(&self, device: &wgpu::Device, target: &wgpu::Buffer, offset: wgpu::BufferAddress, data: &[u8]) {
+        let size = match wgpu::BufferSize::new(data.len() as u64) {
+            Some(size) => size,
+            None => return,
+        };
+
+        let mut state = self.state.lock().expect("Upload belt mutex poisoned");
+        let UploadState { belt, encoder } = &mut *state;
+
+        let encoder = encoder.get_or_insert_with(|| {
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Upload Encoder"),
+            })
+        });
+
+        belt.write_buffer(encoder, target, offset, size, device).copy_from_slice(data);
+    }
+
+    /// Закрывает накопленные записи. Команды нужно отправить раньше
+    /// команд кадра, чтобы кадр видел уже новые данные
+    pub fn finish(&self) -> Option<wgpu::CommandBuffer> {
+        let mut state = self.state.lock().expect("Upload belt mutex poisoned");
+        state.belt.finish();
+        state.encoder.take().map(|encoder| encoder.finish())
+    }
+
+    /// Возвращает куски в кольцо. Вызывается после отправки команд
+    pub fn recall(&self) {
+        let mut state = self.state.lock().expect("Upload belt mutex poisoned");
+        state.belt.recall();
+    }
+}
\ No newline at end of file
@@ -23,4 +23,16 @@ pub enum MoonWalkError {
 
     #[error("Failed to load font: {0}")]
     FontLoading(String),
+
+    #[error("Failed to load texture: {0}")]
+    TextureLoading(String),
+
+    #[error("Failed to load SVG: {0}")]
+    SvgLoading(String),
+
+    #[error("Failed to load Lottie animation: {0}")]
+    LottieLoading(String),
+
+    #[error("Not supported by this device: {0}")]
+    Unsupported(String),
 }
\ No newline at end of file
@@ -15,12 +15,25 @@ mod objects;
 mod batching;
 mod textware;
 mod easy_gpu;
+mod lottie;
 
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use glam::{Vec2, Vec4};
 use wgpu::SurfaceError;
 
-pub use crate::objects::ObjectId;
+pub use crate::objects::{MaskMode, ObjectId, ShaderId, TextureId, ClipRegion, ObjectMask};
+pub use crate::easy_gpu::{SamplerOptions, SamplerPreset, AddressMode};
+pub use crate::objects::path::{Path, PathCommand, PathStyle, Fill, FillRule, Stroke, LineJoin, LineCap, PathMesh, MeshVertex};
+pub use crate::objects::svg::SvgImport;
+pub use crate::lottie::LottiePlayer;
+pub use crate::objects::access::ObjectsMut;
+pub use crate::batching::submiter::{Submiter, Frame, Resources, RenderTarget, DrawItems};
+pub use crate::objects::store::ObjectStore;
+pub use crate::batching::culling::Culler;
+pub use crate::batching::clipping::Clipper;
+pub use crate::rendering::texture::TextureStore;
+pub use crate::easy_gpu::{Context, Buffer, Mask, Pipeline, PipelineBuilder, RenderPass};
+pub use crate::rendering::pipeline::ShaderStore;
 use crate::rendering::renderer::MoonRenderer;
 
 /// Основная структура движка которая содержит рендерер. Конструктор new
@@ -71,6 +84,67 @@ impl MoonWalk {
         self.renderer.set_scale_factor(scale);
     }
 
+    /// Функция для включения режима буфера глубины. Непрозрачные
+    /// объекты (Прямоугольники и изображения с альфой 1, без
+    /// скругления и у изображений без прозрачных пикселей в текстуре)
+    /// рисуются от ближних к дальним (Порядок пересчитывается только
+    /// при смене z), а кто кого закрывает решает буфер глубины на GPU.
+    /// Полупрозрачные объекты сортируются как обычно.
+    /// Полезно для больших непрозрачных сцен (Тайлмапы, фоны).
+    ///     [!] z индекс должен быть в диапазоне -10000..10000, объекты
+    ///         за ним отсекаются. Без этого режима ограничения нет
+    pub fn set_depth_ordering(&mut self, enabled: bool) -> Result<(), error::MoonWalkError> {
+        self.renderer.set_depth_ordering(enabled)
+    }
+
+    /// Функция для включения сглаживания MSAA. Принимает количество
+    /// сэмплов на пиксель: 1 (Выключено, по умолчанию), 2, 4 или 8.
+    /// Края прямоугольников сглаживает шейдер и без MSAA, но пути,
+    /// повёрнутые изображения и всё, что рисуется треугольниками,
+    /// без него получаются с лесенкой. Возвращает ошибку если
+    /// устройство не поддерживает такое количество сэмплов.
+    ///     [*] 4x поддерживается почти везде и обычно достаточно
+    pub fn set_msaa(&mut self, samples: u32) -> Result<(), error::MoonWalkError> {
+        self.renderer.set_msaa(samples)
+    }
+
+    /// Функция для включения отсечения объектов за пределами экрана.
+    /// Такие объекты не попадают в батчи вообще, что сильно помогает
+    /// для длинных прокручиваемых списков и больших игровых карт.
+    /// Рамка объекта считается с учётом вращения, масштаба и
+    /// родителей, а поиск видимых объектов идёт через сетку, которая
+    /// обновляется только для изменённых объектов.
+    ///     [!] Для путей рамкой считается их размер (set_size), обводка
+    ///         выходящая за него может пропасть у края экрана
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.renderer.set_culling(enabled);
+    }
+
+    /// Функция для включения подготовки прямоугольников на GPU.
+    /// Свойства объектов копируются в буферы видеокарты (Только
+    /// изменённые), а инстансы собирает и отсекает compute шейдер.
+    /// Помогает когда прямоугольников сотни тысяч и сборка инстансов
+    /// на процессоре становится узким местом. Возвращает ошибку если
+    /// устройство не поддерживает compute шейдеры (Например WebGL).
+    ///     [!] В режиме буфера глубины прямоугольники на GPU всегда
+    ///         рисуются как полупрозрачные
+    pub fn set_gpu_prepare(&mut self, enabled: bool) -> Result<(), error::MoonWalkError> {
+        self.renderer.set_gpu_prepare(enabled)
+    }
+
+    /// Функция для добавления своего батча (Например клетки тепловой
+    /// карты или столбики осциллограммы, которые рисуются инстансами).
+    /// Батч реализует трейт Submiter: сам собирает конвейер в
+    /// configure (Бинд группа 0 это матрица проекции, лайаут
+    /// ShaderStore::proj_layout), заливает данные в prepare и рисует
+    /// в render_range. Свои элементы батч отдаёт слоем на z
+    /// (DrawItems::Layer), который встаёт в общий порядок с объектами.
+    /// Чтобы менять данные батча после добавления, держите их в
+    /// Arc<Mutex<...>> и внутри батча, и снаружи
+    pub fn add_submiter(&mut self, submiter: Box<dyn Submiter>) -> Result<(), error::MoonWalkError> {
+        self.renderer.add_submiter(submiter)
+    }
+
     /// Функция для рендеринга всех элементов которые накопил движок.
     /// Вызывать нужно КАЖДЫЙ КАДР, но не делать этого в бесконечном
     /// цикле (While/loop). Вместо этого лучше использовать встроенное
@@ -93,6 +167,109 @@ impl MoonWalk {
         self.renderer.new_rect()
     }
 
+    /// Функция для загрузки текстуры из массива байтов в формате RGBA8
+    /// (4 байта на пиксель, sRGB). Размер данных должен быть равен
+    /// width * height * 4, иначе вернётся ошибка TextureLoading.
+    /// Мипмапы генерируются на GPU сразу при загрузке, поэтому
+    /// уменьшенные изображения не мерцают.
+    ///     [!] Загружайте текстуру один раз, а не каждый кадр
+    pub fn load_texture(&mut self, data: &[u8], width: u32, height: u32) -> Result<TextureId, error::MoonWalkError> {
+        self.renderer.load_texture(data, width, height)
+    }
+
+    /// Функция для загрузки текстуры из KTX2 файла (Байты файла целиком).
+    /// Поддерживаются сжатые форматы BC1-BC7, ETC2 и ASTC, а также
+    /// несжатые RGBA8/BGRA8. Если видеокарта поддерживает формат, то
+    /// текстура загружается в видеопамять в сжатом виде (Это экономит
+    /// память в 4-8 раз), иначе распаковывается на процессоре.
+    /// Мип уровни берутся из файла.
+    ///     [!] Суперсжатие (zstd, Basis Universal) не поддерживается
+    pub fn load_ktx2(&mut self, data: &[u8]) -> Result<TextureId, error::MoonWalkError> {
+        self.renderer.load_ktx2(data)
+    }
+
+    /// Функция для создания изображения и получения его ID. Принимает
+    /// ID текстуры (структура TextureId которую можно получить вызвав
+    /// load_texture). Изображение это прямоугольник с текстурой,
+    /// поэтому для него работают все функции конфигурации, в том
+    /// числе скругление углов. Цвет изображения работает как
+    /// тонировка (Белый цвет оставляет текстуру без изменений)
+    pub fn new_image(&mut self, texture: TextureId) -> ObjectId {
+        self.renderer.new_image(texture)
+    }
+
+    /// Функция для настройки сэмплера текстуры. Применяется ко всем
+    /// изображениям с этой текстурой (Кроме тех, у которых свой
+    /// сэмплер через set_image_sampler). Принимает SamplerOptions:
+    ///     preset - Nearest (пиксель-арт), Linear или Anisotropic
+    ///     address - Clamp, Repeat или Mirror
+    pub fn set_texture_sampler(&mut self, texture: TextureId, options: SamplerOptions) {
+        self.renderer.set_texture_sampler(texture, options);
+    }
+
+    /// Функция для настройки сэмплера конкретного изображения. Имеет
+    /// приоритет над сэмплером текстуры.
+    ///     [!] Не работает для каких-либо объектов кроме изображения.
+    pub fn set_image_sampler(&mut self, id: ObjectId, options: SamplerOptions) {
+        self.renderer.set_image_sampler(id, options);
+    }
+
+    /// Функция для создания векторного пути и получения его ID.
+    /// Принимает путь (Структура Path из команд move_to, line_to,
+    /// quad_to, cubic_to, arc и close) и стиль PathStyle с заливкой
+    /// (Правило NonZero или EvenOdd) и/или обводкой (Толщина,
+    /// соединения, окончания и лимит острых углов). Путь тесселируется
+    /// на процессоре один раз, а не каждый кадр. Изначальный размер
+    /// объекта равен размеру пути, set_size растягивает путь.
+    /// Пример:
+    /// let path = Path::new()
+    ///     .move_to(Vec2::new(0.0, 50.0))
+    ///     .quad_to(Vec2::new(50.0, 0.0), Vec2::new(100.0, 50.0));
+    /// let id = mw.new_path(&path, PathStyle::stroked(Stroke::new(4.0, Vec4::ONE)));
+    pub fn new_path(&mut self, path: &Path, style: PathStyle) -> ObjectId {
+        self.renderer.new_path(path, style)
+    }
+
+    /// Функция для замены пути и стиля у уже созданного объекта пути.
+    /// Вызывает повторную тесселяцию, поэтому не стоит вызывать её
+    /// каждый кадр если путь не меняется.
+    ///     [!] Не работает для каких-либо объектов кроме пути.
+    pub fn set_path(&mut self, id: ObjectId, path: &Path, style: PathStyle) {
+        self.renderer.set_path(id, path, style);
+    }
+
+    /// Функция для импорта SVG файла (Байты файла целиком). Все пути,
+    /// базовые фигуры, заливки, обводки, градиенты, трансформации и
+    /// группы собираются в один объект векторного пути, поэтому его
+    /// можно двигать, масштабировать (set_size) и тонировать (set_color)
+    /// как единое целое. Изначальный размер равен размеру холста SVG.
+    /// Возвращает SvgImport:
+    ///     id - ID объекта пути
+    ///     unsupported - Список неподдерживаемых возможностей из файла
+    ///                   (Фильтры, маски, текст, изображения и т.д.)
+    pub fn load_svg(&mut self, data: &[u8]) -> Result<SvgImport, error::MoonWalkError> {
+        self.renderer.load_svg(data)
+    }
+
+    /// Функция для загрузки Lottie (Bodymovin) анимации из JSON файла.
+    /// Возвращает LottiePlayer который управляет проигрыванием:
+    /// play, pause, seek (В секундах), seek_frame, set_looping и
+    /// set_speed. Анимация рисуется одним объектом пути, его ID можно
+    /// получить через player.id() и использовать как любой другой
+    /// объект (Позиция, размер, цвет, z индекс).
+    ///     [!] Чтобы анимация шла, вызывайте update_lottie каждый кадр
+    pub fn load_lottie(&mut self, data: &[u8]) -> Result<LottiePlayer, error::MoonWalkError> {
+        self.renderer.load_lottie(data)
+    }
+
+    /// Функция для продвижения Lottie анимации на dt секунд. Сетка
+    /// анимации пересобирается только если кадр изменился, поэтому
+    /// анимация на паузе ничего не стоит. Новый кадр переписывает
+    /// только объект анимации, остальные объекты не пересобираются
+    pub fn update_lottie(&mut self, player: &mut LottiePlayer, dt: f32) {
+        self.renderer.update_lottie(player, dt);
+    }
+
     /// Функция для изменения позиции любого объекта по его ID
     /// (Структура ObjectId которую можно получить вызвав new_* функцию)
     /// принимает ID объекта и структуру Vec2 для описания 2D позиции
@@ -114,6 +291,27 @@ impl MoonWalk {
         self.renderer.config_rotation(id, radians);
     }
 
+    /// Функция для изменения масштаба любого объекта. Принимает ID
+    /// объекта и Vec2 (Масштаб по x и y, 1.0 это без масштаба).
+    /// В отличии от set_size масштаб не меняет размер объекта (Он
+    /// остаётся прежним для раскладки и проверки попаданий), а
+    /// только растягивает его при рисовании вокруг точки опоры.
+    /// Скругление углов масштабируется вместе с объектом
+    pub fn set_scale(&mut self, id: ObjectId, scale: Vec2) {
+        self.renderer.config_scale(id, scale);
+    }
+
+    /// Функция для изменения точки опоры (Якоря) объекта. Вокруг неё
+    /// происходит вращение и масштаб. Принимает Vec2 в долях размера:
+    ///     (0.5, 0.5) - Центр (По умолчанию)
+    ///     (0.0, 0.0) - Левый верхний угол
+    ///     (0.5, 1.0) - Середина нижней стороны (Например основание
+    ///                  стрелки спидометра)
+    /// Позиция объекта при этом не меняется
+    pub fn set_pivot(&mut self, id: ObjectId, pivot: Vec2) {
+        self.renderer.config_pivot(id, pivot);
+    }
+
     /// Функция для изменения цвета любого объекта по его ID
     /// принимает ObjectId и Vec4 из GLAM. Цвета заполняются
     /// следующим образом:
@@ -128,6 +326,23 @@ impl MoonWalk {
         self.renderer.config_color(id, color);
     }
 
+    /// Функция для изменения прозрачности объекта, от 0 до 1.
+    /// Прозрачность умножается на альфа канал цвета, но сам цвет не
+    /// меняет, поэтому для анимации появления/исчезновения лучше
+    /// использовать её, а не set_color. Если у объекта есть родитель,
+    /// то прозрачности перемножаются вниз по иерархии
+    pub fn set_opacity(&mut self, id: ObjectId, opacity: f32) {
+        self.renderer.config_opacity(id, opacity);
+    }
+
+    /// Функция для скрытия и показа объекта. Скрытый объект вообще не
+    /// попадает в батч (Не занимает место при сортировке и не тратит
+    /// инстанс), в отличии от нулевой прозрачности. Скрытый родитель
+    /// скрывает и всех своих детей
+    pub fn set_visible(&mut self, id: ObjectId, visible: bool) {
+        self.renderer.config_visible(id, visible);
+    }
+
     /// Функция для конфигурации скругления у прямоугольника.
     ///     [!] Не работает для каких-либо объектов кроме прямоугольника.
     ///
@@ -155,6 +370,44 @@ impl MoonWalk {
     pub fn set_rounded(&mut self, id: ObjectId, radii: Vec4) {
         self.renderer.set_rounded(id, radii);
     }
+
+    /// Функция для создания своего эффекта (Фрагментного шейдера) и
+    /// получения его ID. Вершинную стадию и юниформы даёт MoonWalk,
+    /// а вы пишете только fs_main на WGSL. Она получает VertexOutput
+    /// с полями color, uv (0..1 внутри объекта), local_pos и size (В
+    /// пикселях), radii и параметрами объекта params0 и params1. Для
+    /// скругления углов умножьте альфу на shape_alpha(in).
+    /// Возвращает ошибку ShaderCompilation если шейдер не собрался
+    /// (Номера строк в ней считаются вместе с прологом движка).
+    /// Пример:
+    /// let wave = mw.compile_effect(r#"
+    ///     @fragment
+    ///     fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    ///         let k = sin(in.uv.x * 20.0 + in.params0.x) * 0.5 + 0.5;
+    ///         return vec4<f32>(in.color.rgb * k, in.color.a * shape_alpha(in));
+    ///     }
+    /// "#)?;
+    /// mw.set_effect(rect, Some(wave));
+    pub fn compile_effect(&mut self, src: &str) -> Result<ShaderId, error::MoonWalkError> {
+        self.renderer.compile_effect(src)
+    }
+
+    /// Функция для установки эффекта прямоугольнику (None убирает
+    /// эффект). Один эффект можно дать сколько угодно объектам.
+    ///     [!] Не работает для каких-либо объектов кроме прямоугольника.
+    ///     [!] В режиме буфера глубины объекты с эффектом рисуются как
+    ///         полупрозрачные
+    pub fn set_effect(&mut self, id: ObjectId, effect: Option<ShaderId>) {
+        self.renderer.set_effect(id, effect);
+    }
+
+    /// Функция для установки параметров эффекта объекта. Это два
+    /// Vec4, которые шейдер получает как in.params0 и in.params1
+    /// (Например время анимации, второй цвет или сила эффекта).
+    /// Дешёвая операция, её можно вызывать каждый кадр
+    pub fn set_effect_params(&mut self, id: ObjectId, params: [Vec4; 2]) {
+        self.renderer.set_effect_params(id, params);
+    }
  
     /// Функция определения Z индекса объекта. Обратите внимание,
     /// z индекс никак не вляяет на размер или координаты объекта.
@@ -165,10 +418,176 @@ impl MoonWalk {
     ///         Объект B: Z индекс = 2
     ///     Объект B будет перекрывать объект A
     /// Принимает Id объекта и z индекс (флоат, может быть отрицательным) 
+    ///     [!] В режиме буфера глубины (set_depth_ordering) z должен
+    ///         быть в диапазоне -10000..10000, иначе объект отсекается
     pub fn set_z_index(&mut self, id: ObjectId, z: f32) {
         self.renderer.set_z_index(id, z);
     }
 
+    /// Функция для создания иерархии объектов. Делает объект child
+    /// ребёнком объекта parent. После этого позиция и вращение ребёнка
+    /// задаются относительно родителя: (0, 0) это левый верхний угол
+    /// родителя, а вращение родителя поворачивает всех его детей.
+    /// Поэтому чтобы передвинуть карточку со всем содержимым нужно
+    /// менять позицию только у карточки.
+    /// Возвращает false если родитель является потомком ребёнка
+    /// (Циклы в иерархии запрещены).
+    ///     [*] Z индекс не наследуется, у каждого объекта он свой
+    pub fn set_parent(&mut self, child: ObjectId, parent: ObjectId) -> bool {
+        self.renderer.set_parent(child, parent)
+    }
+
+    /// Функция для отвязки объекта от родителя. Локальная позиция
+    /// объекта становится мировой
+    pub fn clear_parent(&mut self, child: ObjectId) {
+        self.renderer.clear_parent(child);
+    }
+
+    /// Функция для получения родителя объекта (None если его нет)
+    pub fn get_parent(&self, child: ObjectId) -> Option<ObjectId> {
+        self.renderer.get_parent(child)
+    }
+
+    /// Функция для обрезки потомков контейнера (Список со скроллом,
+    /// карточка со скруглёнными углами). rect это x, y, ширина и
+    /// высота области в локальных координатах контейнера, radii это
+    /// скругление её углов (Как у set_rounded). Всё что дети и их
+    /// потомки рисуют вне области, не видно. Сам контейнер не
+    /// обрезается. Вложенные области пересекаются.
+    ///     [*] Прямоугольная область без вращения обрезается через
+    ///         scissor и ничего не стоит. Скруглённая или повёрнутая
+    ///         обрезается трафаретом: при первой такой области
+    ///         конвейеры пересобираются с тестом трафарета
+    ///     [!] Край области в трафарете не сглаживается
+    ///     [!] В режиме буфера глубины обрезанные объекты рисуются
+    ///         как полупрозрачные
+    pub fn set_clip(&mut self, container: ObjectId, rect: Vec4, radii: Vec4) {
+        self.renderer.set_clip(container, rect, radii);
+    }
+
+    /// Функция для снятия обрезки с потомков контейнера
+    pub fn clear_clip(&mut self, container: ObjectId) {
+        self.renderer.clear_clip(container);
+    }
+
+    /// Функция для маскирования потомков группы формой другого
+    /// объекта (Аватар в круге, появление по растущему пути). Объект
+    /// маски сам не рисуется: функция скрывает его, а clear_mask
+    /// показывает снова. Двигать, масштабировать и менять маску
+    /// можно как обычный объект.
+    ///     MaskMode::Stencil - Содержимое видно только внутри формы
+    ///         маски: прямоугольника (Со скруглением), прямоугольника
+    ///         изображения или пути. Работает через трафарет, как
+    ///         скруглённая обрезка в set_clip
+    ///     MaskMode::Alpha - Альфа текстуры изображения маски умножает
+    ///         содержимое (Мягкие края, градиенты). Группа рисуется в
+    ///         отдельный слой размером с экран, а потом слой рисуется
+    ///         на z группы. Только для изображений, другие объекты
+    ///         работают как Stencil
+    ///     [!] Группа с альфа маской рисуется целиком на месте своего
+    ///         первого объекта в порядке по z, объекты вне группы не
+    ///         могут оказаться между её детьми
+    ///     [!] Альфа маска внутри группы с альфа маской работает как
+    ///         Stencil
+    pub fn set_mask(&mut self, group: ObjectId, mask: ObjectId, mode: MaskMode) {
+        self.renderer.set_mask(group, mask, mode);
+    }
+
+    /// Функция для снятия маски с группы
+    pub fn clear_mask(&mut self, group: ObjectId) {
+        self.renderer.clear_mask(group);
+    }
+
+    /// Функции для получения свойств объекта по его ID. Возвращают
+    /// локальные значения (Те, которые были переданы в set_* функции),
+    /// а не мировые с учётом родителя
+    pub fn get_position(&self, id: ObjectId) -> Vec2 {
+        self.renderer.store().get_position(id)
+    }
+
+    pub fn get_size(&self, id: ObjectId) -> Vec2 {
+        self.renderer.store().get_size(id)
+    }
+
+    pub fn get_color(&self, id: ObjectId) -> Vec4 {
+        self.renderer.store().get_color(id)
+    }
+
+    /// Угол вращения в радианах
+    pub fn get_rotation(&self, id: ObjectId) -> f32 {
+        self.renderer.store().get_rotation(id)
+    }
+
+    pub fn get_z_index(&self, id: ObjectId) -> f32 {
+        self.renderer.store().get_z_index(id)
+    }
+
+    pub fn get_scale(&self, id: ObjectId) -> Vec2 {
+        self.renderer.store().get_scale(id)
+    }
+
+    pub fn get_pivot(&self, id: ObjectId) -> Vec2 {
+        self.renderer.store().get_pivot(id)
+    }
+
+    pub fn get_opacity(&self, id: ObjectId) -> f32 {
+        self.renderer.store().get_opacity(id)
+    }
+
+    pub fn is_visible(&self, id: ObjectId) -> bool {
+        self.renderer.store().is_visible(id)
+    }
+
+    /// Скругление углов прямоугольника (Для других объектов ноль)
+    pub fn get_rounded(&self, id: ObjectId) -> Vec4 {
+        self.renderer.store().get_rounded(id)
+    }
+
+    /// Массовые версии set_* функций. Принимают срез ID и срез значений
+    /// одинаковой длины (i-е значение для i-го объекта). Как и обычные
+    /// set_* функции, отмечают изменённым каждый объект, и его инстанс
+    /// переписывается на месте без полной пересборки. ID за пределами
+    /// хранилища пропускаются с предупреждением в лог.
+    ///     [!] set_z_indices меняет порядок отрисовки, поэтому после
+    ///         него все батчи собираются заново
+    /// Пример:
+    /// mw.set_positions(&ids, &positions);
+    pub fn set_positions(&mut self, ids: &[ObjectId], positions: &[Vec2]) {
+        self.renderer.store_mut().config_positions(ids, positions);
+    }
+
+    pub fn set_sizes(&mut self, ids: &[ObjectId], sizes: &[Vec2]) {
+        self.renderer.store_mut().config_sizes(ids, sizes);
+    }
+
+    pub fn set_colors(&mut self, ids: &[ObjectId], colors: &[Vec4]) {
+        self.renderer.store_mut().config_colors(ids, colors);
+    }
+
+    /// Углы в радианах
+    pub fn set_rotations(&mut self, ids: &[ObjectId], radians: &[f32]) {
+        self.renderer.store_mut().config_rotations(ids, radians);
+    }
+
+    pub fn set_z_indices(&mut self, ids: &[ObjectId], z: &[f32]) {
+        self.renderer.store_mut().config_z_indices(ids, z);
+    }
+
+    /// Функция для прямого доступа к массивам свойств всех объектов
+    /// (Позиции, размеры, цвета, вращения, масштабы, точки опоры,
+    /// прозрачность и видимость). Индекс в массиве это id.index().
+    /// Какие объекты поменялись неизвестно, поэтому после вызова все
+    /// батчи, сетка отсечения и общий порядок собираются заново. Это
+    /// выгодно, только когда за кадр меняется почти каждый объект
+    /// (Например частицы), иначе лучше массовые set_* функции. Пример:
+    /// let objects = mw.objects_mut();
+    /// for p in &particles {
+    ///     objects.positions[p.id.index()] = p.pos;
+    /// }
+    pub fn objects_mut(&mut self) -> ObjectsMut<'_> {
+        self.renderer.store_mut().objects_mut()
+    }
+
     /// Эта функция пересоздаёт холст для рендеринга. На android
     /// при сворачивании приложение старый холст удаляется поэтому
     /// нам нужен новый.
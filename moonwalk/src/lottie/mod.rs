@@ -0,0 +1,311 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+pub mod model;
+pub mod render;
+
+use glam::Vec2;
+
+use crate::lottie::model::Animation;
+use crate::objects::ObjectId;
+use crate::objects::path::PathMesh;
+use crate::objects::store::ObjectStore;
+
+/// Проигрыватель Lottie анимации. Вся анимация рисуется одним объектом
+/// векторного пути, поэтому его можно двигать, масштабировать и
+/// тонировать как любой другой объект через id(). Сетка пересобирается
+/// только когда меняется кадр (На паузе анимация ничего не стоит).
+/// Поддерживаются слои фигур с группами, прямоугольниками, эллипсами,
+/// путями, заливками, обводками и обрезкой путей (Trim paths),
+/// трансформации слоёв и групп, родители слоёв и прозрачность
+pub struct LottiePlayer {
+    animation: Animation,
+    id: ObjectId,
+    frame: f32,
+    playing: bool,
+    looping: bool,
+    speed: f32,
+
+    // Кадр который сейчас в сетке объекта. None значит сетку
+    // нужно пересобрать
+    rendered_frame: Option<f32>,
+}
+
+impl LottiePlayer {
+    pub fn new(animation: Animation, id: ObjectId) -> Self {
+        let frame = animation.in_point;
+
+        Self {
+            animation,
+            id,
+            frame,
+            playing: true,
+            looping: true,
+            speed: 1.0,
+            rendered_frame: Some(frame),
+        }
+    }
+
+    /// ID объекта пути на котором рисуется анимация
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn play(&mut self) {
+        // Если анимация без повтора закончилась, то начинаем сначала
+        if !self.looping && self.frame >= self.last_frame() {
+            self.frame = self.animation.in_point;
+        }
+
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Скорость проигрывания (1.0 это обычная скорость). Отрицательная
+    /// скорость проигрывает анимацию задом наперёд
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Перейти к времени в секундах от начала анимации
+    pub fn seek(&mut self, seconds: f32) {
+        self.seek_frame(self.animation.in_point + seconds * self.animation.frame_rate);
+    }
+
+    /// Перейти к кадру (Кадры считаются как в файле, с учётом ip)
+    pub fn seek_frame(&mut self, frame: f32) {
+        self.frame = frame.clamp(self.animation.in_point, self.last_frame());
+    }
+
+    pub fn current_frame(&self) -> f32 {
+        self.frame
+    }
+
+    /// Время от начала анимации в секундах
+    pub fn current_time(&self) -> f32 {
+        (self.frame - self.animation.in_point) / self.animation.frame_rate
+    }
+
+    /// Длительность анимации в секундах
+    pub fn duration(&self) -> f32 {
+        (self.animation.out_point - self.animation.in_point) / self.animation.frame_rate
+    }
+
+    pub fn frame_rate(&self) -> f32 {
+        self.animation.frame_rate
+    }
+
+    /// Размер холста анимации
+    pub fn size(&self) -> Vec2 {
+        self.animation.size
+    }
+
+    /// Возможности Lottie которые встретились в файле, но не
+    /// поддерживаются (Например слои изображений или текста)
+    pub fn unsupported(&self) -> &[String] {
+        &self.animation.unsupported
+    }
+
+    /// Продвигает время на dt секунд. Возвращает новую сетку если
+    /// кадр изменился с прошлого вызова
+    pub(crate) fn advance(&mut self, dt: f32) -> Option<PathMesh> {
+        if self.playing {
+            self.step(dt * self.speed * self.animation.frame_rate);
+        }
+
+        if self.rendered_frame == Some(self.frame) {
+            return None;
+        }
+
+        self.rendered_frame = Some(self.frame);
+        Some(render::render_frame(&self.animation, self.frame))
+    }
+
+    /// Продвигает анимацию и заменяет сетку её объекта. Объект только
+    /// отмечается изменённым, остальные батчи не пересобираются
+    pub(crate) fn update(&mut self, store: &mut ObjectStore, dt: f32) {
+        if let Some(mesh) = self.advance(dt) {
+            store.set_path_mesh(self.id, mesh);
+        }
+    }
+
+    fn step(&mut self, frames: f32) {
+        let start = self.animation.in_point;
+        let length = self.animation.out_point - start;
+        let frame = self.frame + frames;
+
+        if self.looping {
+            self.frame = start + (frame - start).rem_euclid(length);
+        } else if frame >= self.last_frame() || frame <= start {
+            self.frame = frame.clamp(start, self.last_frame());
+            self.playing = false;
+        } else {
+            self.frame = frame;
+        }
+    }
+
+    /// Последний кадр который ещё рисуется (op не включается)
+    fn last_frame(&self) -> f32 {
+        (self.animation.out_point - 1.0).max(self.animation.in_point)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::objects::ObjectType;
+
+    /// Небольшая анимация на 2 секунды: прямоугольник растёт с 40 до 80
+    /// за первую секунду, а обводка эллипса дорисовывается обрезкой
+    /// за всю анимацию
+    pub(crate) const ANIMATION: &str = r#"{
+        "fr": 30, "ip": 0, "op": 60, "w": 200, "h": 200,
+        "layers": [{
+            "ty": 4, "ind": 1, "ip": 0, "op": 60, "st": 0,
+            "ks": {},
+            "shapes": [
+                {"ty": "gr", "it": [
+                    {"ty": "rc", "p": {"k": [50, 50]}, "r": {"k": 0},
+                     "s": {"a": 1, "k": [
+                        {"t": 0, "s": [40, 40], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+                        {"t": 30, "s": [80, 80]}
+                     ]}},
+                    {"ty": "fl", "c": {"k": [1, 0, 0, 1]}, "o": {"k": 100}},
+                    {"ty": "tr"}
+                ]},
+                {"ty": "gr", "it": [
+                    {"ty": "el", "p": {"k": [150, 150]}, "s": {"k": [40, 40]}},
+                    {"ty": "st", "c": {"k": [0, 0, 1, 1]}, "o": {"k": 100}, "w": {"k": 4}},
+                    {"ty": "tm", "s": {"k": 0}, "o": {"k": 0},
+                     "e": {"a": 1, "k": [
+                        {"t": 0, "s": [0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+                        {"t": 60, "s": [100]}
+                     ]}},
+                    {"ty": "tr"}
+                ]}
+            ]
+        }]
+    }"#;
+
+    pub(crate) fn animation() -> Animation {
+        Animation::parse(ANIMATION.as_bytes()).expect("Test animation must parse")
+    }
+
+    fn player() -> LottiePlayer {
+        LottiePlayer::new(animation(), ObjectId::new(ObjectType::Path, 0))
+    }
+
+    fn assert_frame(player: &LottiePlayer, expected: f32) {
+        let frame = player.current_frame();
+        assert!((frame - expected).abs() < 1e-3, "frame {} != {}", frame, expected);
+    }
+
+    #[test]
+    fn advances_only_while_playing() {
+        let mut player = player();
+        assert!(player.is_playing());
+        assert_eq!(player.duration(), 2.0);
+
+        // Первый кадр уже в сетке объекта, пересобирать нечего
+        assert!(player.advance(0.0).is_none());
+
+        assert!(player.advance(0.5).is_some());
+        assert_frame(&player, 15.0);
+        assert!(player.advance(0.0).is_none());
+
+        player.pause();
+        assert!(player.advance(1.0).is_none());
+        assert_frame(&player, 15.0);
+
+        player.play();
+        player.set_speed(2.0);
+        assert!(player.advance(0.25).is_some());
+        assert_frame(&player, 30.0);
+    }
+
+    #[test]
+    fn new_frames_mark_only_the_player_object() {
+        let mut store = ObjectStore::new();
+        let rect = store.new_rect();
+        let id = store.new_path_mesh(render::render_frame(&animation(), 0.0));
+        let mut player = LottiePlayer::new(animation(), id);
+        store.clear_changes();
+
+        player.update(&mut store, 0.5);
+        assert!(!store.dirty);
+        assert_eq!(store.changed, vec![id.index()]);
+        assert!(!store.changed.contains(&rect.index()));
+        store.clear_changes();
+
+        // Тот же кадр ничего не отмечает
+        player.update(&mut store, 0.0);
+        assert!(!store.has_changes());
+    }
+
+    #[test]
+    fn seek_clamps_to_animation_range() {
+        let mut player = player();
+        player.pause();
+
+        player.seek(1.0);
+        assert_frame(&player, 30.0);
+        assert_eq!(player.current_time(), 1.0);
+
+        // Кадр op уже не рисуется
+        player.seek(100.0);
+        assert_frame(&player, 59.0);
+
+        player.seek_frame(-10.0);
+        assert_frame(&player, 0.0);
+
+        // Переход на паузе всё равно пересобирает сетку
+        player.seek_frame(10.0);
+        assert!(player.advance(0.0).is_some());
+    }
+
+    #[test]
+    fn looping_wraps_around() {
+        let mut player = player();
+        player.seek_frame(55.0);
+
+        player.advance(10.0 / 30.0);
+        assert_frame(&player, 5.0);
+        assert!(player.is_playing());
+
+        // Задом наперёд переходит через начало в конец
+        player.set_speed(-1.0);
+        player.advance(10.0 / 30.0);
+        assert_frame(&player, 55.0);
+    }
+
+    #[test]
+    fn without_looping_stops_on_last_frame() {
+        let mut player = player();
+        player.set_looping(false);
+        player.seek_frame(55.0);
+
+        player.advance(1.0);
+        assert_frame(&player, 59.0);
+        assert!(!player.is_playing());
+
+        // Повторный запуск начинает с начала
+        player.play();
+        assert!(player.is_playing());
+        assert_frame(&player, 0.0);
+    }
+}
\ No newline at end of file
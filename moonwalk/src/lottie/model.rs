@@ -0,0 +1,545 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Vec2, Vec4};
+use serde_json::Value;
+
+use crate::objects::path::{FillRule, LineCap, LineJoin};
+
+/// Тип слоя фигур в Lottie. Остальные слои (Прекомпозиции, изображения,
+/// текст) не рисуются, а нулевые слои (3) используются только как
+/// родители для трансформаций
+const SHAPE_LAYER: i64 = 4;
+const NULL_LAYER: i64 = 3;
+
+/// Ключевой кадр анимированного свойства. Значение хранится плоским
+/// массивом чисел, поэтому один и тот же код интерполирует числа,
+/// векторы, цвета и формы путей
+pub struct Keyframe {
+    pub time: f32,
+    pub start: Vec<f32>,
+    pub end: Option<Vec<f32>>, // Старый формат Lottie хранит конец в самом кадре
+    pub hold: bool, // Значение не интерполируется до следующего кадра
+    pub ease_out: Vec2,
+    pub ease_in: Vec2,
+}
+
+/// Свойство Lottie, статичное или анимированное
+pub enum Property {
+    Static(Vec<f32>),
+    Animated(Vec<Keyframe>),
+}
+
+impl Property {
+    /// Разбирает свойство вида {"a": 0/1, "k": ...}. Если свойства нет
+    /// в файле, то используется значение по умолчанию
+    pub fn parse(value: Option<&Value>, default: &[f32]) -> Self {
+        let k = match value.and_then(|v| v.get("k")) {
+            Some(k) => k,
+            None => return Self::Static(default.to_vec()),
+        };
+
+        let keyframes = match k.as_array() {
+            Some(items) if items.first().is_some_and(|f| f.get("t").is_some()) => items,
+            _ => return Self::Static(numbers(k)),
+        };
+
+        let mut result = Vec::with_capacity(keyframes.len());
+        for frame in keyframes.iter() {
+            result.push(Keyframe {
+                time: number(frame.get("t"), 0.0),
+                start: frame.get("s").map(numbers).unwrap_or_default(),
+                end: frame.get("e").map(numbers),
+                hold: number(frame.get("h"), 0.0) == 1.0,
+                ease_out: easing(frame.get("o"), Vec2::ZERO),
+                ease_in: easing(frame.get("i"), Vec2::ONE),
+            });
+        }
+
+        Self::Animated(result)
+    }
+
+    /// Значение свойства на кадре
+    pub fn value(&self, frame: f32) -> Vec<f32> {
+        let keyframes = match self {
+            Self::Static(value) => return value.clone(),
+            Self::Animated(keyframes) => keyframes,
+        };
+
+        let first = match keyframes.first() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+
+        if frame <= first.time {
+            return first.start.clone();
+        }
+
+        for pair in keyframes.windows(2) {
+            let (current, next) = (&pair[0], &pair[1]);
+
+            if frame >= next.time {
+                continue;
+            }
+
+            let end = current.end.as_ref().unwrap_or(&next.start);
+            if current.hold || end.len() != current.start.len() {
+                return current.start.clone();
+            }
+
+            let progress = (frame - current.time) / (next.time - current.time).max(f32::EPSILON);
+            let eased = bezier_ease(current.ease_out, current.ease_in, progress);
+
+            return current.start.iter()
+                .zip(end.iter())
+                .map(|(a, b)| a + (b - a) * eased)
+                .collect();
+        }
+
+        // После последнего кадра. В старом формате последний кадр
+        // бывает пустым, тогда берём конец предыдущего
+        let last = &keyframes[keyframes.len() - 1];
+        if !last.start.is_empty() {
+            return last.start.clone();
+        }
+
+        keyframes.iter().rev()
+            .find_map(|k| k.end.clone().or_else(|| (!k.start.is_empty()).then(|| k.start.clone())))
+            .unwrap_or_default()
+    }
+
+    pub fn scalar(&self, frame: f32) -> f32 {
+        self.value(frame).first().copied().unwrap_or(0.0)
+    }
+
+    pub fn vec2(&self, frame: f32) -> Vec2 {
+        let value = self.value(frame);
+        Vec2::new(
+            value.first().copied().unwrap_or(0.0),
+            value.get(1).copied().unwrap_or(0.0),
+        )
+    }
+
+    /// Цвет в Lottie хранится как 0..1, но некоторые экспортёры пишут 0..255
+    pub fn color(&self, frame: f32) -> Vec4 {
+        let value = self.value(frame);
+        let get = |i: usize, default: f32| value.get(i).copied().unwrap_or(default);
+        let color = Vec4::new(get(0, 0.0), get(1, 0.0), get(2, 0.0), get(3, 1.0));
+
+        if color.truncate().max_element() > 1.0 {
+            color / 255.0
+        } else {
+            color
+        }
+    }
+}
+
+/// Позиция в Lottie может быть разделена на отдельные оси
+pub enum Position {
+    Joined(Property),
+    Split(Property, Property),
+}
+
+/// Трансформация слоя или группы
+pub struct Transform {
+    pub anchor: Property,
+    pub position: Position,
+    pub scale: Property, // В процентах
+    pub rotation: Property, // В градусах
+    pub opacity: Property, // 0..100
+}
+
+impl Transform {
+    pub fn parse(value: Option<&Value>) -> Self {
+        let get = |key: &str| value.and_then(|v| v.get(key));
+
+        let position = match get("p") {
+            Some(p) if p.get("s").and_then(Value::as_bool) == Some(true) => {
+                Position::Split(Property::parse(p.get("x"), &[0.0]), Property::parse(p.get("y"), &[0.0]))
+            },
+            p => Position::Joined(Property::parse(p, &[0.0, 0.0])),
+        };
+
+        Self {
+            anchor: Property::parse(get("a"), &[0.0, 0.0]),
+            position,
+            scale: Property::parse(get("s"), &[100.0, 100.0]),
+            rotation: Property::parse(get("r").or_else(|| get("rz")), &[0.0]),
+            opacity: Property::parse(get("o"), &[100.0]),
+        }
+    }
+}
+
+/// Элемент слоя фигур
+pub enum Shape {
+    Group(Vec<Shape>),
+    Transform(Transform),
+    Rect { position: Property, size: Property, roundness: Property },
+    Ellipse { position: Property, size: Property },
+    Path(Property), // Закрытость + тройки (вершина, вход, выход) плоским массивом
+    Fill { color: Property, opacity: Property, rule: FillRule },
+    Stroke { color: Property, opacity: Property, width: Property, cap: LineCap, join: LineJoin, miter_limit: f32 },
+    Trim { start: Property, end: Property, offset: Property },
+}
+
+/// Слой композиции
+pub struct Layer {
+    pub kind: i64,
+    pub index: Option<i64>,
+    pub parent: Option<i64>,
+    pub in_point: f32,
+    pub out_point: f32,
+    pub start_time: f32,
+    pub stretch: f32,
+    pub transform: Transform,
+    pub shapes: Vec<Shape>,
+}
+
+impl Layer {
+    pub fn is_drawable(&self) -> bool {
+        self.kind == SHAPE_LAYER
+    }
+
+    /// Переводит время композиции во время слоя
+    pub fn local_frame(&self, frame: f32) -> f32 {
+        (frame - self.start_time) / self.stretch
+    }
+}
+
+/// Разобранная Lottie анимация
+pub struct Animation {
+    pub frame_rate: f32,
+    pub in_point: f32,
+    pub out_point: f32,
+    pub size: Vec2,
+    pub layers: Vec<Layer>,
+    pub unsupported: Vec<String>,
+}
+
+impl Animation {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let root: Value = serde_json::from_slice(data)
+            .map_err(|e| format!("Invalid Lottie JSON: {}", e))?;
+
+        let layers_json = root.get("layers").and_then(Value::as_array)
+            .ok_or_else(|| "Lottie file has no layers".to_string())?;
+
+        let mut unsupported = Vec::new();
+        let mut layers = Vec::with_capacity(layers_json.len());
+
+        for layer in layers_json.iter() {
+            layers.push(parse_layer(layer, &mut unsupported));
+        }
+
+        for feature in unsupported.iter() {
+            log::warn!("Lottie feature is not supported and was ignored: {}", feature);
+        }
+
+        let in_point = number(root.get("ip"), 0.0);
+
+        Ok(Self {
+            frame_rate: number(root.get("fr"), 60.0).max(1.0),
+            in_point,
+            out_point: number(root.get("op"), in_point + 1.0).max(in_point + 1.0),
+            size: Vec2::new(number(root.get("w"), 0.0), number(root.get("h"), 0.0)),
+            layers,
+            unsupported,
+        })
+    }
+}
+
+fn parse_layer(layer: &Value, unsupported: &mut Vec<String>) -> Layer {
+    let kind = layer.get("ty").and_then(Value::as_i64).unwrap_or(-1);
+
+    if kind != SHAPE_LAYER && kind != NULL_LAYER {
+        report(unsupported, &format!("layer type {}", kind));
+    }
+
+    let shapes = match layer.get("shapes").and_then(Value::as_array) {
+        Some(items) => parse_shapes(items, unsupported),
+        None => Vec::new(),
+    };
+
+    Layer {
+        kind,
+        index: layer.get("ind").and_then(Value::as_i64),
+        parent: layer.get("parent").and_then(Value::as_i64),
+        in_point: number(layer.get("ip"), 0.0),
+        out_point: number(layer.get("op"), f32::MAX),
+        start_time: number(layer.get("st"), 0.0),
+        stretch: number(layer.get("sr"), 1.0).max(f32::EPSILON),
+        transform: Transform::parse(layer.get("ks")),
+        shapes,
+    }
+}
+
+fn parse_shapes(items: &[Value], unsupported: &mut Vec<String>) -> Vec<Shape> {
+    let mut shapes = Vec::with_capacity(items.len());
+
+    for item in items.iter() {
+        // Скрытые элементы пропускаем полностью
+        if item.get("hd").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        if let Some(shape) = parse_shape(item, unsupported) {
+            shapes.push(shape);
+        }
+    }
+
+    shapes
+}
+
+fn parse_shape(item: &Value, unsupported: &mut Vec<String>) -> Option<Shape> {
+    let get = |key: &str| item.get(key);
+    let ty = get("ty").and_then(Value::as_str).unwrap_or("");
+
+    let shape = match ty {
+        "gr" => {
+            let items = get("it").and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[]);
+            Shape::Group(parse_shapes(items, unsupported))
+        },
+        "tr" => Shape::Transform(Transform::parse(Some(item))),
+        "rc" => Shape::Rect {
+            position: Property::parse(get("p"), &[0.0, 0.0]),
+            size: Property::parse(get("s"), &[0.0, 0.0]),
+            roundness: Property::parse(get("r"), &[0.0]),
+        },
+        "el" => Shape::Ellipse {
+            position: Property::parse(get("p"), &[0.0, 0.0]),
+            size: Property::parse(get("s"), &[0.0, 0.0]),
+        },
+        "sh" => Shape::Path(Property::parse(get("ks"), &[])),
+        "fl" => Shape::Fill {
+            color: Property::parse(get("c"), &[0.0, 0.0, 0.0, 1.0]),
+            opacity: Property::parse(get("o"), &[100.0]),
+            rule: if number(get("r"), 1.0) == 2.0 { FillRule::EvenOdd } else { FillRule::NonZero },
+        },
+        "st" => Shape::Stroke {
+            color: Property::parse(get("c"), &[0.0, 0.0, 0.0, 1.0]),
+            opacity: Property::parse(get("o"), &[100.0]),
+            width: Property::parse(get("w"), &[1.0]),
+            cap: match number(get("lc"), 1.0) as i64 {
+                2 => LineCap::Round,
+                3 => LineCap::Square,
+                _ => LineCap::Butt,
+            },
+            join: match number(get("lj"), 1.0) as i64 {
+                2 => LineJoin::Round,
+                3 => LineJoin::Bevel,
+                _ => LineJoin::Miter,
+            },
+            miter_limit: number(get("ml"), 4.0),
+        },
+        "tm" => Shape::Trim {
+            start: Property::parse(get("s"), &[0.0]),
+            end: Property::parse(get("e"), &[100.0]),
+            offset: Property::parse(get("o"), &[0.0]),
+        },
+        _ => {
+            report(unsupported, &format!("shape type \"{}\"", ty));
+            return None;
+        },
+    };
+
+    Some(shape)
+}
+
+fn report(unsupported: &mut Vec<String>, feature: &str) {
+    if !unsupported.iter().any(|f| f == feature) {
+        unsupported.push(feature.to_string());
+    }
+}
+
+fn number(value: Option<&Value>, default: f32) -> f32 {
+    match value {
+        Some(Value::Number(n)) => n.as_f64().map(|n| n as f32).unwrap_or(default),
+        Some(Value::Bool(b)) => if *b { 1.0 } else { 0.0 },
+        _ => default,
+    }
+}
+
+/// Переводит значение ключевого кадра в плоский массив. Форма пути
+/// записывается как [закрыт, v.x, v.y, i.x, i.y, o.x, o.y, ...]
+fn numbers(value: &Value) -> Vec<f32> {
+    match value {
+        Value::Number(_) => vec![number(Some(value), 0.0)],
+        Value::Array(items) => match items.first() {
+            Some(Value::Object(_)) => numbers(&items[0]),
+            _ => items.iter().map(|v| number(Some(v), 0.0)).collect(),
+        },
+        Value::Object(shape) => {
+            let closed = number(shape.get("c"), 0.0);
+            let points = |key: &str| shape.get(key).and_then(Value::as_array).cloned().unwrap_or_default();
+            let (v, i, o) = (points("v"), points("i"), points("o"));
+
+            let mut result = vec![closed];
+            for k in 0..v.len() {
+                for list in [&v, &i, &o] {
+                    let point = list.get(k).map(numbers).unwrap_or_default();
+                    result.push(point.first().copied().unwrap_or(0.0));
+                    result.push(point.get(1).copied().unwrap_or(0.0));
+                }
+            }
+
+            result
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Точка кривой плавности. Координаты бывают массивами (По одному
+/// значению на ось), используется первое значение
+fn easing(value: Option<&Value>, default: Vec2) -> Vec2 {
+    let axis = |key: &str, default: f32| {
+        let v = value.and_then(|v| v.get(key));
+        match v {
+            Some(Value::Array(items)) => number(items.first(), default),
+            other => number(other, default),
+        }
+    };
+
+    Vec2::new(axis("x", default.x), axis("y", default.y))
+}
+
+/// Кривая плавности как в CSS cubic-bezier. Сначала находим параметр
+/// кривой по x (Метод Ньютона с запасным делением пополам), потом y
+fn bezier_ease(p1: Vec2, p2: Vec2, x: f32) -> f32 {
+    let x = x.clamp(0.0, 1.0);
+
+    let curve = |t: f32, a: f32, b: f32| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+    };
+
+    let derivative = |t: f32, a: f32, b: f32| {
+        let u = 1.0 - t;
+        3.0 * u * u * a + 6.0 * u * t * (b - a) + 3.0 * t * t * (1.0 - b)
+    };
+
+    let mut t = x;
+    for _ in 0..8 {
+        let error = curve(t, p1.x, p2.x) - x;
+        let slope = derivative(t, p1.x, p2.x);
+
+        if error.abs() < 1e-5 {
+            return curve(t, p1.y, p2.y);
+        }
+
+        if slope.abs() < 1e-6 {
+            break;
+        }
+
+        t = (t - error / slope).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    for _ in 0..32 {
+        let value = curve(t, p1.x, p2.x);
+        if (value - x).abs() < 1e-5 {
+            break;
+        }
+
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+
+        t = (low + high) * 0.5;
+    }
+
+    curve(t, p1.y, p2.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(json: &str) -> Property {
+        let value: Value = serde_json::from_str(json).expect("Test property must be valid JSON");
+        Property::parse(Some(&value), &[])
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn static_and_missing_values() {
+        assert_eq!(property(r#"{"k": [1, 2]}"#).value(10.0), vec![1.0, 2.0]);
+        assert_eq!(property(r#"{"k": 5}"#).scalar(10.0), 5.0);
+        assert_eq!(Property::parse(None, &[100.0]).scalar(0.0), 100.0);
+    }
+
+    #[test]
+    fn linear_keyframes_interpolate() {
+        let opacity = property(r#"{"a": 1, "k": [
+            {"t": 10, "s": [0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 20, "s": [100]}
+        ]}"#);
+
+        // До первого и после последнего кадра значение держится
+        assert_close(opacity.scalar(0.0), 0.0);
+        assert_close(opacity.scalar(12.5), 25.0);
+        assert_close(opacity.scalar(15.0), 50.0);
+        assert_close(opacity.scalar(30.0), 100.0);
+    }
+
+    #[test]
+    fn eased_keyframes_follow_bezier() {
+        // Ease in-out: медленно в начале, симметрично относительно середины
+        let value = property(r#"{"a": 1, "k": [
+            {"t": 0, "s": [0], "o": {"x": [0.42], "y": [0]}, "i": {"x": [0.58], "y": [1]}},
+            {"t": 10, "s": [100]}
+        ]}"#);
+
+        assert!(value.scalar(2.0) < 20.0);
+        assert_close(value.scalar(5.0), 50.0);
+        assert_close(value.scalar(2.0) + value.scalar(8.0), 100.0);
+    }
+
+    #[test]
+    fn hold_keyframes_do_not_interpolate() {
+        let value = property(r#"{"a": 1, "k": [
+            {"t": 0, "s": [0, 0], "h": 1},
+            {"t": 10, "s": [10, 20], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 20, "s": [20, 40]}
+        ]}"#);
+
+        assert_eq!(value.vec2(9.9), Vec2::ZERO);
+        assert_eq!(value.vec2(10.0), Vec2::new(10.0, 20.0));
+        assert!((value.vec2(15.0) - Vec2::new(15.0, 30.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn legacy_end_values() {
+        // Старый формат: конец в самом кадре, последний кадр пустой
+        let value = property(r#"{"a": 1, "k": [
+            {"t": 0, "s": [0], "e": [50], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 10}
+        ]}"#);
+
+        assert_close(value.scalar(5.0), 25.0);
+        assert_close(value.scalar(20.0), 50.0);
+    }
+
+    #[test]
+    fn colors_in_bytes_are_normalized() {
+        assert_eq!(property(r#"{"k": [255, 0, 0, 255]}"#).color(0.0), Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(property(r#"{"k": [0, 0.5, 1]}"#).color(0.0), Vec4::new(0.0, 0.5, 1.0, 1.0));
+    }
+
+    #[test]
+    fn unsupported_features_are_reported_once() {
+        let animation = Animation::parse(br#"{"layers": [
+            {"ty": 2}, {"ty": 2},
+            {"ty": 4, "shapes": [{"ty": "gf"}, {"ty": "rc"}]}
+        ]}"#).expect("Test animation must parse");
+
+        assert_eq!(animation.unsupported, vec!["layer type 2", "shape type \"gf\""]);
+        assert_eq!(animation.layers[2].shapes.len(), 1);
+        assert!(Animation::parse(b"{}").is_err());
+    }
+}
\ No newline at end of file
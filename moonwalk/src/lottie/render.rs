@@ -0,0 +1,492 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Affine2, Vec2};
+
+use crate::lottie::model::{Animation, Layer, Position, Shape, Transform};
+use crate::objects::path::{Fill, Path, PathMesh, PathStyle, Stroke};
+
+/// Константа для аппроксимации четверти эллипса кубической кривой
+const KAPPA: f32 = 0.552_284_8;
+
+/// Количество отрезков на одну кривую при обрезке пути
+const TRIM_STEPS: usize = 16;
+
+/// Максимальная глубина цепочки родителей (Защита от циклов)
+const MAX_PARENT_DEPTH: usize = 32;
+
+/// Контур в формате Lottie: вершины с абсолютными контрольными точками
+/// (Вершина, входящая точка, исходящая точка)
+#[derive(Clone)]
+struct Contour {
+    points: Vec<[Vec2; 3]>,
+    closed: bool,
+}
+
+impl Contour {
+    fn transform(&mut self, matrix: Affine2) {
+        for point in self.points.iter_mut() {
+            for p in point.iter_mut() {
+                *p = matrix.transform_point2(*p);
+            }
+        }
+    }
+
+    /// Сегменты контура как кубические кривые
+    fn segments(&self) -> impl Iterator<Item = [Vec2; 4]> + '_ {
+        let count = self.points.len();
+        let segments = if self.closed { count } else { count.saturating_sub(1) };
+
+        (0..segments).map(move |k| {
+            let a = self.points[k];
+            let b = self.points[(k + 1) % count];
+            [a[0], a[2], b[1], b[0]]
+        })
+    }
+}
+
+/// Обрезка пути (Trim paths) в долях длины 0..1
+#[derive(Clone, Copy)]
+struct Trim {
+    start: f32,
+    end: f32,
+    offset: f32,
+}
+
+/// Строит сетку всей анимации на кадре. Слои рисуются снизу вверх
+/// (Первый слой в файле самый верхний)
+pub fn render_frame(animation: &Animation, frame: f32) -> PathMesh {
+    let mut mesh = PathMesh::default();
+
+    for (index, layer) in animation.layers.iter().enumerate().rev() {
+        if !layer.is_drawable() || frame < layer.in_point || frame >= layer.out_point {
+            continue;
+        }
+
+        let local = layer.local_frame(frame);
+        let matrix = layer_matrix(animation, index, frame, 0);
+        let opacity = layer.transform.opacity.scalar(local) / 100.0;
+
+        draw_group(&layer.shapes, local, matrix, opacity, &[], &mut mesh);
+    }
+
+    // Размер холста не зависит от содержимого кадра
+    mesh.natural_size = animation.size;
+    mesh
+}
+
+/// Матрица слоя с учётом цепочки родителей. Время родителя считается
+/// по его собственному смещению
+fn layer_matrix(animation: &Animation, index: usize, frame: f32, depth: usize) -> Affine2 {
+    let layer: &Layer = &animation.layers[index];
+    let local = transform_matrix(&layer.transform, layer.local_frame(frame));
+
+    if depth >= MAX_PARENT_DEPTH {
+        return local;
+    }
+
+    let parent = layer.parent.and_then(|parent| {
+        animation.layers.iter().position(|l| l.index == Some(parent))
+    });
+
+    match parent {
+        Some(parent) => layer_matrix(animation, parent, frame, depth + 1) * local,
+        None => local,
+    }
+}
+
+/// Матрица трансформации Lottie: T(позиция) * R(вращение) * S(масштаб) * T(-якорь)
+fn transform_matrix(transform: &Transform, frame: f32) -> Affine2 {
+    let position = match &transform.position {
+        Position::Joined(p) => p.vec2(frame),
+        Position::Split(x, y) => Vec2::new(x.scalar(frame), y.scalar(frame)),
+    };
+
+    Affine2::from_translation(position)
+        * Affine2::from_angle(transform.rotation.scalar(frame).to_radians())
+        * Affine2::from_scale(transform.scale.vec2(frame) / 100.0)
+        * Affine2::from_translation(-transform.anchor.vec2(frame))
+}
+
+/// Трансформация группы (Элемент tr внутри группы)
+fn group_transform(items: &[Shape], frame: f32) -> (Affine2, f32) {
+    for item in items.iter() {
+        if let Shape::Transform(transform) = item {
+            let opacity = transform.opacity.scalar(frame) / 100.0;
+            return (transform_matrix(transform, frame), opacity);
+        }
+    }
+
+    (Affine2::IDENTITY, 1.0)
+}
+
+/// Обрезки которые действуют на элемент с индексом index. Обрезка
+/// действует на все фигуры выше неё в той же группе и во вложенных
+fn trims_for(items: &[Shape], index: usize, frame: f32, inherited: &[Trim]) -> Vec<Trim> {
+    let mut trims = inherited.to_vec();
+
+    for item in items.iter().skip(index + 1) {
+        if let Shape::Trim { start, end, offset } = item {
+            trims.push(Trim {
+                start: start.scalar(frame) / 100.0,
+                end: end.scalar(frame) / 100.0,
+                offset: offset.scalar(frame) / 360.0,
+            });
+        }
+    }
+
+    trims
+}
+
+/// Рисует группу. Как и в Lottie, заливка или обводка применяется
+/// ко всем фигурам выше неё в группе (Включая вложенные группы),
+/// а элементы выше в списке рисуются поверх нижних
+fn draw_group(items: &[Shape], frame: f32, matrix: Affine2, opacity: f32, trims: &[Trim], mesh: &mut PathMesh) {
+    for index in (0..items.len()).rev() {
+        match &items[index] {
+            Shape::Group(children) => {
+                let (local, local_opacity) = group_transform(children, frame);
+                let child_trims = trims_for(items, index, frame, trims);
+
+                draw_group(children, frame, matrix * local, opacity * local_opacity, &child_trims, mesh);
+            },
+
+            Shape::Fill { color, opacity: fill_opacity, rule } => {
+                let mut color = color.color(frame);
+                color.w *= fill_opacity.scalar(frame) / 100.0 * opacity;
+
+                let style = PathStyle {
+                    fill: Some(Fill { rule: *rule, color }),
+                    stroke: None,
+                };
+
+                draw_style(items, index, frame, matrix, trims, &style, mesh);
+            },
+
+            Shape::Stroke { color, opacity: stroke_opacity, width, cap, join, miter_limit } => {
+                let mut color = color.color(frame);
+                color.w *= stroke_opacity.scalar(frame) / 100.0 * opacity;
+
+                let style = PathStyle::stroked(
+                    Stroke::new(width.scalar(frame), color)
+                        .with_cap(*cap)
+                        .with_join(*join)
+                        .with_miter_limit(*miter_limit)
+                );
+
+                draw_style(items, index, frame, matrix, trims, &style, mesh);
+            },
+
+            _ => {},
+        }
+    }
+}
+
+/// Собирает все фигуры выше стиля в один путь и тесселирует его.
+/// Путь тесселируется в пространстве группы, а затем переводится
+/// матрицей, так обводка масштабируется вместе с фигурой
+fn draw_style(
+    items: &[Shape],
+    index: usize,
+    frame: f32,
+    matrix: Affine2,
+    trims: &[Trim],
+    style: &PathStyle,
+    mesh: &mut PathMesh,
+) {
+    let mut contours = Vec::new();
+    collect_contours(&items[..index], frame, trims, items, &mut contours);
+
+    if contours.is_empty() {
+        return;
+    }
+
+    let mut part = PathMesh::default();
+    part.append(&to_path(&contours), style);
+    mesh.extend(&part, matrix);
+}
+
+/// Собирает контуры фигур в пространстве группы. all_items нужен
+/// чтобы найти обрезки, которые идут после фигуры
+fn collect_contours(items: &[Shape], frame: f32, trims: &[Trim], all_items: &[Shape], out: &mut Vec<Contour>) {
+    for (index, item) in items.iter().enumerate() {
+        let item_trims = trims_for(all_items, index, frame, trims);
+
+        let mut contours = match item {
+            Shape::Group(children) => {
+                let (local, _) = group_transform(children, frame);
+                let mut nested = Vec::new();
+                collect_contours(children, frame, &item_trims, children, &mut nested);
+
+                // Обрезки уже применены внутри вложенной группы
+                for contour in nested.iter_mut() {
+                    contour.transform(local);
+                }
+
+                out.append(&mut nested);
+                continue;
+            },
+            Shape::Rect { position, size, roundness } => {
+                vec![rect_contour(position.vec2(frame), size.vec2(frame), roundness.scalar(frame))]
+            },
+            Shape::Ellipse { position, size } => {
+                vec![ellipse_contour(position.vec2(frame), size.vec2(frame))]
+            },
+            Shape::Path(data) => shape_contour(&data.value(frame)).into_iter().collect(),
+            _ => continue,
+        };
+
+        for trim in item_trims.iter() {
+            contours = contours.iter().flat_map(|c| trim_contour(c, *trim)).collect();
+        }
+
+        out.append(&mut contours);
+    }
+}
+
+fn shape_contour(data: &[f32]) -> Option<Contour> {
+    let (closed, rest) = data.split_first()?;
+    let points = rest.chunks_exact(6)
+        .map(|c| {
+            let vertex = Vec2::new(c[0], c[1]);
+            [vertex, vertex + Vec2::new(c[2], c[3]), vertex + Vec2::new(c[4], c[5])]
+        })
+        .collect();
+
+    Some(Contour { points, closed: *closed > 0.5 })
+}
+
+/// Прямоугольник с центром в position. Как и в Lottie, контур
+/// начинается с правого верхнего угла и идёт по часовой стрелке
+fn rect_contour(center: Vec2, size: Vec2, roundness: f32) -> Contour {
+    let half = size * 0.5;
+    let r = roundness.min(half.x).min(half.y).max(0.0);
+    let (left, top) = (center.x - half.x, center.y - half.y);
+    let (right, bottom) = (center.x + half.x, center.y + half.y);
+
+    if r <= 0.0 {
+        let corner = |x: f32, y: f32| {
+            let p = Vec2::new(x, y);
+            [p, p, p]
+        };
+
+        return Contour {
+            points: vec![corner(right, top), corner(right, bottom), corner(left, bottom), corner(left, top)],
+            closed: true,
+        };
+    }
+
+    let k = r * KAPPA;
+    let point = |x: f32, y: f32, inp: Vec2, out: Vec2| {
+        let p = Vec2::new(x, y);
+        [p, p + inp, p + out]
+    };
+
+    let points = vec![
+        point(right - r, top, Vec2::ZERO, Vec2::new(k, 0.0)),
+        point(right, top + r, Vec2::new(0.0, -k), Vec2::ZERO),
+        point(right, bottom - r, Vec2::ZERO, Vec2::new(0.0, k)),
+        point(right - r, bottom, Vec2::new(k, 0.0), Vec2::ZERO),
+        point(left + r, bottom, Vec2::ZERO, Vec2::new(-k, 0.0)),
+        point(left, bottom - r, Vec2::new(0.0, k), Vec2::ZERO),
+        point(left, top + r, Vec2::ZERO, Vec2::new(0.0, -k)),
+        point(left + r, top, Vec2::new(-k, 0.0), Vec2::ZERO),
+    ];
+
+    Contour { points, closed: true }
+}
+
+/// Эллипс из 4 кубических кривых, начиная с верхней точки
+fn ellipse_contour(center: Vec2, size: Vec2) -> Contour {
+    let r = size * 0.5;
+    let k = r * KAPPA;
+    let point = |offset: Vec2, tangent: Vec2| {
+        let p = center + offset;
+        [p, p - tangent, p + tangent]
+    };
+
+    let points = vec![
+        point(Vec2::new(0.0, -r.y), Vec2::new(k.x, 0.0)),
+        point(Vec2::new(r.x, 0.0), Vec2::new(0.0, k.y)),
+        point(Vec2::new(0.0, r.y), Vec2::new(-k.x, 0.0)),
+        point(Vec2::new(-r.x, 0.0), Vec2::new(0.0, -k.y)),
+    ];
+
+    Contour { points, closed: true }
+}
+
+/// Обрезает контур по длине. Результат это ломаные линии, кривые
+/// аппроксимируются отрезками
+fn trim_contour(contour: &Contour, trim: Trim) -> Vec<Contour> {
+    let (mut start, mut end) = (trim.start.min(trim.end), trim.start.max(trim.end));
+    if start <= 0.0 && end >= 1.0 {
+        return vec![contour.clone()];
+    }
+
+    if end - start <= 0.0 {
+        return Vec::new();
+    }
+
+    start += trim.offset;
+    end += trim.offset;
+
+    let shift = start.floor();
+    start -= shift;
+    end -= shift;
+
+    let polyline = flatten(contour);
+    let total = polyline.last().map(|p| p.1).unwrap_or(0.0);
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    // Обрезка может переходить через конец контура
+    if end > 1.0 {
+        vec![
+            cut(&polyline, start * total, total),
+            cut(&polyline, 0.0, (end - 1.0) * total),
+        ]
+    } else {
+        vec![cut(&polyline, start * total, end * total)]
+    }
+}
+
+/// Ломаная контура с накопленной длиной в каждой точке
+fn flatten(contour: &Contour) -> Vec<(Vec2, f32)> {
+    let mut result = Vec::new();
+    let mut length = 0.0;
+
+    for [p0, p1, p2, p3] in contour.segments() {
+        if result.is_empty() {
+            result.push((p0, 0.0));
+        }
+
+        let mut previous = p0;
+        for step in 1..=TRIM_STEPS {
+            let t = step as f32 / TRIM_STEPS as f32;
+            let u = 1.0 - t;
+            let point = p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t);
+
+            length += point.distance(previous);
+            result.push((point, length));
+            previous = point;
+        }
+    }
+
+    result
+}
+
+fn cut(polyline: &[(Vec2, f32)], from: f32, to: f32) -> Contour {
+    let mut points = Vec::new();
+    let at = |a: (Vec2, f32), b: (Vec2, f32), length: f32| {
+        let k = if b.1 > a.1 { (length - a.1) / (b.1 - a.1) } else { 0.0 };
+        a.0.lerp(b.0, k)
+    };
+
+    for pair in polyline.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if b.1 < from || a.1 > to {
+            continue;
+        }
+
+        if points.is_empty() {
+            let p = at(a, b, from.max(a.1));
+            points.push([p, p, p]);
+        }
+
+        let p = at(a, b, to.min(b.1));
+        points.push([p, p, p]);
+    }
+
+    Contour { points, closed: false }
+}
+
+fn to_path(contours: &[Contour]) -> Path {
+    let mut path = Path::new();
+
+    for contour in contours.iter() {
+        let first = match contour.points.first() {
+            Some(first) => first[0],
+            None => continue,
+        };
+
+        path = path.move_to(first);
+        for [_, c1, c2, to] in contour.segments() {
+            path = path.cubic_to(c1, c2, to);
+        }
+
+        if contour.closed {
+            path = path.close();
+        }
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lottie::tests::animation;
+
+    /// Границы вершин заданного цвета
+    fn bounds(mesh: &PathMesh, color: glam::Vec4) -> Option<(Vec2, Vec2)> {
+        mesh.vertices.iter()
+            .filter(|v| v.color == color)
+            .fold(None, |acc, v| match acc {
+                None => Some((v.position, v.position)),
+                Some((min, max)) => Some((min.min(v.position), max.max(v.position))),
+            })
+    }
+
+    const RED: glam::Vec4 = glam::Vec4::new(1.0, 0.0, 0.0, 1.0);
+    const BLUE: glam::Vec4 = glam::Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+    #[test]
+    fn frame_mesh_follows_animation() {
+        let animation = animation();
+        let first = render_frame(&animation, 0.0);
+
+        assert!(!first.indices.is_empty());
+        assert_eq!(first.natural_size, Vec2::new(200.0, 200.0));
+
+        // Прямоугольник 40x40 вокруг (50, 50), обводка ещё обрезана целиком
+        let (min, max) = bounds(&first, RED).expect("Rectangle must be filled");
+        assert!((min - Vec2::splat(30.0)).length() < 1e-3);
+        assert!((max - Vec2::splat(70.0)).length() < 1e-3);
+        assert!(bounds(&first, BLUE).is_none());
+
+        let middle = render_frame(&animation, 30.0);
+        let (min, max) = bounds(&middle, RED).expect("Rectangle must be filled");
+        assert!((min - Vec2::splat(10.0)).length() < 1e-3);
+        assert!((max - Vec2::splat(90.0)).length() < 1e-3);
+        assert!(bounds(&middle, BLUE).is_some());
+
+        assert_ne!(first.vertices.len(), middle.vertices.len());
+    }
+
+    #[test]
+    fn trim_draws_part_of_the_stroke() {
+        let animation = animation();
+        let half = render_frame(&animation, 30.0);
+        let full = render_frame(&animation, 59.99);
+
+        let (half_min, half_max) = bounds(&half, BLUE).expect("Half of the stroke must be drawn");
+        let (full_min, full_max) = bounds(&full, BLUE).expect("Stroke must be drawn");
+
+        // Эллипс радиусом 20 вокруг (150, 150) с обводкой 4. Контур
+        // начинается сверху и идёт по часовой стрелке, поэтому половина
+        // контура это правая половина эллипса
+        assert!(full_min.x < 130.0 && full_max.x > 170.0);
+        assert!(half_min.x > 145.0 && half_max.x > 170.0);
+        assert!(half_min.y < 130.0 && half_max.y > 170.0);
+    }
+
+    #[test]
+    fn layers_outside_their_range_are_skipped() {
+        let animation = animation();
+        let mesh = render_frame(&animation, 60.0);
+
+        assert!(mesh.vertices.is_empty());
+        assert!(mesh.indices.is_empty());
+    }
+}
\ No newline at end of file
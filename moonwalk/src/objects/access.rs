@@ -0,0 +1,152 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Vec2, Vec4};
+
+use crate::objects::ObjectId;
+use crate::objects::store::ObjectStore;
+
+/// Прямой доступ к массивам хранилища (SoA) для систем частиц и
+/// других массовых обновлений. Индекс в любом массиве это
+/// ObjectId::index(). Какие объекты поменялись неизвестно, поэтому
+/// получение доступа делает хранилище грязным: все батчи, сетка
+/// отсечения и общий порядок собираются заново (Для части объектов
+/// лучше массовые set_* функции, они переписывают только их).
+///     [!] z индекс сюда не входит, потому что его изменение
+///         вызывает сортировку. Для него есть set_z_indices
+pub struct ObjectsMut<'a> {
+    pub positions: &'a mut [Vec2],
+    pub sizes: &'a mut [Vec2],
+    pub colors: &'a mut [Vec4],
+    pub rotations: &'a mut [f32],
+    pub scales: &'a mut [Vec2],
+    pub pivots: &'a mut [Vec2],
+    pub opacities: &'a mut [f32],
+    pub visible: &'a mut [bool],
+}
+
+/// Записывает значения по индексам объектов. Лишние элементы более
+/// длинного среза и ID за пределами хранилища пропускаются
+#[inline(always)]
+fn write_many<T: Copy>(target: &mut [T], ids: &[ObjectId], values: &[T]) {
+    if ids.len() != values.len() {
+        log::warn!("Bulk update got {} ids and {} values", ids.len(), values.len());
+    }
+
+    let mut skipped = 0;
+    for (id, &value) in ids.iter().zip(values) {
+        match target.get_mut(id.index()) {
+            Some(slot) => *slot = value,
+            None => skipped += 1,
+        }
+    }
+
+    if skipped > 0 {
+        log::warn!("Bulk update skipped {} ids outside the store", skipped);
+    }
+}
+
+/// Геттеры и массовые обновления свойств объектов
+impl ObjectStore {
+    #[inline(always)]
+    fn mark_many(&mut self, ids: &[ObjectId]) {
+        for id in ids {
+            if id.index() < self.changed_marks.len() {
+                self.mark_changed(id.index());
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_position(&self, id: ObjectId) -> Vec2 {
+        self.positions[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_size(&self, id: ObjectId) -> Vec2 {
+        self.sizes[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_color(&self, id: ObjectId) -> Vec4 {
+        self.colors[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_rotation(&self, id: ObjectId) -> f32 {
+        self.rotations[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_z_index(&self, id: ObjectId) -> f32 {
+        self.z_indices[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_scale(&self, id: ObjectId) -> Vec2 {
+        self.scales[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_pivot(&self, id: ObjectId) -> Vec2 {
+        self.pivots[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_opacity(&self, id: ObjectId) -> f32 {
+        self.opacities[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self, id: ObjectId) -> bool {
+        self.visible[id.index()]
+    }
+
+    #[inline(always)]
+    pub fn get_rounded(&self, id: ObjectId) -> Vec4 {
+        self.rect_radii[id.index()]
+    }
+
+    pub fn config_positions(&mut self, ids: &[ObjectId], values: &[Vec2]) {
+        write_many(&mut self.positions, ids, values);
+        self.mark_many(ids);
+    }
+
+    pub fn config_sizes(&mut self, ids: &[ObjectId], values: &[Vec2]) {
+        write_many(&mut self.sizes, ids, values);
+        self.mark_many(ids);
+    }
+
+    pub fn config_colors(&mut self, ids: &[ObjectId], values: &[Vec4]) {
+        write_many(&mut self.colors, ids, values);
+        self.mark_many(ids);
+    }
+
+    pub fn config_rotations(&mut self, ids: &[ObjectId], values: &[f32]) {
+        write_many(&mut self.rotations, ids, values);
+        self.mark_many(ids);
+    }
+
+    pub fn config_z_indices(&mut self, ids: &[ObjectId], values: &[f32]) {
+        write_many(&mut self.z_indices, ids, values);
+        self.dirty = true;
+        self.z_dirty = true;
+    }
+
+    /// Мутабельный доступ ко всем массивам сразу. Какие объекты
+    /// поменялись неизвестно, поэтому батчи пересобираются целиком
+    pub fn objects_mut(&mut self) -> ObjectsMut<'_> {
+        self.dirty = true;
+
+        ObjectsMut {
+            positions: &mut self.positions,
+            sizes: &mut self.sizes,
+            colors: &mut self.colors,
+            rotations: &mut self.rotations,
+            scales: &mut self.scales,
+            pivots: &mut self.pivots,
+            opacities: &mut self.opacities,
+            visible: &mut self.visible,
+        }
+    }
+}
\ No newline at end of file
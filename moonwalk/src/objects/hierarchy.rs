@@ -0,0 +1,352 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Vec2, Vec3, Vec4};
+
+use crate::easy_gpu::MatrixStack;
+use crate::objects::ObjectId;
+use crate::objects::store::ObjectStore;
+
+/// То, что ребёнок наследует от родителя кроме матрицы
+#[derive(Clone, Copy)]
+struct Inherited {
+    rotation: f32,
+    scale: Vec2,
+    opacity: f32,
+    visible: bool,
+}
+
+impl Inherited {
+    const ROOT: Self = Self {
+        rotation: 0.0,
+        scale: Vec2::ONE,
+        opacity: 1.0,
+        visible: true,
+    };
+}
+
+/// Иерархия объектов. Позиция, вращение и масштаб ребёнка задаются
+/// относительно родителя: точка (0, 0) ребёнка это левый верхний угол
+/// родителя, а вращение и масштаб родителя (Вокруг его точки опоры)
+/// применяются ко всем детям. Прозрачность умножается вниз по
+/// иерархии, а скрытый родитель скрывает всех детей. Мировые
+/// трансформации считаются в prepare через матричный стэк, поэтому
+/// двигать нужно только родителя
+impl ObjectStore {
+    /// Делает объект ребёнком другого объекта. Возвращает false если
+    /// это создало бы цикл (Родитель уже является потомком ребёнка)
+    pub fn set_parent(&mut self, child_id: ObjectId, parent_id: ObjectId) -> bool {
+        let (child, parent) = (child_id.index(), parent_id.index());
+
+        if child >= self.parents.len() || parent >= self.parents.len() {
+            return false;
+        }
+
+        // Поднимаемся от нового родителя к корню, ребёнка там быть
+        // не должно
+        let mut current = Some(parent);
+        while let Some(index) = current {
+            if index == child {
+                log::warn!("Object {} can't be a parent of its own ancestor", parent);
+                return false;
+            }
+
+            current = self.parents[index].map(|p| p.index());
+        }
+
+        self.detach(child);
+        self.parents[child] = Some(parent_id);
+        self.children.entry(parent).or_default().push(child);
+        self.dirty = true;
+
+        true
+    }
+
+    /// Отвязывает объект от родителя. Объект остаётся на месте в
+    /// локальных координатах (Которые теперь становятся мировыми)
+    pub fn clear_parent(&mut self, child: ObjectId) {
+        if child.index() < self.parents.len() {
+            self.detach(child.index());
+            self.dirty = true;
+        }
+    }
+
+    pub fn get_parent(&self, child: ObjectId) -> Option<ObjectId> {
+        self.parents.get(child.index()).copied().flatten()
+    }
+
+    fn detach(&mut self, child: usize) {
+        let parent = match self.parents[child].take() {
+            Some(parent) => parent.index(),
+            None => return,
+        };
+
+        if let Some(siblings) = self.children.get_mut(&parent) {
+            siblings.retain(|&c| c != child);
+
+            if siblings.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+    }
+
+    /// Есть ли хотя бы одна связь родитель-ребёнок
+    #[inline]
+    pub fn has_hierarchy(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// Мировые позиции объектов. Без иерархии они совпадают с
+    /// локальными, поэтому копия не делается
+    #[inline]
+    pub fn world_positions(&self) -> &[Vec2] {
+        if self.has_hierarchy() { &self.world_positions } else { &self.positions }
+    }
+
+    #[inline]
+    pub fn world_rotations(&self) -> &[f32] {
+        if self.has_hierarchy() { &self.world_rotations } else { &self.rotations }
+    }
+
+    #[inline]
+    pub fn world_scales(&self) -> &[Vec2] {
+        if self.has_hierarchy() { &self.world_scales } else { &self.scales }
+    }
+
+    #[inline]
+    pub fn world_opacities(&self) -> &[f32] {
+        if self.has_hierarchy() { &self.world_opacities } else { &self.opacities }
+    }
+
+    #[inline]
+    pub fn world_visible(&self) -> &[bool] {
+        if self.has_hierarchy() { &self.world_visible } else { &self.visible }
+    }
+
+    /// Итоговый цвет объекта: альфа канал умножается на прозрачность
+    /// (С учётом родителей). Базовый цвет при этом не меняется
+    #[inline]
+    pub fn final_color(&self, index: usize) -> Vec4 {
+        let mut color = self.colors[index];
+        color.w *= self.world_opacities()[index];
+        color
+    }
+
+    /// Пересчитывает мировые трансформации. Объекты вне иерархии
+    /// просто копируются, а каждое дерево обходится в глубину.
+    /// Изменение родителя меняет мировые данные всех его потомков,
+    /// поэтому они тоже отмечаются изменёнными
+    pub fn update_world_transforms(&mut self, stack: &mut MatrixStack) {
+        if !self.has_hierarchy() {
+            return;
+        }
+
+        if !self.dirty {
+            self.mark_descendants();
+        }
+
+        self.world_positions.clone_from(&self.positions);
+        self.world_rotations.clone_from(&self.rotations);
+        self.world_scales.clone_from(&self.scales);
+        self.world_opacities.clone_from(&self.opacities);
+        self.world_visible.clone_from(&self.visible);
+
+        let roots: Vec<usize> = self.children.keys()
+            .copied()
+            .filter(|&index| self.parents[index].is_none())
+            .collect();
+
+        for root in roots {
+            stack.set_identity();
+            self.visit(root, Inherited::ROOT, stack);
+        }
+    }
+
+    fn mark_descendants(&mut self) {
+        let mut pending: Vec<usize> = self.changed.iter()
+            .copied()
+            .filter(|index| self.children.contains_key(index))
+            .collect();
+
+        while let Some(index) = pending.pop() {
+            if let Some(children) = self.children.get(&index).cloned() {
+                for child in children {
+                    self.mark_changed(child);
+                    pending.push(child);
+                }
+            }
+        }
+    }
+
+    /// Матрица объекта: T(позиция) * T(опора) * R(вращение) * S(масштаб) * T(-опора).
+    /// Дети получают её как систему координат.
+    ///     [!] Неравномерный масштаб родителя вместе с вращением
+    ///         ребёнка даёт сдвиг (skew), который инстанс передать не
+    ///         может, поэтому мировой масштаб это просто произведение
+    fn visit(&mut self, index: usize, parent: Inherited, stack: &mut MatrixStack) {
+        let pivot = self.sizes[index] * self.pivots[index];
+        let world = Inherited {
+            rotation: parent.rotation + self.rotations[index],
+            scale: parent.scale * self.scales[index],
+            opacity: parent.opacity * self.opacities[index],
+            visible: parent.visible && self.visible[index],
+        };
+
+        stack.push();
+        stack.translate((self.positions[index] + pivot).extend(0.0));
+
+        // Объект вращается и масштабируется вокруг точки опоры в
+        // шейдере, поэтому в мировую позицию записывается левый
+        // верхний угол без трансформаций
+        let world_pivot = stack.model.transform_point3(Vec3::ZERO);
+        self.world_positions[index] = world_pivot.truncate() - pivot;
+        self.world_rotations[index] = world.rotation;
+        self.world_scales[index] = world.scale;
+        self.world_opacities[index] = world.opacity;
+        self.world_visible[index] = world.visible;
+
+        if let Some(children) = self.children.get(&index).cloned() {
+            stack.rotate_z(self.rotations[index].to_degrees());
+            stack.scale(self.scales[index].extend(1.0));
+            stack.translate((-pivot).extend(0.0));
+
+            for child in children {
+                self.visit(child, world, stack);
+            }
+        }
+
+        stack.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Родитель 100x100 в (100, 100), ребёнок 20x20 в (50, 0) и внук
+    /// 10x10 в (10, 10). Точки опоры в центре. Мировые трансформации
+    /// уже посчитаны, флаги хранилища сняты
+    fn family(stack: &mut MatrixStack) -> (ObjectStore, ObjectId, ObjectId, ObjectId) {
+        let mut store = ObjectStore::new();
+        let (parent, child, grandchild) = (store.new_rect(), store.new_rect(), store.new_rect());
+
+        store.config_position(parent, Vec2::new(100.0, 100.0));
+        store.config_position(child, Vec2::new(50.0, 0.0));
+        store.config_size(child, Vec2::splat(20.0));
+        store.config_position(grandchild, Vec2::new(10.0, 10.0));
+        store.config_size(grandchild, Vec2::splat(10.0));
+
+        assert!(store.set_parent(child, parent));
+        assert!(store.set_parent(grandchild, child));
+
+        store.update_world_transforms(stack);
+        store.clear_changes();
+
+        (store, parent, child, grandchild)
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!((actual - expected).length() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn grandchild_follows_parent_transform() {
+        let mut stack = MatrixStack::new();
+        let (mut store, parent, child, grandchild) = family(&mut stack);
+
+        assert_close(store.world_positions()[child.index()], Vec2::new(150.0, 100.0));
+        assert_close(store.world_positions()[grandchild.index()], Vec2::new(160.0, 110.0));
+
+        // Поворот и масштаб родителя вокруг его центра (150, 150)
+        // без полной пересборки хранилища
+        store.config_rotation(parent, std::f32::consts::FRAC_PI_2);
+        store.config_scale(parent, Vec2::splat(2.0));
+        store.config_scale(grandchild, Vec2::splat(0.5));
+        assert!(!store.dirty);
+
+        store.update_world_transforms(&mut stack);
+
+        // Центр внука в системе родителя (65, 15): от центра родителя
+        // это (15, -35), после масштаба (30, -70), после поворота (70, 30)
+        assert_close(store.world_positions()[child.index()], Vec2::new(220.0, 160.0));
+        assert_close(store.world_positions()[grandchild.index()], Vec2::new(215.0, 175.0));
+
+        assert_eq!(store.world_rotations()[grandchild.index()], std::f32::consts::FRAC_PI_2);
+        assert_close(store.world_scales()[child.index()], Vec2::splat(2.0));
+        assert_close(store.world_scales()[grandchild.index()], Vec2::ONE);
+
+        // Локальные значения потомков не меняются
+        assert_eq!(store.positions[grandchild.index()], Vec2::new(10.0, 10.0));
+        assert_eq!(store.rotations[grandchild.index()], 0.0);
+
+        // Потомки отмечены изменёнными, чтобы батчи переписали их
+        assert!(store.changed.contains(&child.index()));
+        assert!(store.changed.contains(&grandchild.index()));
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let mut stack = MatrixStack::new();
+        let (mut store, parent, _, grandchild) = family(&mut stack);
+
+        assert!(!store.set_parent(parent, grandchild));
+        assert!(!store.set_parent(parent, parent));
+        assert_eq!(store.get_parent(parent), None);
+
+        // Отвязанный внук снова в мировых координатах
+        store.clear_parent(grandchild);
+        store.update_world_transforms(&mut stack);
+        assert_close(store.world_positions()[grandchild.index()], Vec2::new(10.0, 10.0));
+    }
+
+    #[test]
+    fn opacity_and_visibility_propagate() {
+        let mut stack = MatrixStack::new();
+        let (mut store, parent, child, grandchild) = family(&mut stack);
+        let other = store.new_rect();
+        store.update_world_transforms(&mut stack);
+        store.clear_changes();
+
+        store.config_color(grandchild, Vec4::new(1.0, 0.0, 0.0, 0.8));
+        store.config_opacity(parent, 0.5);
+        store.config_opacity(child, 0.5);
+        store.config_opacity(grandchild, 0.5);
+        store.update_world_transforms(&mut stack);
+
+        assert_eq!(store.world_opacities()[parent.index()], 0.5);
+        assert_eq!(store.world_opacities()[child.index()], 0.25);
+        assert_eq!(store.world_opacities()[grandchild.index()], 0.125);
+        assert_eq!(store.final_color(grandchild.index()), Vec4::new(1.0, 0.0, 0.0, 0.1));
+        assert_eq!(store.colors[grandchild.index()].w, 0.8);
+        store.clear_changes();
+
+        // Изменение только родителя отмечает всех потомков, но не
+        // посторонние объекты
+        store.config_opacity(parent, 1.0);
+        store.update_world_transforms(&mut stack);
+
+        assert_eq!(store.changed, vec![parent.index(), child.index(), grandchild.index()]);
+        assert!(!store.changed.contains(&other.index()));
+        assert_eq!(store.world_opacities()[grandchild.index()], 0.25);
+        store.clear_changes();
+
+        // Скрытый ребёнок скрывает внука, но не родителя
+        store.config_visible(child, false);
+        store.update_world_transforms(&mut stack);
+
+        assert_eq!(
+            [parent, child, grandchild].map(|id| store.world_visible()[id.index()]),
+            [true, false, false],
+        );
+        assert!(store.visible[grandchild.index()]);
+
+        store.config_visible(child, true);
+        store.config_visible(parent, false);
+        store.update_world_transforms(&mut stack);
+
+        assert_eq!(
+            [parent, child, grandchild, other].map(|id| store.world_visible()[id.index()]),
+            [false, false, false, true],
+        );
+    }
+}
\ No newline at end of file
@@ -2,6 +2,10 @@
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
 pub mod store;
+pub mod path;
+pub mod svg;
+pub mod hierarchy;
+pub mod access;
 
 /// Айди объекта
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -11,16 +15,53 @@ pub struct ObjectId(pub usize);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct ShaderId(pub u32);
 
+/// Айди текстуры
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextureId(pub u32);
+
+/// Область обрезки потомков контейнера. rect это x, y, ширина и
+/// высота в локальных координатах контейнера (Точка (0, 0) это его
+/// левый верхний угол), radii это скругление углов как у set_rounded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipRegion {
+    pub rect: glam::Vec4,
+    pub radii: glam::Vec4,
+}
+
+/// Как маска обрезает содержимое группы
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskMode {
+    /// Форма объекта маски пишется в трафарет: прямоугольник (Со
+    /// скруглением), прямоугольник изображения или путь. Содержимое
+    /// видно только внутри формы
+    Stencil,
+    /// Альфа текстуры изображения маски умножает содержимое группы.
+    /// Только для изображений, другие объекты работают как Stencil
+    Alpha,
+}
+
+/// Маска группы: объект, чья форма или альфа обрезает потомков
+/// группы
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectMask {
+    pub object: ObjectId,
+    pub mode: MaskMode,
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ObjectType {
     Rect = 1,
+    Image = 2,
+    Path = 3,
 }
 
 impl ObjectType {
     pub fn from_u8(v: u8) -> Option<Self> {
         match v {
             1 => Some(Self::Rect),
+            2 => Some(Self::Image),
+            3 => Some(Self::Path),
             _ => None,
         }
     }
@@ -0,0 +1,487 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Affine2, Vec2, Vec4};
+use lyon_tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex,
+    StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+};
+use lyon_tessellation::geom::{Angle, Arc};
+use lyon_tessellation::math::{point, vector};
+use lyon_tessellation::path::Path as LyonPath;
+
+/// Точность аппроксимации кривых при тесселяции (В пикселях)
+const TOLERANCE: f32 = 0.1;
+
+/// Команда векторного пути. Координаты локальные, точка (0, 0)
+/// совпадает с позицией объекта
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(Vec2), // Начать новый контур
+    LineTo(Vec2), // Прямая линия
+    QuadTo(Vec2, Vec2), // Квадратичная кривая Безье (Контрольная точка, конец)
+    CubicTo(Vec2, Vec2, Vec2), // Кубическая кривая Безье (2 контрольные точки, конец)
+
+    // Дуга эллипса (Центр, радиусы, начальный угол, угол дуги).
+    // Углы в радианах, положительный угол дуги идёт по часовой
+    // стрелке (Ось Y направлена вниз)
+    Arc(Vec2, Vec2, f32, f32),
+
+    Close, // Замкнуть контур
+}
+
+/// Векторный путь из команд. Строится цепочкой вызовов:
+/// let path = Path::new()
+///     .move_to(Vec2::new(0.0, 0.0))
+///     .cubic_to(Vec2::new(50.0, -40.0), Vec2::new(100.0, 40.0), Vec2::new(150.0, 0.0))
+///     .close();
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self { commands: Vec::new() }
+    }
+
+    pub fn move_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::MoveTo(to));
+        self
+    }
+
+    pub fn line_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::LineTo(to));
+        self
+    }
+
+    pub fn quad_to(mut self, ctrl: Vec2, to: Vec2) -> Self {
+        self.commands.push(PathCommand::QuadTo(ctrl, to));
+        self
+    }
+
+    pub fn cubic_to(mut self, ctrl1: Vec2, ctrl2: Vec2, to: Vec2) -> Self {
+        self.commands.push(PathCommand::CubicTo(ctrl1, ctrl2, to));
+        self
+    }
+
+    /// Дуга эллипса. Если контур уже начат, то до начала дуги
+    /// проводится прямая линия
+    pub fn arc(mut self, center: Vec2, radii: Vec2, start_angle: f32, sweep_angle: f32) -> Self {
+        self.commands.push(PathCommand::Arc(center, radii, start_angle, sweep_angle));
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Собирает путь lyon из команд. Lyon требует строгую пару
+    /// begin/end для каждого контура, поэтому тут отслеживается
+    /// открыт ли сейчас контур
+    fn to_lyon(&self) -> LyonPath {
+        let mut builder = LyonPath::builder();
+        let mut open = false;
+        let mut current = Vec2::ZERO;
+        let mut start = Vec2::ZERO;
+
+        for command in self.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    if open {
+                        builder.end(false);
+                    }
+
+                    builder.begin(point(to.x, to.y));
+                    open = true;
+                    start = to;
+                    current = to;
+                },
+
+                PathCommand::Close => {
+                    if open {
+                        builder.end(true);
+                        open = false;
+                    }
+
+                    current = start;
+                },
+
+                PathCommand::Arc(center, radii, start_angle, sweep_angle) => {
+                    let arc = Arc {
+                        center: point(center.x, center.y),
+                        radii: vector(radii.x, radii.y),
+                        start_angle: Angle::radians(start_angle),
+                        sweep_angle: Angle::radians(sweep_angle),
+                        x_rotation: Angle::radians(0.0),
+                    };
+
+                    let from = arc.from();
+                    if open {
+                        builder.line_to(from);
+                    } else {
+                        builder.begin(from);
+                        open = true;
+                        start = Vec2::new(from.x, from.y);
+                    }
+
+                    arc.for_each_quadratic_bezier(&mut |curve| {
+                        builder.quadratic_bezier_to(curve.ctrl, curve.to);
+                    });
+
+                    let to = arc.to();
+                    current = Vec2::new(to.x, to.y);
+                },
+
+                // Линии и кривые без открытого контура начинаются
+                // из последней точки (Как в SVG)
+                PathCommand::LineTo(to) | PathCommand::QuadTo(_, to) | PathCommand::CubicTo(_, _, to) => {
+                    if !open {
+                        builder.begin(point(current.x, current.y));
+                        open = true;
+                        start = current;
+                    }
+
+                    match *command {
+                        PathCommand::QuadTo(c, _) => {
+                            builder.quadratic_bezier_to(point(c.x, c.y), point(to.x, to.y));
+                        },
+                        PathCommand::CubicTo(c1, c2, _) => {
+                            builder.cubic_bezier_to(point(c1.x, c1.y), point(c2.x, c2.y), point(to.x, to.y));
+                        },
+                        _ => {
+                            builder.line_to(point(to.x, to.y));
+                        },
+                    }
+
+                    current = to;
+                },
+            }
+        }
+
+        if open {
+            builder.end(false);
+        }
+
+        builder.build()
+    }
+}
+
+/// Правило заливки для самопересекающихся путей и путей с дырами
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero, // Точка внутри, если сумма направлений контуров не ноль
+
+    EvenOdd, // Точка внутри, если её пересекает нечётное число контуров
+}
+
+/// Соединение сегментов обводки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    #[default]
+    Miter, // Острый угол (Обрезается по miter_limit)
+
+    Round, // Скруглённый угол
+    Bevel, // Срезанный угол
+}
+
+/// Окончание незамкнутой обводки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    #[default]
+    Butt, // Обрезается ровно в конечной точке
+
+    Round, // Полукруг
+    Square, // Квадрат, выходящий за конечную точку на половину толщины
+}
+
+/// Заливка пути
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    pub rule: FillRule,
+    pub color: Vec4,
+}
+
+/// Обводка пути
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    pub color: Vec4,
+}
+
+impl Stroke {
+    pub fn new(width: f32, color: Vec4) -> Self {
+        Self {
+            width,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            color,
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+}
+
+/// Стиль пути. Заливка и обводка независимы, можно использовать
+/// их одновременно (Обводка рисуется поверх заливки). Цвет объекта
+/// умножается на цвета стиля, поэтому работает как тонировка
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PathStyle {
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+impl PathStyle {
+    /// Только заливка с правилом NonZero
+    pub fn filled(color: Vec4) -> Self {
+        Self {
+            fill: Some(Fill { rule: FillRule::NonZero, color }),
+            stroke: None,
+        }
+    }
+
+    /// Только обводка
+    pub fn stroked(stroke: Stroke) -> Self {
+        Self { fill: None, stroke: Some(stroke) }
+    }
+
+    pub fn with_fill_rule(mut self, rule: FillRule) -> Self {
+        if let Some(fill) = &mut self.fill {
+            fill.rule = rule;
+        }
+
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = Some(stroke);
+        self
+    }
+}
+
+/// Вершина тесселированного пути в локальных координатах
+#[derive(Debug, Clone, Copy)]
+pub struct MeshVertex {
+    pub position: Vec2,
+    pub color: Vec4,
+}
+
+/// Готовая сетка треугольников пути. Хранится в хранилище объектов
+/// и пересчитывается только при изменении пути или стиля
+#[derive(Debug, Clone, Default)]
+pub struct PathMesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+
+    // Естественный размер пути (Правый нижний угол сетки). Размер
+    // объекта растягивает путь относительно этого размера
+    pub natural_size: Vec2,
+}
+
+impl PathMesh {
+    /// Тесселирует путь и добавляет треугольники в сетку
+    pub fn append(&mut self, path: &Path, style: &PathStyle) {
+        let lyon_path = path.to_lyon();
+        let mut buffers: VertexBuffers<MeshVertex, u32> = VertexBuffers::new();
+
+        if let Some(fill) = style.fill {
+            let rule = match fill.rule {
+                FillRule::NonZero => lyon_tessellation::FillRule::NonZero,
+                FillRule::EvenOdd => lyon_tessellation::FillRule::EvenOdd,
+            };
+
+            let options = FillOptions::tolerance(TOLERANCE).with_fill_rule(rule);
+            let result = FillTessellator::new().tessellate_path(
+                &lyon_path,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| MeshVertex {
+                    position: Vec2::new(v.position().x, v.position().y),
+                    color: fill.color,
+                }),
+            );
+
+            if let Err(e) = result {
+                log::warn!("Path fill tessellation failed: {:?}", e);
+            }
+
+            self.push_buffers(&mut buffers);
+        }
+
+        if let Some(stroke) = style.stroke {
+            let options = StrokeOptions::tolerance(TOLERANCE)
+                .with_line_width(stroke.width)
+                .with_line_join(match stroke.join {
+                    LineJoin::Miter => lyon_tessellation::LineJoin::Miter,
+                    LineJoin::Round => lyon_tessellation::LineJoin::Round,
+                    LineJoin::Bevel => lyon_tessellation::LineJoin::Bevel,
+                })
+                .with_line_cap(match stroke.cap {
+                    LineCap::Butt => lyon_tessellation::LineCap::Butt,
+                    LineCap::Round => lyon_tessellation::LineCap::Round,
+                    LineCap::Square => lyon_tessellation::LineCap::Square,
+                })
+                // [!] Lyon паникует при лимите меньше 1
+                .with_miter_limit(stroke.miter_limit.max(1.0));
+
+            let result = StrokeTessellator::new().tessellate_path(
+                &lyon_path,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| MeshVertex {
+                    position: Vec2::new(v.position().x, v.position().y),
+                    color: stroke.color,
+                }),
+            );
+
+            if let Err(e) = result {
+                log::warn!("Path stroke tessellation failed: {:?}", e);
+            }
+
+            self.push_buffers(&mut buffers);
+        }
+    }
+
+    /// Переносит треугольники из буферов lyon в сетку со смещением
+    /// индексов и обновляет естественный размер
+    fn push_buffers(&mut self, buffers: &mut VertexBuffers<MeshVertex, u32>) {
+        let base = self.vertices.len() as u32;
+
+        for vertex in buffers.vertices.iter() {
+            self.natural_size = self.natural_size.max(vertex.position);
+        }
+
+        self.vertices.append(&mut buffers.vertices);
+        self.indices.extend(buffers.indices.drain(..).map(|i| i + base));
+    }
+
+    /// Добавляет другую сетку, переводя её вершины через трансформацию
+    pub fn extend(&mut self, other: &PathMesh, transform: Affine2) {
+        let base = self.vertices.len() as u32;
+
+        for vertex in other.vertices.iter() {
+            let position = transform.transform_point2(vertex.position);
+            self.natural_size = self.natural_size.max(position);
+
+            self.vertices.push(MeshVertex { position, color: vertex.color });
+        }
+
+        self.indices.extend(other.indices.iter().map(|i| i + base));
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.natural_size = Vec2::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon_tessellation::path::Event;
+
+    const RED: Vec4 = Vec4::new(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Vec4 = Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+    fn square() -> Path {
+        Path::new()
+            .move_to(Vec2::new(0.0, 0.0))
+            .line_to(Vec2::new(100.0, 0.0))
+            .line_to(Vec2::new(100.0, 100.0))
+            .line_to(Vec2::new(0.0, 100.0))
+            .close()
+    }
+
+    /// Контуры lyon пути: количество сегментов и замкнутость
+    fn contours(path: &Path) -> Vec<(usize, bool)> {
+        let mut contours = Vec::new();
+        let mut segments = 0;
+
+        for event in path.to_lyon().iter() {
+            match event {
+                Event::Begin { .. } => segments = 0,
+                Event::End { close, .. } => contours.push((segments, close)),
+                _ => segments += 1,
+            }
+        }
+
+        contours
+    }
+
+    #[test]
+    fn contours_are_balanced() {
+        assert_eq!(contours(&square()), vec![(3, true)]);
+
+        // Новый move_to завершает открытый контур, а линия без
+        // move_to начинается из последней точки
+        let path = Path::new()
+            .move_to(Vec2::ZERO)
+            .line_to(Vec2::X)
+            .move_to(Vec2::Y)
+            .line_to(Vec2::ONE)
+            .close()
+            .line_to(Vec2::X);
+
+        assert_eq!(contours(&path), vec![(1, false), (1, true), (1, false)]);
+    }
+
+    #[test]
+    fn filled_square_is_two_triangles() {
+        let mut mesh = PathMesh::default();
+        mesh.append(&square(), &PathStyle::filled(RED));
+
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 6);
+        assert!(mesh.vertices.iter().all(|v| v.color == RED));
+        assert!(mesh.indices.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+        assert_eq!(mesh.natural_size, Vec2::new(100.0, 100.0));
+    }
+
+    #[test]
+    fn stroked_square_goes_around_the_outline() {
+        let mut mesh = PathMesh::default();
+        mesh.append(&square(), &PathStyle::stroked(Stroke::new(10.0, BLUE)));
+
+        // По внутренней и внешней вершине на каждый угол (Lyon
+        // повторяет пару начала там где контур замыкается), по два
+        // треугольника на каждую сторону
+        assert_eq!(mesh.vertices.len(), 10);
+        assert_eq!(mesh.indices.len(), 24);
+        assert!(mesh.vertices.iter().all(|v| v.color == BLUE));
+
+        // Обводка выходит за путь на половину толщины
+        assert_eq!(mesh.natural_size, Vec2::new(105.0, 105.0));
+    }
+
+    #[test]
+    fn stroke_is_appended_after_fill() {
+        let mut mesh = PathMesh::default();
+        mesh.append(&square(), &PathStyle::filled(RED).with_stroke(Stroke::new(10.0, BLUE)));
+
+        assert_eq!(mesh.vertices.len(), 14);
+        assert_eq!(mesh.indices.len(), 30);
+        assert!(mesh.vertices[..4].iter().all(|v| v.color == RED));
+        assert!(mesh.vertices[4..].iter().all(|v| v.color == BLUE));
+
+        // Индексы обводки смещены на вершины заливки
+        assert!(mesh.indices[6..].iter().all(|&i| (4..14).contains(&i)));
+    }
+}
\ No newline at end of file
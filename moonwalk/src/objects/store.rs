@@ -2,9 +2,12 @@
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
 use glam::{Vec2, Vec4};
+use rustc_hash::FxHashMap;
 
 use crate::objects;
-use crate::objects::ObjectId;
+use crate::objects::{ClipRegion, ObjectId, ObjectMask, ShaderId, TextureId};
+use crate::objects::path::{Path, PathMesh, PathStyle};
+use crate::easy_gpu::SamplerOptions;
 
 /// Хранилище для объектов
 pub struct ObjectStore {
@@ -14,15 +17,65 @@ pub struct ObjectStore {
     pub colors: Vec<Vec4>,
     pub rotations: Vec<f32>,
     pub z_indices: Vec<f32>,
+    pub scales: Vec<Vec2>,
+    pub pivots: Vec<Vec2>,
+    pub opacities: Vec<f32>,
+    pub visible: Vec<bool>,
     
     // Айди объектов
     pub rect_ids: Vec<ObjectId>,
+    pub image_ids: Vec<ObjectId>,
+    pub path_ids: Vec<ObjectId>,
     
     // Данные специфичные для прямоугольника
     pub rect_radii: Vec<Vec4>,
 
+    // Пользовательские эффекты прямоугольников и их параметры.
+    // Хранятся только для тех, у кого эффект задан. Такие
+    // прямоугольники рисует батч эффектов, а не батч прямоугольников
+    pub effects: FxHashMap<usize, ShaderId>,
+    pub effect_params: FxHashMap<usize, [Vec4; 2]>,
+
+    // Данные специфичные для изображения. Сэмплер хранится только
+    // для тех изображений, у которых он отличается от сэмплера текстуры
+    pub image_textures: Vec<TextureId>,
+    pub image_samplers: FxHashMap<usize, SamplerOptions>,
+
+    // Данные специфичные для векторного пути. Хранится уже
+    // тесселированная сетка, она пересчитывается только в set_path
+    pub path_meshes: FxHashMap<usize, PathMesh>,
+
+    // Иерархия. Родитель хранится для каждого объекта, а список детей
+    // только для тех, у кого они есть
+    pub parents: Vec<Option<ObjectId>>,
+    pub children: FxHashMap<usize, Vec<usize>>,
+
+    // Области обрезки контейнеров. Обрезаются потомки контейнера,
+    // а не он сам
+    pub clips: FxHashMap<usize, ClipRegion>,
+
+    // Маски групп. Как и у обрезки, маска действует на потомков
+    pub masks: FxHashMap<usize, ObjectMask>,
+
+    // Мировые трансформации. Считаются в prepare и используются
+    // только если есть иерархия (Смотрите objects/hierarchy.rs)
+    pub world_positions: Vec<Vec2>,
+    pub world_rotations: Vec<f32>,
+    pub world_scales: Vec<Vec2>,
+    pub world_opacities: Vec<f32>,
+    pub world_visible: Vec<bool>,
+
+    // Полная пересборка батчей. Нужна только при изменении структуры:
+    // объекты добавлены, скрыты/показаны, перемещены по z или по
+    // иерархии. Обычные свойства (Позиция, цвет и т.д.) отмечают
+    // только изменённый объект в changed
     pub dirty: bool,
 
+    // Объекты, чьи инстансы нужно переписать на своих местах в
+    // буфере. changed_marks защищает список от повторов
+    pub changed: Vec<usize>,
+    pub changed_marks: Vec<bool>,
+
     // Оптимизация: Сортировка каждую пересборку батча явлется
     // достаточно узким местом. Мы теряем ~2-3 fps при 100
     // тысячах объектов. Поэтому тут используется отдельный флаг
@@ -32,6 +85,12 @@ pub struct ObjectStore {
     pub z_dirty: bool,
 }
 
+impl Default for ObjectStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ObjectStore {
     pub fn new() -> Self {
         Self {
@@ -41,11 +100,33 @@ impl ObjectStore {
             colors: Vec::with_capacity(1024),
             rotations: Vec::with_capacity(1024),
             z_indices: Vec::with_capacity(1024),
+            scales: Vec::with_capacity(1024),
+            pivots: Vec::with_capacity(1024),
+            opacities: Vec::with_capacity(1024),
+            visible: Vec::with_capacity(1024),
             rect_ids: Vec::with_capacity(1024),
             rect_radii: Vec::with_capacity(1024),
+            effects: FxHashMap::default(),
+            effect_params: FxHashMap::default(),
+            image_ids: Vec::new(),
+            image_textures: Vec::with_capacity(1024),
+            image_samplers: FxHashMap::default(),
+            path_ids: Vec::new(),
+            path_meshes: FxHashMap::default(),
+            parents: Vec::with_capacity(1024),
+            children: FxHashMap::default(),
+            clips: FxHashMap::default(),
+            masks: FxHashMap::default(),
+            world_positions: Vec::new(),
+            world_rotations: Vec::new(),
+            world_scales: Vec::new(),
+            world_opacities: Vec::new(),
+            world_visible: Vec::new(),
 
             // Объекты изначально не грязные потому-что их нет
             dirty: false,
+            changed: Vec::new(),
+            changed_marks: Vec::with_capacity(1024),
             z_dirty: false,
         }
     }
@@ -58,7 +139,14 @@ impl ObjectStore {
         self.colors.push(Vec4::ONE); // Цвет белый (1, 1, 1, 1)
         self.rotations.push(0.0); // Вращение: 0.0 радиан
         self.z_indices.push(0.0); // Нулевой z индекс
+        self.scales.push(Vec2::ONE); // Без масштаба
+        self.pivots.push(Vec2::splat(0.5)); // Точка опоры в центре
+        self.opacities.push(1.0); // Полностью непрозрачный
+        self.visible.push(true); // Видимый
         self.rect_radii.push(Vec4::ZERO); 
+        self.image_textures.push(TextureId::default());
+        self.parents.push(None);
+        self.changed_marks.push(false);
 
         // После создания объекта нам нужно пересобрать всё, поэтому
         // делаем хранилище грязным
@@ -79,30 +167,135 @@ impl ObjectStore {
         id
     }
 
-    /// Каждая функция конфигурации должна делать хранилище объектов
-    /// грязным чтобы пересобрать всё
+    pub fn new_image(&mut self, texture: TextureId) -> ObjectId {
+        let index = self.alloc_common();
+        let id = objects::ObjectId::new(objects::ObjectType::Image, index);
+
+        self.image_textures[index] = texture;
+        self.image_ids.push(id);
+
+        id
+    }
+
+    pub fn new_path(&mut self, path: &Path, style: PathStyle) -> ObjectId {
+        let mut mesh = PathMesh::default();
+        mesh.append(path, &style);
+
+        self.new_path_mesh(mesh)
+    }
+
+    /// Создаёт объект пути из готовой сетки (Например из SVG)
+    pub fn new_path_mesh(&mut self, mesh: PathMesh) -> ObjectId {
+        let index = self.alloc_common();
+        let id = objects::ObjectId::new(objects::ObjectType::Path, index);
+
+        // По умолчанию путь рисуется в своём естественном размере
+        self.sizes[index] = mesh.natural_size;
+
+        self.path_ids.push(id);
+        self.path_meshes.insert(index, mesh);
+
+        id
+    }
+
+    /// Заменяет путь объекта и сразу тесселирует его. Размер
+    /// объекта не меняется, поэтому новый путь растягивается
+    /// под текущий размер. Путь только отмечается изменённым: батч
+    /// путей перепишет его вершины на месте (Или пересоберёт свои
+    /// буферы, если у сетки сменилось число вершин)
+    pub fn set_path(&mut self, id: ObjectId, path: &Path, style: PathStyle) {
+        if let Some(mesh) = self.path_meshes.get_mut(&id.index()) {
+            mesh.clear();
+            mesh.append(path, &style);
+            self.mark_changed(id.index());
+        }
+    }
+
+    /// Заменяет сетку пути на уже готовую (Например кадр анимации).
+    /// Как и set_path, отмечает только изменённый путь
+    pub fn set_path_mesh(&mut self, id: ObjectId, mesh: PathMesh) {
+        if let Some(current) = self.path_meshes.get_mut(&id.index()) {
+            *current = mesh;
+            self.mark_changed(id.index());
+        }
+    }
+
+    /// Отмечает объект изменённым. Его инстанс будет переписан на
+    /// том же месте в буфере без полной пересборки
+    #[inline(always)]
+    pub fn mark_changed(&mut self, index: usize) {
+        if !self.changed_marks[index] {
+            self.changed_marks[index] = true;
+            self.changed.push(index);
+        }
+    }
+
+    /// Есть ли что рисовать заново
+    #[inline]
+    pub fn has_changes(&self) -> bool {
+        self.dirty || !self.changed.is_empty()
+    }
+
+    /// Снимает все флаги после того как батчи подготовлены
+    pub fn clear_changes(&mut self) {
+        for &index in self.changed.iter() {
+            self.changed_marks[index] = false;
+        }
+
+        self.changed.clear();
+        self.dirty = false;
+        self.z_dirty = false;
+    }
+
+    /// Каждая функция конфигурации должна либо отметить объект
+    /// изменённым, либо (Если меняется порядок или состав батча)
+    /// сделать хранилище объектов грязным чтобы пересобрать всё
 
     #[inline(always)]
     pub fn config_position(&mut self, id: ObjectId, pos: Vec2) {
         self.positions[id.index()] = pos;
-        self.dirty = true;
+        self.mark_changed(id.index());
     }
 
     #[inline(always)]
     pub fn config_size(&mut self, id: ObjectId, size: Vec2) {
         self.sizes[id.index()] = size;
-        self.dirty = true;
+        self.mark_changed(id.index());
     }
 
     #[inline(always)]
     pub fn config_color(&mut self, id: ObjectId, color: Vec4) {
         self.colors[id.index()] = color;
-        self.dirty = true;
+        self.mark_changed(id.index());
     }
     
     #[inline(always)]
     pub fn config_rotation(&mut self, id: ObjectId, rad: f32) {
         self.rotations[id.index()] = rad;
+        self.mark_changed(id.index());
+    }
+
+    #[inline(always)]
+    pub fn config_scale(&mut self, id: ObjectId, scale: Vec2) {
+        self.scales[id.index()] = scale;
+        self.mark_changed(id.index());
+    }
+
+    #[inline(always)]
+    pub fn config_pivot(&mut self, id: ObjectId, pivot: Vec2) {
+        self.pivots[id.index()] = pivot;
+        self.mark_changed(id.index());
+    }
+
+    #[inline(always)]
+    pub fn config_opacity(&mut self, id: ObjectId, opacity: f32) {
+        self.opacities[id.index()] = opacity.clamp(0.0, 1.0);
+        self.mark_changed(id.index());
+    }
+
+    #[inline(always)]
+    pub fn config_visible(&mut self, id: ObjectId, visible: bool) {
+        self.visible[id.index()] = visible;
         self.dirty = true;
     }
 
@@ -116,7 +309,150 @@ impl ObjectStore {
     pub fn set_rounded(&mut self, id: ObjectId, radii: Vec4) {
         if id.index() < self.rect_radii.len() {
              self.rect_radii[id.index()] = radii;
-             self.dirty = true;
+             self.mark_changed(id.index());
+        }
+    }
+
+    pub fn config_image_sampler(&mut self, id: ObjectId, options: SamplerOptions) {
+        self.image_samplers.insert(id.index(), options);
+        self.dirty = true;
+    }
+
+    /// Объект переезжает в другой батч, поэтому нужна пересборка
+    pub fn config_effect(&mut self, id: ObjectId, effect: Option<ShaderId>) {
+        let previous = match effect {
+            Some(effect) => self.effects.insert(id.index(), effect),
+            None => self.effects.remove(&id.index()),
+        };
+
+        if previous != effect {
+            self.dirty = true;
+        }
+    }
+
+    pub fn config_effect_params(&mut self, id: ObjectId, params: [Vec4; 2]) {
+        self.effect_params.insert(id.index(), params);
+        self.mark_changed(id.index());
+    }
+
+    /// Меняет то, какие объекты обрезаются, поэтому нужна пересборка
+    pub fn config_clip(&mut self, id: ObjectId, clip: Option<ClipRegion>) {
+        let previous = match clip {
+            Some(clip) => self.clips.insert(id.index(), clip),
+            None => self.clips.remove(&id.index()),
+        };
+
+        if previous != clip {
+            self.dirty = true;
+        }
+    }
+
+    /// Объект маски сам не рисуется, поэтому он скрывается, а когда
+    /// маску снимают, снова становится видимым
+    pub fn config_mask(&mut self, group: ObjectId, mask: Option<ObjectMask>) {
+        let previous = match mask {
+            Some(mask) => self.masks.insert(group.index(), mask),
+            None => self.masks.remove(&group.index()),
+        };
+
+        if previous == mask {
+            return;
         }
+
+        if let Some(previous) = previous {
+            self.config_visible(previous.object, true);
+        }
+
+        if let Some(mask) = mask {
+            self.config_visible(mask.object, false);
+        }
+
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(side: f32) -> Path {
+        Path::new()
+            .move_to(Vec2::ZERO)
+            .line_to(Vec2::new(side, 0.0))
+            .line_to(Vec2::new(side, side))
+            .line_to(Vec2::new(0.0, side))
+            .close()
+    }
+
+    #[test]
+    fn path_changes_mark_only_the_path() {
+        let mut store = ObjectStore::new();
+        let rect = store.new_rect();
+        let path = store.new_path(&square(10.0), PathStyle::filled(Vec4::ONE));
+        store.clear_changes();
+
+        store.set_path(path, &square(20.0), PathStyle::filled(Vec4::ONE));
+        assert!(!store.dirty);
+        assert_eq!(store.changed, vec![path.index()]);
+        assert_eq!(store.path_meshes[&path.index()].natural_size, Vec2::splat(20.0));
+        store.clear_changes();
+
+        let mut mesh = PathMesh::default();
+        mesh.append(&square(5.0), &PathStyle::filled(Vec4::ONE));
+        store.set_path_mesh(path, mesh);
+        assert!(!store.dirty);
+        assert_eq!(store.changed, vec![path.index()]);
+        store.clear_changes();
+
+        // Сетку можно заменить только у пути
+        store.set_path_mesh(rect, PathMesh::default());
+        assert!(!store.has_changes());
+    }
+
+    #[test]
+    fn bulk_updates_skip_unknown_ids() {
+        let mut store = ObjectStore::new();
+        let a = store.new_rect();
+        let b = store.new_rect();
+        let missing = ObjectId::new(objects::ObjectType::Rect, 10);
+        store.clear_changes();
+
+        store.config_positions(&[a, missing, b], &[Vec2::ONE, Vec2::ONE, Vec2::splat(2.0)]);
+        assert_eq!(store.positions, vec![Vec2::ONE, Vec2::splat(2.0)]);
+        assert_eq!(store.changed, vec![a.index(), b.index()]);
+        assert!(!store.dirty);
+    }
+
+    #[test]
+    fn structural_changes_make_store_dirty() {
+        let mut store = ObjectStore::new();
+        let a = store.new_rect();
+        let b = store.new_rect();
+        store.clear_changes();
+
+        // Трансформации и цвет переписываются на месте
+        store.config_position(a, Vec2::new(10.0, 0.0));
+        store.config_color(a, Vec4::ONE);
+        store.config_opacity(b, 0.5);
+        assert!(!store.dirty);
+        assert_eq!(store.changed, vec![a.index(), b.index()]);
+        store.clear_changes();
+
+        // Видимость меняет состав батча
+        store.config_visible(a, false);
+        assert!(store.dirty);
+        store.clear_changes();
+
+        // Эффект переносит объект в батч эффектов
+        store.config_effect(b, Some(ShaderId::default()));
+        assert!(store.dirty);
+        store.clear_changes();
+
+        store.config_effect(b, Some(ShaderId::default()));
+        assert!(!store.dirty);
+
+        // Обрезка переносит объект в полупрозрачную часть
+        store.config_clip(a, Some(ClipRegion { rect: Vec4::new(0.0, 0.0, 50.0, 50.0), radii: Vec4::ZERO }));
+        assert!(store.dirty);
     }
 }
\ No newline at end of file
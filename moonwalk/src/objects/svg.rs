@@ -0,0 +1,354 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use glam::{Affine2, Vec2, Vec4};
+
+use crate::objects::ObjectId;
+use crate::objects::path::{Fill, FillRule, LineCap, LineJoin, Path, PathMesh, PathStyle, Stroke};
+
+/// Результат импорта SVG. Все пути файла собираются в один объект
+/// векторного пути, поэтому его можно двигать, масштабировать и
+/// тонировать как единое целое
+#[derive(Debug, Clone)]
+pub struct SvgImport {
+    pub id: ObjectId,
+
+    // Возможности SVG которые встретились в файле, но не
+    // поддерживаются (Фильтры, маски, текст и т.д.). Такие элементы
+    // либо пропускаются, либо рисуются без этого эффекта
+    pub unsupported: Vec<String>,
+}
+
+/// Сетка разобранного SVG файла и список неподдерживаемых возможностей
+pub struct ParsedSvg {
+    pub mesh: PathMesh,
+    pub unsupported: Vec<String>,
+}
+
+/// Разбирает SVG файл в одну сетку. Пути, базовые фигуры (usvg
+/// переводит их в пути), группы и трансформации поддерживаются
+/// полностью. Градиенты вычисляются в вершинах сетки, поэтому на
+/// больших фигурах с многими остановками возможна потеря точности
+pub fn parse_svg(data: &[u8]) -> Result<ParsedSvg, String> {
+    let mut unsupported = Vec::new();
+
+    // Текст не поддерживается (usvg собран без шрифтов и молча
+    // выкидывает его), поэтому ищем его в исходном XML заранее
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(doc) = usvg::roxmltree::Document::parse(text) {
+            if doc.descendants().any(|node| node.has_tag_name("text")) {
+                report(&mut unsupported, "text");
+            }
+        }
+    }
+
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default())
+        .map_err(|e| format!("Invalid SVG file: {}", e))?;
+
+    let mut mesh = PathMesh::default();
+    convert_group(tree.root(), 1.0, &mut mesh, &mut unsupported);
+
+    // Естественный размер это размер холста SVG, а не сетки, чтобы
+    // отступы внутри иконки сохранялись при масштабировании
+    let size = tree.size();
+    mesh.natural_size = Vec2::new(size.width(), size.height());
+
+    for feature in unsupported.iter() {
+        log::warn!("SVG feature is not supported and was ignored: {}", feature);
+    }
+
+    Ok(ParsedSvg { mesh, unsupported })
+}
+
+fn report(unsupported: &mut Vec<String>, feature: &str) {
+    if !unsupported.iter().any(|f| f == feature) {
+        unsupported.push(feature.to_string());
+    }
+}
+
+/// Обходит группу рекурсивно. Прозрачность групп умножается вниз
+/// по иерархии
+fn convert_group(group: &usvg::Group, opacity: f32, mesh: &mut PathMesh, unsupported: &mut Vec<String>) {
+    if !group.filters().is_empty() {
+        report(unsupported, "filter");
+    }
+
+    if group.mask().is_some() {
+        report(unsupported, "mask");
+    }
+
+    if group.clip_path().is_some() {
+        report(unsupported, "clip-path");
+    }
+
+    if group.blend_mode() != usvg::BlendMode::Normal {
+        report(unsupported, "mix-blend-mode");
+    }
+
+    let opacity = opacity * group.opacity().get();
+
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(child) => convert_group(child, opacity, mesh, unsupported),
+            usvg::Node::Path(path) => convert_path(path, opacity, mesh, unsupported),
+            usvg::Node::Image(_) => report(unsupported, "image"),
+            usvg::Node::Text(_) => report(unsupported, "text"),
+        }
+    }
+}
+
+/// Тесселирует путь в пространстве пользователя (Там же заданы
+/// градиенты), а затем переводит сетку через абсолютную
+/// трансформацию. Так обводка правильно растягивается вместе с путём
+fn convert_path(svg_path: &usvg::Path, opacity: f32, mesh: &mut PathMesh, unsupported: &mut Vec<String>) {
+    if !svg_path.is_visible() {
+        return;
+    }
+
+    let path = to_path(svg_path.data());
+    let transform = to_affine(svg_path.abs_transform());
+
+    // Порядок рисования в SVG это по умолчанию заливка, потом обводка
+    let fill_first = svg_path.paint_order() == usvg::PaintOrder::FillAndStroke;
+    let mut layers = [None, None];
+
+    if let Some(fill) = svg_path.fill() {
+        let rule = match fill.rule() {
+            usvg::FillRule::NonZero => FillRule::NonZero,
+            usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+        };
+
+        let style = PathStyle {
+            fill: Some(Fill { rule, color: Vec4::ONE }),
+            stroke: None,
+        };
+
+        layers[if fill_first { 0 } else { 1 }] = Some((style, fill.paint(), fill.opacity().get()));
+    }
+
+    if let Some(stroke) = svg_path.stroke() {
+        if stroke.dasharray().is_some() {
+            report(unsupported, "stroke-dasharray");
+        }
+
+        let join = match stroke.linejoin() {
+            usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => LineJoin::Miter,
+            usvg::LineJoin::Round => LineJoin::Round,
+            usvg::LineJoin::Bevel => LineJoin::Bevel,
+        };
+
+        let cap = match stroke.linecap() {
+            usvg::LineCap::Butt => LineCap::Butt,
+            usvg::LineCap::Round => LineCap::Round,
+            usvg::LineCap::Square => LineCap::Square,
+        };
+
+        let style = PathStyle::stroked(
+            Stroke::new(stroke.width().get(), Vec4::ONE)
+                .with_join(join)
+                .with_cap(cap)
+                .with_miter_limit(stroke.miterlimit().get())
+        );
+
+        layers[if fill_first { 1 } else { 0 }] = Some((style, stroke.paint(), stroke.opacity().get()));
+    }
+
+    for (style, paint, paint_opacity) in layers.into_iter().flatten() {
+        if let usvg::Paint::Pattern(_) = paint {
+            report(unsupported, "pattern");
+            continue;
+        }
+
+        let mut part = PathMesh::default();
+        part.append(&path, &style);
+
+        for vertex in part.vertices.iter_mut() {
+            let mut color = paint_color(paint, vertex.position);
+            color.w *= paint_opacity * opacity;
+            vertex.color = color;
+        }
+
+        mesh.extend(&part, transform);
+    }
+}
+
+fn to_path(data: &usvg::tiny_skia_path::Path) -> Path {
+    use usvg::tiny_skia_path::PathSegment;
+
+    let mut path = Path::new();
+
+    for segment in data.segments() {
+        path = match segment {
+            PathSegment::MoveTo(p) => path.move_to(Vec2::new(p.x, p.y)),
+            PathSegment::LineTo(p) => path.line_to(Vec2::new(p.x, p.y)),
+            PathSegment::QuadTo(c, p) => path.quad_to(Vec2::new(c.x, c.y), Vec2::new(p.x, p.y)),
+            PathSegment::CubicTo(c1, c2, p) => path.cubic_to(
+                Vec2::new(c1.x, c1.y),
+                Vec2::new(c2.x, c2.y),
+                Vec2::new(p.x, p.y),
+            ),
+            PathSegment::Close => path.close(),
+        };
+    }
+
+    path
+}
+
+fn to_affine(t: usvg::Transform) -> Affine2 {
+    Affine2::from_cols_array(&[t.sx, t.ky, t.kx, t.sy, t.tx, t.ty])
+}
+
+/// Цвет заливки в точке пространства пользователя
+fn paint_color(paint: &usvg::Paint, point: Vec2) -> Vec4 {
+    match paint {
+        usvg::Paint::Color(color) => to_color(*color, 1.0),
+
+        usvg::Paint::LinearGradient(gradient) => {
+            let p = to_affine(gradient.transform()).inverse().transform_point2(point);
+            let start = Vec2::new(gradient.x1(), gradient.y1());
+            let dir = Vec2::new(gradient.x2(), gradient.y2()) - start;
+
+            let t = if dir.length_squared() > 0.0 {
+                (p - start).dot(dir) / dir.length_squared()
+            } else {
+                0.0
+            };
+
+            sample_stops(gradient.stops(), spread(gradient.spread_method(), t))
+        },
+
+        // Фокус радиального градиента не учитывается
+        usvg::Paint::RadialGradient(gradient) => {
+            let p = to_affine(gradient.transform()).inverse().transform_point2(point);
+            let center = Vec2::new(gradient.cx(), gradient.cy());
+            let t = p.distance(center) / gradient.r().get();
+
+            sample_stops(gradient.stops(), spread(gradient.spread_method(), t))
+        },
+
+        usvg::Paint::Pattern(_) => Vec4::ZERO,
+    }
+}
+
+fn spread(method: usvg::SpreadMethod, t: f32) -> f32 {
+    match method {
+        usvg::SpreadMethod::Pad => t.clamp(0.0, 1.0),
+        usvg::SpreadMethod::Repeat => t.rem_euclid(1.0),
+        usvg::SpreadMethod::Reflect => {
+            let t = t.rem_euclid(2.0);
+            if t > 1.0 { 2.0 - t } else { t }
+        },
+    }
+}
+
+fn sample_stops(stops: &[usvg::Stop], t: f32) -> Vec4 {
+    let color = |stop: &usvg::Stop| to_color(stop.color(), stop.opacity().get());
+
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec4::ZERO,
+    };
+
+    if t <= first.offset().get() {
+        return color(first);
+    }
+
+    for pair in stops.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let (start, end) = (a.offset().get(), b.offset().get());
+
+        if t <= end {
+            let k = if end > start { (t - start) / (end - start) } else { 1.0 };
+            return color(a).lerp(color(b), k);
+        }
+    }
+
+    color(last)
+}
+
+fn to_color(color: usvg::Color, alpha: f32) -> Vec4 {
+    Vec4::new(
+        color.red as f32 / 255.0,
+        color.green as f32 / 255.0,
+        color.blue as f32 / 255.0,
+        alpha,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> ParsedSvg {
+        let svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">{}</svg>"#, body);
+        parse_svg(svg.as_bytes()).expect("Test SVG must parse")
+    }
+
+    fn bounds(mesh: &PathMesh) -> (Vec2, Vec2) {
+        mesh.vertices.iter().fold((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)), |(min, max), v| {
+            (min.min(v.position), max.max(v.position))
+        })
+    }
+
+    fn assert_bounds(mesh: &PathMesh, min: Vec2, max: Vec2) {
+        let actual = bounds(mesh);
+        assert!((actual.0 - min).length() < 1e-3 && (actual.1 - max).length() < 1e-3, "{:?} != {:?}", actual, (min, max));
+    }
+
+    #[test]
+    fn simple_path_is_filled() {
+        let svg = parse(r##"<path d="M10 10 H60 V40 H10 Z" fill="#ff0000"/>"##);
+
+        assert!(svg.unsupported.is_empty());
+        assert_eq!(svg.mesh.natural_size, Vec2::new(100.0, 50.0));
+        assert!(!svg.mesh.indices.is_empty());
+        assert_eq!(svg.mesh.indices.len() % 3, 0);
+        assert!(svg.mesh.vertices.iter().all(|v| v.color == Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        assert_bounds(&svg.mesh, Vec2::new(10.0, 10.0), Vec2::new(60.0, 40.0));
+    }
+
+    #[test]
+    fn transforms_and_opacity_are_applied() {
+        let svg = parse(r#"<g transform="translate(20 5) scale(2)" opacity="0.5">
+            <rect width="10" height="10" fill="blue" fill-opacity="0.5"/>
+        </g>"#);
+
+        assert_bounds(&svg.mesh, Vec2::new(20.0, 5.0), Vec2::new(40.0, 25.0));
+        assert!(svg.mesh.vertices.iter().all(|v| v.color == Vec4::new(0.0, 0.0, 1.0, 0.25)));
+    }
+
+    #[test]
+    fn stroke_is_drawn_over_fill() {
+        let svg = parse(r#"<rect x="10" y="10" width="20" height="20" fill="red" stroke="lime" stroke-width="4"/>"#);
+
+        // Обводка выходит за фигуру на половину ширины и идёт после заливки
+        assert_bounds(&svg.mesh, Vec2::new(8.0, 8.0), Vec2::new(32.0, 32.0));
+        assert_eq!(svg.mesh.vertices.first().map(|v| v.color), Some(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(svg.mesh.vertices.last().map(|v| v.color), Some(Vec4::new(0.0, 1.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn unsupported_features_are_reported_once() {
+        let svg = parse(r#"
+            <defs>
+                <filter id="blur"><feGaussianBlur stdDeviation="2"/></filter>
+                <mask id="mask"><rect width="50" height="50" fill="white"/></mask>
+            </defs>
+            <rect width="10" height="10" filter="url(#blur)"/>
+            <rect width="10" height="10" filter="url(#blur)"/>
+            <rect width="20" height="20" mask="url(#mask)"/>
+            <path d="M0 40 H100" stroke="black" stroke-dasharray="4 2"/>
+            <text x="0" y="20">Hello</text>
+        "#);
+
+        assert_eq!(svg.unsupported, vec!["text", "filter", "mask", "stroke-dasharray"]);
+
+        // Элементы с неподдерживаемыми эффектами рисуются без них
+        assert!(!svg.mesh.indices.is_empty());
+    }
+
+    #[test]
+    fn invalid_data_is_an_error() {
+        assert!(parse_svg(b"not an svg").is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Горячая перезагрузка шейдеров для разработки. Модуль есть только с
+// фичей hot-reload и только в отладочной сборке: стандартные шейдеры
+// читаются из папки исходников крейта, а не из include_str!, и при
+// их изменении конвейеры пересобираются на следующем кадре
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::rendering::pipeline::BUILTIN_SHADERS;
+
+/// Как часто проверять время изменения файлов. Проверка это один
+/// системный вызов на файл, но делать его каждый кадр незачем
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+fn shader_path(name: &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders")).join(name)
+}
+
+fn modified(name: &str) -> Option<SystemTime> {
+    std::fs::metadata(shader_path(name)).and_then(|meta| meta.modified()).ok()
+}
+
+/// Читает шейдер с диска. None если файла нет (Например приложение
+/// запущено не на машине разработчика), тогда берётся встроенный
+pub fn read(name: &str) -> Option<String> {
+    if !BUILTIN_SHADERS.iter().any(|shader| shader.name == name) {
+        return None;
+    }
+
+    match std::fs::read_to_string(shader_path(name)) {
+        Ok(source) => Some(source),
+        Err(e) => {
+            log::warn!("Failed to read shader {}, using the embedded one: {}", name, e);
+            None
+        },
+    }
+}
+
+/// Следит за временем изменения файлов шейдеров
+pub struct ShaderWatcher {
+    modified: Vec<Option<SystemTime>>,
+    last_poll: Instant,
+}
+
+impl ShaderWatcher {
+    pub fn new() -> Self {
+        Self {
+            modified: BUILTIN_SHADERS.iter().map(|shader| modified(shader.name)).collect(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Возвращает имена шейдеров, изменившихся с прошлой проверки
+    pub fn poll(&mut self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return changed;
+        }
+
+        self.last_poll = Instant::now();
+
+        for (shader, last) in BUILTIN_SHADERS.iter().zip(self.modified.iter_mut()) {
+            let current = modified(shader.name);
+
+            if current != *last {
+                log::info!("Shader {} changed, rebuilding pipelines", shader.name);
+                *last = current;
+                changed.push(shader.name);
+            }
+        }
+
+        changed
+    }
+}
\ No newline at end of file
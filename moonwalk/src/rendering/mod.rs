@@ -4,4 +4,8 @@
 pub mod state;
 pub mod renderer;
 pub mod vertex;
-pub mod pipeline;
\ No newline at end of file
+pub mod pipeline;
+pub mod texture;
+
+#[cfg(all(feature = "hot-reload", debug_assertions))]
+pub mod hot_reload;
\ No newline at end of file
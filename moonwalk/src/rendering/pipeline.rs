@@ -1,15 +1,310 @@
 // Часть проекта MoonWalk с открытым исходным кодом.
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use crate::easy_gpu::{Context, Pipeline, PipelineBuilder};
+use crate::easy_gpu::{Context, Mask, Pipeline, PipelineBuilder};
 use crate::objects::ShaderId;
+use crate::rendering::vertex::{EffectInstance, PathVertex, QuadVertex, RectInstance};
+use crate::batching::submiter::RenderTarget;
+use crate::batching::shapes::gpu_rect::PREPARE_SHADER;
+#[cfg(all(feature = "hot-reload", debug_assertions))]
+use crate::rendering::hot_reload;
 use crate::error::MoonWalkError;
 
+/// ID конвейеров фигур обрезки: запись значения в трафарет и
+/// пересечение с уже записанной фигурой (Смотрите Clipper). Фигуры
+/// бывают прямоугольные (Инстансы) и из путей (Треугольники)
+pub const CLIP_WRITE: ShaderId = ShaderId(4);
+pub const CLIP_INTERSECT: ShaderId = ShaderId(5);
+pub const CLIP_PATH_WRITE: ShaderId = ShaderId(6);
+pub const CLIP_PATH_INTERSECT: ShaderId = ShaderId(7);
+
+/// ID конвейера, который рисует слой группы с альфа маской
+pub const MASK_COMPOSITE: ShaderId = ShaderId(8);
+
+/// Первый ID пользовательских эффектов. Стандартные шейдеры занимают
+/// ID меньше него (Смотрите DepthMode::shader_id)
+const EFFECT_BASE: u32 = 1000;
+
+/// Лайаут статичной вершины квада
+const QUAD_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<QuadVertex>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &[
+        // @location(0) position: vec2<f32>
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 0, shader_location: 0 },
+    ],
+};
+
+/// Лайаут инстанса прямоугольника (Используется и для изображений)
+const RECT_INSTANCE_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<RectInstance>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Instance,
+    attributes: &[
+        // Pos + ыize (vec4<f32>) 16 байт
+        wgpu::VertexAttribute { 
+            format: wgpu::VertexFormat::Float32x4, 
+            offset: 0,  
+            shader_location: 1 
+        },
+
+        // Radii (vec4<f32>) 16 байт
+        // Смещение 16
+        wgpu::VertexAttribute { 
+            format: wgpu::VertexFormat::Float32x4, 
+            offset: 16, 
+            shader_location: 2 
+        },
+        
+        // Extra: Z + Rot (vec2<f32>) 8 байт
+        // Смещение 16 + 16 = 32
+        wgpu::VertexAttribute { 
+            format: wgpu::VertexFormat::Float32x2,
+            offset: 32, 
+            shader_location: 3 
+        },
+        
+        // Color (u32) 4 байта
+        // Смещение 32 + 8 = 40
+        wgpu::VertexAttribute { 
+            format: wgpu::VertexFormat::Uint32,
+            offset: 40, 
+            shader_location: 4 
+        },
+
+        // Transform: Scale + Pivot (vec4<f32>) 16 байт
+        // Смещение 40 + 4 = 44
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x4,
+            offset: 44,
+            shader_location: 5
+        },
+    ],
+};
+
+/// Лайаут инстанса с эффектом. Первые 60 байт совпадают с инстансом
+/// прямоугольника, после них идёт блок параметров объекта
+const EFFECT_INSTANCE_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<EffectInstance>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Instance,
+    attributes: &[
+        // Те же атрибуты что и в RECT_INSTANCE_LAYOUT
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 0, shader_location: 1 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 16, shader_location: 2 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 32, shader_location: 3 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Uint32, offset: 40, shader_location: 4 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 44, shader_location: 5 },
+
+        // Params0 (vec4<f32>) 16 байт
+        // Смещение 44 + 16 = 60
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x4,
+            offset: 60,
+            shader_location: 6
+        },
+
+        // Params1 (vec4<f32>) 16 байт
+        // Смещение 60 + 16 = 76
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x4,
+            offset: 76,
+            shader_location: 7
+        },
+    ],
+};
+
+/// Лайаут вершины векторного пути
+const PATH_VERTEX_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<PathVertex>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &[
+        // Position (vec2<f32>) 8 байт
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x2,
+            offset: 0,
+            shader_location: 0
+        },
+
+        // Z (f32) 4 байта
+        // Смещение 8
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32,
+            offset: 8,
+            shader_location: 1
+        },
+
+        // Color (u32) 4 байта
+        // Смещение 8 + 4 = 12
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Uint32,
+            offset: 12,
+            shader_location: 2
+        },
+    ],
+};
+
+/// Лайаут вершины текста (textware::TextVertex)
+const TEXT_VERTEX_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+    array_stride: std::mem::size_of::<crate::textware::TextVertex>() as wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode::Vertex,
+    attributes: &[
+        // Position (vec3<f32>) 12 байт
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 0, shader_location: 0 },
+
+        // Color (vec4<f32>) 16 байт
+        // Смещение 12 + 8 = 20 (Перед ним лежит uv)
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, offset: 20, shader_location: 1 },
+
+        // UV (vec2<f32>) 8 байт
+        // Смещение 12
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 12, shader_location: 2 },
+    ],
+};
+
+/// Встроенный шейдер: имя файла в shaders/ (Под ним шейдер читается
+/// с диска при горячей перезагрузке), исходник и лайауты вершинных
+/// буферов, с которыми собирается его конвейер
+pub(crate) struct BuiltinShader {
+    pub name: &'static str,
+    pub source: &'static str,
+    pub layouts: &'static [wgpu::VertexBufferLayout<'static>],
+}
+
+impl BuiltinShader {
+    /// Исходник шейдера (Смотрите shader_source)
+    pub fn load(&self) -> Cow<'static, str> {
+        shader_source(self.name, self.source)
+    }
+
+    /// Конвейер из source с лайаутами вершин этого шейдера
+    pub fn builder<'a>(&self, ctx: &'a Context, source: &'a str) -> PipelineBuilder<'a> {
+        self.layouts.iter().fold(PipelineBuilder::new(ctx, source), |builder, layout| {
+            builder.add_layout(layout.clone())
+        })
+    }
+}
+
+pub(crate) const RECT_SHADER: BuiltinShader = BuiltinShader {
+    name: "rect.wgsl",
+    source: include_str!("../shaders/rect.wgsl"),
+    layouts: &[QUAD_LAYOUT, RECT_INSTANCE_LAYOUT],
+};
+
+/// Изображение использует тот же инстанс что и прямоугольник
+pub(crate) const IMAGE_SHADER: BuiltinShader = BuiltinShader {
+    name: "image.wgsl",
+    source: include_str!("../shaders/image.wgsl"),
+    layouts: &[QUAD_LAYOUT, RECT_INSTANCE_LAYOUT],
+};
+
+pub(crate) const PATH_SHADER: BuiltinShader = BuiltinShader {
+    name: "path.wgsl",
+    source: include_str!("../shaders/path.wgsl"),
+    layouts: &[PATH_VERTEX_LAYOUT],
+};
+
+pub(crate) const CLIP_SHADER: BuiltinShader = BuiltinShader {
+    name: "clip.wgsl",
+    source: include_str!("../shaders/clip.wgsl"),
+    layouts: &[QUAD_LAYOUT, RECT_INSTANCE_LAYOUT],
+};
+
+pub(crate) const MASK_SHADER: BuiltinShader = BuiltinShader {
+    name: "mask.wgsl",
+    source: include_str!("../shaders/mask.wgsl"),
+    layouts: &[QUAD_LAYOUT, RECT_INSTANCE_LAYOUT],
+};
+
+/// Пролог эффекта: юниформы, вершинная стадия и хелперы. Код
+/// пользователя дописывается после него
+pub(crate) const EFFECT_SHADER: BuiltinShader = BuiltinShader {
+    name: "effect.wgsl",
+    source: include_str!("../shaders/effect.wgsl"),
+    layouts: &[QUAD_LAYOUT, EFFECT_INSTANCE_LAYOUT],
+};
+
+/// Копирование мип уровней. Конвейер собирает easy_gpu::mipmap со
+/// встроенным исходником, вершины строятся в шейдере
+pub(crate) const BLIT_SHADER: BuiltinShader = BuiltinShader {
+    name: "blit.wgsl",
+    source: include_str!("../shaders/blit.wgsl"),
+    layouts: &[],
+};
+
+/// Конвейера для текста пока нет, шейдер только проверяется тестами
+pub(crate) const TEXT_SHADER: BuiltinShader = BuiltinShader {
+    name: "text.wgsl",
+    source: include_str!("../shaders/text.wgsl"),
+    layouts: &[TEXT_VERTEX_LAYOUT],
+};
+
+/// Все встроенные шейдеры крейта. За ними следит горячая
+/// перезагрузка, и по ним же тесты проверяют шейдеры против
+/// лайаутов конвейеров
+#[cfg_attr(not(all(feature = "hot-reload", debug_assertions)), allow(dead_code))]
+pub(crate) const BUILTIN_SHADERS: [BuiltinShader; 9] = [
+    RECT_SHADER,
+    IMAGE_SHADER,
+    PATH_SHADER,
+    CLIP_SHADER,
+    MASK_SHADER,
+    EFFECT_SHADER,
+    BLIT_SHADER,
+    TEXT_SHADER,
+    PREPARE_SHADER,
+];
+
+/// Как стандартный конвейер работает с буфером глубины
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+    /// Прохода без буфера глубины, порядок только по сортировке
+    None,
+    /// Непрозрачные объекты: тест и запись глубины
+    Opaque,
+    /// Полупрозрачные объекты: только тест глубины (Они не должны
+    /// закрывать то, что нарисовано после них)
+    Translucent,
+}
+
+impl DepthMode {
+    /// Варианты одного шейдера хранятся под разными ID
+    fn shader_id(self, base: u32) -> ShaderId {
+        match self {
+            DepthMode::None => ShaderId(base),
+            DepthMode::Opaque => ShaderId(base + 10),
+            DepthMode::Translucent => ShaderId(base + 20),
+        }
+    }
+
+    fn apply(self, builder: PipelineBuilder<'_>) -> PipelineBuilder<'_> {
+        // При равном z выигрывает нарисованный позже, как и при
+        // сортировке
+        match self {
+            DepthMode::None => builder,
+            DepthMode::Opaque => builder
+                .with_depth(true, wgpu::CompareFunction::GreaterEqual)
+                .no_blend(),
+            DepthMode::Translucent => builder
+                .with_depth(false, wgpu::CompareFunction::GreaterEqual),
+        }
+    }
+}
+
 pub struct ShaderStore {
     pipelines: HashMap<ShaderId, Pipeline>,
     pub proj_layout: wgpu::BindGroupLayout,
+
+    // Исходники эффектов без пролога. ID эффекта это EFFECT_BASE +
+    // номер в списке. Нужны для пересборки конвейеров при смене
+    // параметров прохода или пролога (Горячая перезагрузка)
+    effects: Vec<String>,
+
+    // Идёт горячая перезагрузка: шейдер с ошибкой оставляет прежний
+    // конвейер. При смене параметров прохода прежний уже не подходит
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    pub reloading: bool,
     // pub glyph_layout: wgpu::BindGroupLayout,
 }
 
@@ -54,90 +349,154 @@ impl ShaderStore {
         Self {
             pipelines: HashMap::new(),
             proj_layout,
+            effects: Vec::new(),
+
+            #[cfg(all(feature = "hot-reload", debug_assertions))]
+            reloading: false,
             // glyph_layout,
         }
     }
 
-    pub fn create_default_rect(&mut self, ctx: &Context, format: wgpu::TextureFormat) -> Result<ShaderId, MoonWalkError> {
-        let vertex_layout = wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<crate::rendering::vertex::QuadVertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                // @location(0) position: vec2<f32>
-                wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, offset: 0, shader_location: 0 },
-            ],
-        };
+    pub fn create_default_rect(&mut self, ctx: &Context, target: &RenderTarget, depth: DepthMode) -> Result<ShaderId, MoonWalkError> {
+        let source = RECT_SHADER.load();
+        let builder = RECT_SHADER.builder(ctx, &source);
 
-        let instance_layout = wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<crate::rendering::vertex::RectInstance>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                // Pos + ыize (vec4<f32>) 16 байт
-                wgpu::VertexAttribute { 
-                    format: wgpu::VertexFormat::Float32x4, 
-                    offset: 0,  
-                    shader_location: 1 
-                },
+        let pipeline = build_default(ctx, clip_test(depth.apply(builder), target), target, &[&self.proj_layout]);
+        self.insert_default(depth.shader_id(1), pipeline)
+    }
 
-                // Radii (vec4<f32>) 16 байт
-                // Смещение 16
-                wgpu::VertexAttribute { 
-                    format: wgpu::VertexFormat::Float32x4, 
-                    offset: 16, 
-                    shader_location: 2 
-                },
-                
-                // Extra: Z + Rot (vec2<f32>) 8 байт
-                // Смещение 16 + 16 = 32
-                wgpu::VertexAttribute { 
-                    format: wgpu::VertexFormat::Float32x2,
-                    offset: 32, 
-                    shader_location: 3 
-                },
-                
-                // Color (u32) 4 байта
-                // Смещение 32 + 8 = 40
-                wgpu::VertexAttribute { 
-                    format: wgpu::VertexFormat::Uint32,
-                    offset: 40, 
-                    shader_location: 4 
-                },
-            ],
-        };
+    /// Изображение использует тот же инстанс что и прямоугольник
+    /// (Скругление работает так же), но дополнительно получает
+    /// текстуру во второй бинд группе
+    pub fn create_default_image(
+        &mut self,
+        ctx: &Context,
+        target: &RenderTarget,
+        texture_layout: &wgpu::BindGroupLayout,
+        depth: DepthMode,
+    ) -> Result<ShaderId, MoonWalkError> {
+        let source = IMAGE_SHADER.load();
+        let builder = IMAGE_SHADER.builder(ctx, &source);
 
-        let pipeline = PipelineBuilder::new(ctx, include_str!("../shaders/rect.wgsl"))
-            .add_layout(vertex_layout)
-            .add_layout(instance_layout)
-            .build(format, &[&self.proj_layout]);
+        let pipeline = build_default(ctx, clip_test(depth.apply(builder), target), target, &[&self.proj_layout, texture_layout]);
+        self.insert_default(depth.shader_id(2), pipeline)
+    }
 
-        let id = ShaderId(1);
-        self.pipelines.insert(id, pipeline);
-        Ok(id)
+    /// Векторный путь рисуется обычными треугольниками без инстансов
+    pub fn create_default_path(&mut self, ctx: &Context, target: &RenderTarget, depth: DepthMode) -> Result<ShaderId, MoonWalkError> {
+        let source = PATH_SHADER.load();
+        let builder = PATH_SHADER.builder(ctx, &source);
+
+        let pipeline = build_default(ctx, clip_test(depth.apply(builder), target), target, &[&self.proj_layout]);
+        self.insert_default(depth.shader_id(3), pipeline)
     }
 
-    pub fn compile_shader(&mut self, ctx: &Context, src: &str, format: wgpu::TextureFormat) -> Result<ShaderId, MoonWalkError> {
-        let vertex_layout = wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<[f32; 15]>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &wgpu::vertex_attr_array![
-                0 => Float32x3,
-                1 => Float32x4,
-                2 => Float32x2,
-                3 => Float32x2,
-                4 => Float32x4 
-            ],
-        };
+    /// Фигуры обрезки пишут только в трафарет. Первая фигура цепочки
+    /// записывает своё значение, каждая вложенная увеличивает его
+    /// там, где уже лежит предыдущее, так что итоговое значение
+    /// остаётся только в пересечении всех фигур
+    pub fn create_clip_shapes(&mut self, ctx: &Context, target: &RenderTarget) -> Result<(), MoonWalkError> {
+        let source = CLIP_SHADER.load();
+
+        for (id, stencil) in [(CLIP_WRITE, Mask::write()), (CLIP_INTERSECT, Mask::increment_equal())] {
+            let builder = CLIP_SHADER.builder(ctx, &source)
+                .with_color_writes(wgpu::ColorWrites::empty())
+                .with_stencil(stencil);
+
+            let pipeline = build_default(ctx, builder, target, &[&self.proj_layout]);
+            self.insert_default(id, pipeline)?;
+        }
+
+        // Путь и так закрашивает только свою форму, поэтому его
+        // шейдер подходит как есть, нужно только выключить цвет
+        let source = PATH_SHADER.load();
+
+        for (id, stencil) in [(CLIP_PATH_WRITE, Mask::write()), (CLIP_PATH_INTERSECT, Mask::increment_equal())] {
+            let builder = PATH_SHADER.builder(ctx, &source)
+                .with_color_writes(wgpu::ColorWrites::empty())
+                .with_stencil(stencil);
+
+            let pipeline = build_default(ctx, builder, target, &[&self.proj_layout]);
+            self.insert_default(id, pipeline)?;
+        }
+
+        Ok(())
+    }
 
-        let pipeline = PipelineBuilder::new(ctx, src)
-            .add_layout(vertex_layout)
-            .build(format, &[&self.proj_layout]);
-            
-        let id = ShaderId(self.pipelines.len() as u32 + 100);
+    /// Слой рисуется как обычный полупрозрачный объект: он
+    /// проверяет глубину и трафарет внешних областей обрезки
+    pub fn create_mask_composite(
+        &mut self,
+        ctx: &Context,
+        target: &RenderTarget,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(), MoonWalkError> {
+        let depth = if target.depth { DepthMode::Translucent } else { DepthMode::None };
+
+        let source = MASK_SHADER.load();
+        let builder = MASK_SHADER.builder(ctx, &source)
+            .with_blend(Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING));
+
+        let layouts = [&self.proj_layout, texture_layout, texture_layout];
+        let pipeline = build_default(ctx, clip_test(depth.apply(builder), target), target, &layouts);
+        self.insert_default(MASK_COMPOSITE, pipeline)?;
+
+        Ok(())
+    }
+
+    /// Компилирует пользовательский эффект и возвращает его ID. src
+    /// это фрагментная стадия (fs_main), она дописывается после
+    /// пролога (shaders/effect.wgsl). Ошибки компиляции возвращаются
+    /// как ShaderCompilation, а не паникой wgpu
+    pub fn compile_effect(&mut self, ctx: &Context, src: &str, target: &RenderTarget) -> Result<ShaderId, MoonWalkError> {
+        let pipeline = build_effect(ctx, &effect_source(src), target, &self.proj_layout)?;
+
+        let id = ShaderId(EFFECT_BASE + self.effects.len() as u32);
         self.pipelines.insert(id, pipeline);
-        
+        self.effects.push(src.to_string());
+
         Ok(id)
     }
 
+    /// Пересобирает конвейеры всех эффектов под новые параметры
+    /// прохода (И с новым прологом при горячей перезагрузке). ID
+    /// эффектов не меняются. Эффект с ошибкой не мешает собрать
+    /// остальные, возвращается первая ошибка
+    pub fn rebuild_effects(&mut self, ctx: &Context, target: &RenderTarget) -> Result<(), MoonWalkError> {
+        let mut result = Ok(());
+
+        for i in 0..self.effects.len() {
+            let pipeline = build_effect(ctx, &effect_source(&self.effects[i]), target, &self.proj_layout);
+
+            if let Err(e) = self.insert_default(ShaderId(EFFECT_BASE + i as u32), pipeline) {
+                result = result.and(Err(e));
+            }
+        }
+
+        result
+    }
+
+    /// Кладёт стандартный конвейер (Или уже скомпилированный эффект)
+    /// под его ID. При горячей перезагрузке ошибка в шейдере не
+    /// роняет приложение: она пишется в лог, а остаётся последний
+    /// рабочий конвейер
+    fn insert_default(&mut self, id: ShaderId, pipeline: Result<Pipeline, MoonWalkError>) -> Result<ShaderId, MoonWalkError> {
+        match pipeline {
+            Ok(pipeline) => {
+                self.pipelines.insert(id, pipeline);
+                Ok(id)
+            },
+
+            #[cfg(all(feature = "hot-reload", debug_assertions))]
+            Err(e) if self.reloading && self.pipelines.contains_key(&id) => {
+                log::error!("Shader reload failed, keeping the previous pipeline: {}", e);
+                Ok(id)
+            },
+
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn get_pipeline(&self, id: ShaderId) -> Option<&Pipeline> {
         self.pipelines.get(&id)
     }
@@ -152,4 +511,248 @@ impl ShaderStore {
             label: Some("Projection Bind Group"),
         })
     }
+}
+
+/// Собирает конвейер эффекта. В режиме буфера глубины эффект всегда
+/// считается полупрозрачным
+fn build_effect(
+    ctx: &Context,
+    source: &str,
+    target: &RenderTarget,
+    proj_layout: &wgpu::BindGroupLayout,
+) -> Result<Pipeline, MoonWalkError> {
+    let depth = if target.depth { DepthMode::Translucent } else { DepthMode::None };
+
+    let builder = EFFECT_SHADER.builder(ctx, source);
+
+    build_checked(ctx, clip_test(depth.apply(builder), target), target, &[proj_layout])
+}
+
+/// Пролог эффекта (Тоже перезагружается с диска) и код пользователя
+fn effect_source(src: &str) -> String {
+    format!("{}\n{}", EFFECT_SHADER.load(), src)
+}
+
+/// В проходе с трафаретом обрезки конвейер содержимого проверяет
+/// трафарет. С опорным значением 0 тест проходит везде, поэтому
+/// необрезанные объекты рисуются как обычно
+fn clip_test<'a>(builder: PipelineBuilder<'a>, target: &RenderTarget) -> PipelineBuilder<'a> {
+    if target.stencil {
+        builder.with_stencil(Mask::read_at_least())
+    } else {
+        builder
+    }
+}
+
+/// Исходник стандартного шейдера. С фичей hot-reload (Только в
+/// отладочной сборке) он читается с диска, чтобы правки применялись
+/// без пересборки крейта
+#[cfg_attr(not(all(feature = "hot-reload", debug_assertions)), allow(unused_variables))]
+pub(crate) fn shader_source(name: &str, embedded: &'static str) -> Cow<'static, str> {
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    if let Some(source) = hot_reload::read(name) {
+        return Cow::Owned(source);
+    }
+
+    Cow::Borrowed(embedded)
+}
+
+/// Собирает стандартный конвейер под проход. Стандартные шейдеры
+/// встроены в крейт, поэтому их ошибки ловятся только когда они
+/// читаются с диска
+#[cfg_attr(not(all(feature = "hot-reload", debug_assertions)), allow(unused_variables))]
+fn build_default(
+    ctx: &Context,
+    builder: PipelineBuilder<'_>,
+    target: &RenderTarget,
+    layouts: &[&wgpu::BindGroupLayout],
+) -> Result<Pipeline, MoonWalkError> {
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    return build_checked(ctx, builder, target, layouts);
+
+    #[cfg(not(all(feature = "hot-reload", debug_assertions)))]
+    Ok(builder.with_samples(target.samples).build(target.format, layouts))
+}
+
+/// Собирает конвейер под проход и возвращает ошибки шейдера как
+/// ShaderCompilation
+fn build_checked(
+    ctx: &Context,
+    builder: PipelineBuilder<'_>,
+    target: &RenderTarget,
+    layouts: &[&wgpu::BindGroupLayout],
+) -> Result<Pipeline, MoonWalkError> {
+    // Ошибки шейдера ловим в свой скоуп, иначе wgpu отдаст их в
+    // обработчик по умолчанию, который паникует
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let pipeline = builder.with_samples(target.samples).build(target.format, layouts);
+
+    match pollster::block_on(ctx.device.pop_error_scope()) {
+        Some(error) => Err(MoonWalkError::ShaderCompilation(error.to_string())),
+        None => Ok(pipeline),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batching::shapes::gpu_rect::PREPARE_ENTRIES;
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    /// Пример пользовательского эффекта, чтобы проверить пролог
+    /// вместе с фрагментной стадией
+    const SAMPLE_EFFECT: &str = r#"
+        @fragment
+        fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+            let k = sin(in.uv.x * 20.0 + in.params0.x) * 0.5 + 0.5;
+            return vec4<f32>(in.color.rgb * k * in.params1.rgb, in.color.a * shape_alpha(in));
+        }
+    "#;
+
+    /// Исходник, который проверяет тест. Пролог эффекта без кода
+    /// пользователя не собирается, к нему добавляется пример
+    fn test_source(shader: &BuiltinShader) -> String {
+        if shader.name == EFFECT_SHADER.name {
+            format!("{}\n{}", shader.source, SAMPLE_EFFECT)
+        } else {
+            shader.source.to_string()
+        }
+    }
+
+    /// Разбирает и проверяет шейдер так же, как это делает wgpu
+    fn validate(name: &str, source: &str) -> naga::Module {
+        let module = match naga::front::wgsl::parse_str(source) {
+            Ok(module) => module,
+            Err(e) => panic!("{} failed to parse:\n{}", name, e.emit_to_string(source)),
+        };
+
+        let mut validator = Validator::new(ValidationFlags::all(), Capabilities::default());
+        if let Err(e) = validator.validate(&module) {
+            panic!("{} failed validation:\n{}", name, e.emit_to_string(source));
+        }
+
+        module
+    }
+
+    fn entry_point<'m>(module: &'m naga::Module, shader: &str, name: &str, stage: naga::ShaderStage) -> &'m naga::EntryPoint {
+        match module.entry_points.iter().find(|ep| ep.name == name) {
+            Some(ep) if ep.stage == stage => ep,
+            Some(ep) => panic!("{}: entry point {} is {:?}, expected {:?}", shader, name, ep.stage, stage),
+            None => panic!("{}: entry point {} ({:?}) is missing", shader, name, stage),
+        }
+    }
+
+    /// Входы вершинной стадии: location и тип. Входы бывают как
+    /// отдельными аргументами, так и полями структуры
+    fn vertex_inputs(module: &naga::Module, ep: &naga::EntryPoint) -> Vec<(u32, naga::TypeInner)> {
+        let mut inputs = Vec::new();
+
+        for arg in ep.function.arguments.iter() {
+            match &arg.binding {
+                Some(naga::Binding::Location { location, .. }) => {
+                    inputs.push((*location, module.types[arg.ty].inner.clone()));
+                },
+                Some(naga::Binding::BuiltIn(_)) => {},
+                None => {
+                    if let naga::TypeInner::Struct { members, .. } = &module.types[arg.ty].inner {
+                        for member in members.iter() {
+                            if let Some(naga::Binding::Location { location, .. }) = &member.binding {
+                                inputs.push((*location, module.types[member.ty].inner.clone()));
+                            }
+                        }
+                    }
+                },
+            }
+        }
+
+        inputs
+    }
+
+    /// Тип в шейдере, который ожидает формат атрибута
+    fn format_type(format: wgpu::VertexFormat) -> naga::TypeInner {
+        let vector = |size, scalar| naga::TypeInner::Vector { size, scalar };
+
+        match format {
+            wgpu::VertexFormat::Float32 => naga::TypeInner::Scalar(naga::Scalar::F32),
+            wgpu::VertexFormat::Float32x2 => vector(naga::VectorSize::Bi, naga::Scalar::F32),
+            wgpu::VertexFormat::Float32x3 => vector(naga::VectorSize::Tri, naga::Scalar::F32),
+            wgpu::VertexFormat::Float32x4 => vector(naga::VectorSize::Quad, naga::Scalar::F32),
+            wgpu::VertexFormat::Uint32 => naga::TypeInner::Scalar(naga::Scalar::U32),
+            other => panic!("Vertex format {:?} is not covered by the test", other),
+        }
+    }
+
+    #[test]
+    fn render_shaders_match_pipeline_layouts() {
+        // Шейдеры рисования собираются через PipelineBuilder, у compute
+        // шейдера подготовки своя проверка
+        for shader in BUILTIN_SHADERS.iter().filter(|shader| shader.name != PREPARE_SHADER.name) {
+            let name = shader.name;
+            let module = validate(name, &test_source(shader));
+
+            // PipelineBuilder всегда берёт эти точки входа
+            let vertex = entry_point(&module, name, "vs_main", naga::ShaderStage::Vertex);
+            entry_point(&module, name, "fs_main", naga::ShaderStage::Fragment);
+
+            let inputs = vertex_inputs(&module, vertex);
+            let mut provided = Vec::new();
+
+            for layout in shader.layouts.iter() {
+                for attr in layout.attributes.iter() {
+                    assert!(
+                        attr.offset + attr.format.size() <= layout.array_stride,
+                        "{}: attribute at location {} is outside of the {} byte stride",
+                        name, attr.shader_location, layout.array_stride,
+                    );
+
+                    assert!(
+                        !provided.contains(&attr.shader_location),
+                        "{}: location {} is provided by several attributes",
+                        name, attr.shader_location,
+                    );
+                    provided.push(attr.shader_location);
+
+                    match inputs.iter().find(|(location, _)| *location == attr.shader_location) {
+                        Some((_, ty)) => assert_eq!(
+                            *ty, format_type(attr.format),
+                            "{}: location {} has a different type in the shader than {:?}",
+                            name, attr.shader_location, attr.format,
+                        ),
+                        None => panic!("{}: layout provides location {} the shader doesn't read", name, attr.shader_location),
+                    }
+                }
+            }
+
+            // Вход без атрибута в лайауте wgpu не примет
+            for (location, _) in inputs.iter() {
+                assert!(provided.contains(location), "{}: location {} has no vertex attribute", name, location);
+            }
+        }
+    }
+
+    #[test]
+    fn compute_shaders_validate() {
+        let module = validate(PREPARE_SHADER.name, PREPARE_SHADER.source);
+
+        for entry in PREPARE_ENTRIES {
+            entry_point(&module, PREPARE_SHADER.name, entry, naga::ShaderStage::Compute);
+        }
+    }
+
+    #[test]
+    fn every_bundled_shader_is_checked() {
+        let checked: Vec<&str> = BUILTIN_SHADERS.iter().map(|shader| shader.name).collect();
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders");
+        let entries = std::fs::read_dir(dir).expect("Failed to read the shaders directory");
+
+        for entry in entries {
+            let path = entry.expect("Failed to read a shaders directory entry").path();
+
+            if path.extension().is_some_and(|ext| ext == "wgsl") {
+                let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                assert!(checked.contains(&name), "{} is not covered by the shader tests", name);
+            }
+        }
+    }
 }
\ No newline at end of file
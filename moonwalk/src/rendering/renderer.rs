@@ -5,10 +5,16 @@ use std::sync::Arc;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use glam::{Vec2, Vec4};
 
-use crate::easy_gpu::Context;
+use crate::easy_gpu::{Context, SamplerOptions};
 use crate::error::MoonWalkError;
 use crate::rendering::state::RenderState;
-use crate::objects::ObjectId;
+use crate::batching::submiter::Submiter;
+use crate::objects::{ClipRegion, MaskMode, ObjectId, ObjectMask, ShaderId, TextureId};
+use crate::objects::store::ObjectStore;
+use crate::objects::path::{Path, PathStyle};
+use crate::objects::svg::{self, SvgImport};
+use crate::lottie::{self, LottiePlayer};
+use crate::lottie::model::Animation;
 
 /// Структура рендерера. Она хранит контекст (easy_gpu -> wgpu)
 /// и состояние рендера (матричный стэк, храниоище объектов и так далее)
@@ -68,6 +74,7 @@ impl MoonRenderer {
             let logical_h = height as f32 / self.scale_factor;
 
             self.state.update_projection(&self.context, logical_w, logical_h);
+            self.state.resize_targets(&self.context);
         }
     }
 
@@ -120,6 +127,10 @@ impl MoonRenderer {
         // Подменяем поверхность в контексте
         self.context.surface = Some(std::sync::Arc::new(new_surface));
         self.context.config = config;
+
+        // Цели прохода (MSAA и глубина) должны совпадать по размеру
+        // с новой поверхностью
+        self.state.resize_targets(&self.context);
     }
 
     #[inline]
@@ -127,6 +138,51 @@ impl MoonRenderer {
         self.state.store.new_rect()
     }
 
+    #[inline]
+    pub fn new_image(&mut self, texture: TextureId) -> ObjectId {
+        self.state.store.new_image(texture)
+    }
+
+    #[inline]
+    pub fn new_path(&mut self, path: &Path, style: PathStyle) -> ObjectId {
+        self.state.store.new_path(path, style)
+    }
+
+    pub fn set_path(&mut self, id: ObjectId, path: &Path, style: PathStyle) {
+        self.state.store.set_path(id, path, style);
+    }
+
+    pub fn load_svg(&mut self, data: &[u8]) -> Result<SvgImport, MoonWalkError> {
+        let parsed = svg::parse_svg(data).map_err(MoonWalkError::SvgLoading)?;
+        let id = self.state.store.new_path_mesh(parsed.mesh);
+
+        Ok(SvgImport { id, unsupported: parsed.unsupported })
+    }
+
+    pub fn load_lottie(&mut self, data: &[u8]) -> Result<LottiePlayer, MoonWalkError> {
+        let animation = Animation::parse(data).map_err(MoonWalkError::LottieLoading)?;
+        let mesh = lottie::render::render_frame(&animation, animation.in_point);
+        let id = self.state.store.new_path_mesh(mesh);
+
+        Ok(LottiePlayer::new(animation, id))
+    }
+
+    pub fn update_lottie(&mut self, player: &mut LottiePlayer, dt: f32) {
+        player.update(&mut self.state.store, dt);
+    }
+
+    pub fn load_texture(&mut self, data: &[u8], width: u32, height: u32) -> Result<TextureId, MoonWalkError> {
+        self.state.textures.load_rgba(&self.context, data, width, height)
+    }
+
+    pub fn load_ktx2(&mut self, data: &[u8]) -> Result<TextureId, MoonWalkError> {
+        self.state.textures.load_ktx2(&self.context, data)
+    }
+
+    pub fn set_texture_sampler(&mut self, texture: TextureId, options: SamplerOptions) {
+        self.state.textures.set_sampler(&self.context, texture, options);
+    }
+
     #[inline]
     pub fn config_position(&mut self, id: ObjectId, pos: Vec2) {
         self.state.store.config_position(id, pos);
@@ -147,6 +203,26 @@ impl MoonRenderer {
         self.state.store.config_rotation(id, radians);
     }
 
+    #[inline]
+    pub fn config_scale(&mut self, id: ObjectId, scale: Vec2) {
+        self.state.store.config_scale(id, scale);
+    }
+
+    #[inline]
+    pub fn config_pivot(&mut self, id: ObjectId, pivot: Vec2) {
+        self.state.store.config_pivot(id, pivot);
+    }
+
+    #[inline]
+    pub fn config_opacity(&mut self, id: ObjectId, opacity: f32) {
+        self.state.store.config_opacity(id, opacity);
+    }
+
+    #[inline]
+    pub fn config_visible(&mut self, id: ObjectId, visible: bool) {
+        self.state.store.config_visible(id, visible);
+    }
+
     #[inline]
     pub fn set_z_index(&mut self, id: ObjectId, z: f32) {
         self.state.store.config_z_index(id, z);
@@ -157,4 +233,96 @@ impl MoonRenderer {
     pub fn set_rounded(&mut self, id: ObjectId, radii: Vec4) {
         self.state.store.set_rounded(id, radii);
     }
+
+    // Специфично для изображения
+    pub fn set_parent(&mut self, child: ObjectId, parent: ObjectId) -> bool {
+        self.state.store.set_parent(child, parent)
+    }
+
+    pub fn clear_parent(&mut self, child: ObjectId) {
+        self.state.store.clear_parent(child);
+    }
+
+    #[inline]
+    pub fn get_parent(&self, child: ObjectId) -> Option<ObjectId> {
+        self.state.store.get_parent(child)
+    }
+
+    #[inline]
+    pub fn set_clip(&mut self, container: ObjectId, rect: Vec4, radii: Vec4) {
+        self.state.store.config_clip(container, Some(ClipRegion { rect, radii }));
+    }
+
+    #[inline]
+    pub fn clear_clip(&mut self, container: ObjectId) {
+        self.state.store.config_clip(container, None);
+    }
+
+    #[inline]
+    pub fn set_mask(&mut self, group: ObjectId, mask: ObjectId, mode: MaskMode) {
+        self.state.store.config_mask(group, Some(ObjectMask { object: mask, mode }));
+    }
+
+    #[inline]
+    pub fn clear_mask(&mut self, group: ObjectId) {
+        self.state.store.config_mask(group, None);
+    }
+
+    #[inline]
+    pub fn set_depth_ordering(&mut self, enabled: bool) -> Result<(), MoonWalkError> {
+        self.state.set_depth_ordering(&self.context, enabled)
+    }
+
+    #[inline]
+    pub fn set_msaa(&mut self, samples: u32) -> Result<(), MoonWalkError> {
+        self.state.set_msaa(&self.context, samples)
+    }
+
+    #[inline]
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.state.culler.set_enabled(enabled);
+    }
+
+    #[inline]
+    pub fn set_gpu_prepare(&mut self, enabled: bool) -> Result<(), MoonWalkError> {
+        self.state.set_gpu_prepare(&self.context, enabled)
+    }
+
+    #[inline]
+    pub fn add_submiter(&mut self, submiter: Box<dyn Submiter>) -> Result<(), MoonWalkError> {
+        self.state.add_submiter(&self.context, submiter)
+    }
+
+    /// Хранилище объектов для чтения свойств
+    #[inline]
+    pub fn store(&self) -> &ObjectStore {
+        &self.state.store
+    }
+
+    /// Хранилище объектов для массовых изменений. Функции хранилища
+    /// сами помечают его грязным
+    #[inline]
+    pub fn store_mut(&mut self) -> &mut ObjectStore {
+        &mut self.state.store
+    }
+
+    pub fn compile_effect(&mut self, src: &str) -> Result<ShaderId, MoonWalkError> {
+        self.state.shaders.compile_effect(&self.context, src, &self.state.target)
+    }
+
+    // Специфично для прямоугольника с эффектом
+    #[inline]
+    pub fn set_effect(&mut self, id: ObjectId, effect: Option<ShaderId>) {
+        self.state.store.config_effect(id, effect);
+    }
+
+    #[inline]
+    pub fn set_effect_params(&mut self, id: ObjectId, params: [Vec4; 2]) {
+        self.state.store.config_effect_params(id, params);
+    }
+
+    #[inline]
+    pub fn set_image_sampler(&mut self, id: ObjectId, options: SamplerOptions) {
+        self.state.store.config_image_sampler(id, options);
+    }
 }
\ No newline at end of file
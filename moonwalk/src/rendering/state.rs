@@ -2,13 +2,21 @@
 // Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
 
 use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
 
-use crate::easy_gpu::{Context, Buffer, MatrixStack, RenderPass};
-use crate::batching::group::BatchGroup;
+use crate::easy_gpu::{Context, Buffer, ComputePipeline, MatrixStack, RenderPass, Texture, DEPTH_FORMAT};
+use crate::batching::group::{self, BatchGroup};
+use crate::batching::shapes::rect::RectBatch;
+use crate::batching::shapes::gpu_rect::GpuRectBatch;
+use crate::batching::submiter::{Frame, RenderTarget, Resources, Submiter};
+use crate::batching::culling::Culler;
+use crate::batching::clipping::{ClipCursor, Clipper, NO_CLIP};
 use crate::rendering::pipeline::ShaderStore;
+use crate::rendering::texture::TextureStore;
 use crate::objects::store::ObjectStore;
-use crate::objects::ShaderId;
 use crate::error::MoonWalkError;
+#[cfg(all(feature = "hot-reload", debug_assertions))]
+use crate::rendering::hot_reload::ShaderWatcher;
 
 /// Структура для единой юниформы под все шейдеры. Не передаём
 /// матрицу модели для экономии передачи данных через шину.
@@ -24,10 +32,19 @@ pub struct RenderState {
     pub store: ObjectStore, // Хранилище объектов
     pub batches: BatchGroup, // Группа батчинга
     pub shaders: ShaderStore, // Хранилище шейдеров
+    pub textures: TextureStore, // Хранилище текстур
     pub matrix_stack: MatrixStack, // Матричный стэк
     pub uniform_buffer: Buffer<GlobalUniform>, // Буфер дла передачи данных в шейдер
     pub proj_bind_group: wgpu::BindGroup,
-    pub rect_shader: ShaderId, // Пайплайн для прямоугольника
+    pub target: RenderTarget, // Параметры прохода, под которые собраны конвейеры
+    pub depth_view: Option<wgpu::TextureView>, // Буфер глубины и трафарета (Выключен по умолчанию)
+    pub msaa_view: Option<wgpu::TextureView>, // Мультисэмпловый цвет (Только с MSAA)
+    pub gpu_prepare: bool, // Прямоугольники собирает compute шейдер
+    pub culler: Culler, // Отсечение объектов за экраном (Выключено по умолчанию)
+    pub clipper: Clipper, // Обрезка и маски потомков контейнеров
+
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    pub shader_watcher: ShaderWatcher, // Следит за шейдерами на диске
 }
 
 impl RenderState {
@@ -40,8 +57,25 @@ impl RenderState {
         // конвейер для рендеринга.
         let mut shaders = ShaderStore::new(ctx);
 
-        // Создаём шейдер для прямоугольника.
-        let rect_shader = shaders.create_default_rect(ctx, ctx.config.format)?;
+        // Создаём хранилище текстур (Изображениям нужен лайаут бинд
+        // группы текстуры)
+        let textures = TextureStore::new(ctx);
+
+        // Встроенные батчи (Прямоугольники, изображения, пути) сами
+        // собирают свои конвейеры под проход
+        let target = RenderTarget {
+            format: ctx.config.format,
+            depth: false,
+            samples: 1,
+            stencil: false,
+        };
+
+        let mut batches = BatchGroup::new(ctx);
+        batches.configure(ctx, &mut shaders, &textures, &target)?;
+
+        let mut clipper = Clipper::new(ctx);
+        clipper.configure(ctx, &mut shaders, &textures, &target)?;
+        clipper.set_viewport(Vec2::new(width as f32, height as f32));
         
         // Создаём матричный стэк
         let mut matrix_stack = MatrixStack::new();
@@ -50,6 +84,10 @@ impl RenderState {
         // и высоты окна
         matrix_stack.set_ortho(width as f32, height as f32);
         
+        // Отсечение смотрит на тот же прямоугольник что и проекция
+        let mut culler = Culler::new();
+        culler.set_viewport(Vec2::ZERO, Vec2::new(width as f32, height as f32));
+
         // Создаём глобальные данные для передачи в шейдеры
         let uniform_data = GlobalUniform {
             view_proj: matrix_stack.projection.to_cols_array_2d(),
@@ -61,67 +99,351 @@ impl RenderState {
 
         Ok(Self {
             store: ObjectStore::new(),
-            batches: BatchGroup::new(ctx),
+            batches,
             shaders,
+            textures,
             matrix_stack,
             uniform_buffer,
             proj_bind_group,
-            rect_shader,
+            target,
+            depth_view: None,
+            msaa_view: None,
+            gpu_prepare: false,
+            culler,
+            clipper,
+
+            #[cfg(all(feature = "hot-reload", debug_assertions))]
+            shader_watcher: ShaderWatcher::new(),
         })
     }
 
+    /// Включает или выключает режим буфера глубины. Все батчи
+    /// пересобирают конвейеры под проход с буфером глубины
+    pub fn set_depth_ordering(&mut self, ctx: &Context, enabled: bool) -> Result<(), MoonWalkError> {
+        if enabled == self.target.depth {
+            return Ok(());
+        }
+
+        let target = RenderTarget { depth: enabled, ..self.target };
+        self.reconfigure(ctx, target)?;
+        self.update_depth_target(ctx);
+
+        // Без буфера глубины z не попадает в глубину и не отсекается
+        self.matrix_stack.set_depth(enabled);
+        self.upload_projection(ctx);
+
+        // Объекты перераспределяются между непрозрачной и
+        // полупрозрачной частью батчей
+        self.store.dirty = true;
+
+        Ok(())
+    }
+
+    /// Включает MSAA с заданным количеством сэмплов (1 выключает).
+    /// Все батчи пересобирают конвейеры под новое количество, а цели
+    /// прохода пересоздаются. Если какой-то конвейер не собрался,
+    /// остаются прежние сэмплы, конвейеры и цели
+    pub fn set_msaa(&mut self, ctx: &Context, samples: u32) -> Result<(), MoonWalkError> {
+        if samples == self.target.samples {
+            return Ok(());
+        }
+
+        if !matches!(samples, 1 | 2 | 4 | 8) {
+            return Err(MoonWalkError::Unsupported(format!("{}x MSAA, use 1, 2, 4 or 8 samples", samples)));
+        }
+
+        // Количество сэмплов должны поддерживать и цвет, и глубина
+        let supported = [self.target.format, DEPTH_FORMAT].iter().all(|&format| {
+            ctx.adapter.get_texture_format_features(format).flags.sample_count_supported(samples)
+        });
+
+        if !supported {
+            return Err(MoonWalkError::Unsupported(format!("{}x MSAA", samples)));
+        }
+
+        let target = RenderTarget { samples, ..self.target };
+        self.reconfigure(ctx, target)?;
+        self.resize_targets(ctx);
+
+        Ok(())
+    }
+
+    /// Пересобирает конвейеры батчей и обрезки под текущие параметры
+    /// прохода
+    fn configure(&mut self, ctx: &Context) -> Result<(), MoonWalkError> {
+        self.batches.configure(ctx, &mut self.shaders, &self.textures, &self.target)?;
+        self.clipper.configure(ctx, &mut self.shaders, &self.textures, &self.target)
+    }
+
+    /// Пересобирает конвейеры под новые параметры прохода. Если
+    /// какой-то конвейер не собрался, возвращает прежние параметры и
+    /// конвейеры под них, чтобы все конвейеры совпадали с проходом
+    fn reconfigure(&mut self, ctx: &Context, target: RenderTarget) -> Result<(), MoonWalkError> {
+        let previous = std::mem::replace(&mut self.target, target);
+
+        if let Err(e) = self.configure(ctx) {
+            self.target = previous;
+
+            if let Err(e) = self.configure(ctx) {
+                log::error!("Failed to restore pipelines: {}", e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Включает трафарет обрезки, когда у какой-то области появилась
+    /// фигура, и выключает, когда фигур не осталось. Без фигур
+    /// конвейеры собираются без теста трафарета. Если конвейеры не
+    /// собрались, трафарет остаётся прежним
+    fn update_stencil(&mut self, ctx: &Context) {
+        let needed = self.clipper.needs_stencil();
+
+        if needed == self.target.stencil {
+            return;
+        }
+
+        let target = RenderTarget { stencil: needed, ..self.target };
+
+        if let Err(e) = self.reconfigure(ctx, target) {
+            log::error!("Failed to rebuild pipelines for clipping: {}", e);
+            return;
+        }
+
+        self.update_depth_target(ctx);
+    }
+
+    /// Буфер глубины и трафарета нужен, если включена глубина или
+    /// обрезка фигурами
+    fn update_depth_target(&mut self, ctx: &Context) {
+        let needed = self.target.depth || self.target.stencil;
+
+        if needed != self.depth_view.is_some() {
+            self.depth_view = needed.then(|| {
+                Texture::create_depth_target(ctx, ctx.config.width, ctx.config.height, self.target.samples)
+            });
+        }
+    }
+
+    /// Включает или выключает подготовку прямоугольников на GPU.
+    /// Возвращает ошибку если устройство не умеет compute шейдеры
+    pub fn set_gpu_prepare(&mut self, ctx: &Context, enabled: bool) -> Result<(), MoonWalkError> {
+        if enabled == self.gpu_prepare {
+            return Ok(());
+        }
+
+        if enabled && !ComputePipeline::supported(ctx) {
+            return Err(MoonWalkError::Unsupported("compute shaders".to_string()));
+        }
+
+        let mut rects: Box<dyn Submiter> = if enabled {
+            Box::new(GpuRectBatch::new(ctx))
+        } else {
+            Box::new(RectBatch::new(ctx))
+        };
+
+        rects.configure(ctx, &mut self.shaders, &self.textures, &self.target)?;
+        self.batches.replace(group::RECTS, rects);
+        self.gpu_prepare = enabled;
+
+        // Новый батч собирается с нуля
+        self.store.dirty = true;
+
+        Ok(())
+    }
+
+    /// Добавляет пользовательский батч в конец реестра
+    pub fn add_submiter(&mut self, ctx: &Context, mut submiter: Box<dyn Submiter>) -> Result<(), MoonWalkError> {
+        submiter.configure(ctx, &mut self.shaders, &self.textures, &self.target)?;
+        self.batches.submiters.push(submiter);
+
+        // Общий порядок нужно собрать заново уже с новым батчем
+        self.store.dirty = true;
+
+        Ok(())
+    }
+
+    /// Пересоздаёт буфер глубины и цель MSAA под текущий размер
+    /// холста и количество сэмплов. Вызывается при изменении размера,
+    /// пересоздании поверхности и смене MSAA
+    pub fn resize_targets(&mut self, ctx: &Context) {
+        let (width, height) = (ctx.config.width, ctx.config.height);
+        let samples = self.target.samples;
+
+        if let Some(view) = &mut self.depth_view {
+            *view = Texture::create_depth_target(ctx, width, height, samples);
+        }
+
+        self.msaa_view = if samples > 1 {
+            Some(Texture::create_msaa_target(ctx, width, height, self.target.format, samples))
+        } else {
+            None
+        };
+    }
+
     /// Функция для обновления матрицы проекции. Вызывается при изменении размера
     /// окна через вьюпорт функцию из renderer (А она вызывается из публичного API)
     pub fn update_projection(&mut self, ctx: &Context, width: f32, height: f32) {
         self.matrix_stack.set_ortho(width, height);
-        
+        self.culler.set_viewport(Vec2::ZERO, Vec2::new(width, height));
+        self.clipper.set_viewport(Vec2::new(width, height));
+        self.upload_projection(ctx);
+    }
+
+    fn upload_projection(&mut self, ctx: &Context) {
         let uniform_data = GlobalUniform {
             view_proj: self.matrix_stack.projection.to_cols_array_2d(),
         };
-        
+
         self.uniform_buffer.update_one(ctx, &uniform_data);
     }
 
+    /// Пересобирает конвейеры батчей и обрезки, чьи шейдеры на диске
+    /// изменились. Шейдер с ошибкой оставляет прежний конвейер и не
+    /// мешает пересобрать остальные
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    fn reload_shaders(&mut self, ctx: &Context) {
+        let changed = self.shader_watcher.poll();
+
+        if changed.is_empty() {
+            return;
+        }
+
+        self.shaders.reloading = true;
+        self.batches.reload(ctx, &mut self.shaders, &self.textures, &self.target, &changed);
+
+        if self.clipper.shader_files().iter().any(|file| changed.contains(file)) {
+            if let Err(e) = self.clipper.configure(ctx, &mut self.shaders, &self.textures, &self.target) {
+                log::error!("Shader reload failed: {}", e);
+            }
+        }
+
+        self.shaders.reloading = false;
+    }
+
     /// Функция для рисования всех объектов
     pub fn draw(&mut self, ctx: &Context, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        #[cfg(all(feature = "hot-reload", debug_assertions))]
+        self.reload_shaders(ctx);
+
+        // Пересчитываем мировые трансформации иерархии объектов
+        if self.store.has_changes() {
+            self.store.update_world_transforms(&mut self.matrix_stack);
+        }
+
+        // Объект зашедший на экран или ушедший с него меняет состав
+        // батчей, поэтому тогда нужна полная пересборка
+        if self.culler.update(&self.store) {
+            self.store.dirty = true;
+        }
+
+        // Области обрезки и маски считаются по мировым трансформациям.
+        // Первая фигура в трафарете включает его
+        self.clipper.update(ctx, &self.store, &self.textures);
+        self.update_stencil(ctx);
+
         // Подготавливаем батчи
-        self.batches.rects.prepare(ctx, &self.store);
+        self.batches.prepare(&Frame {
+            ctx,
+            store: &self.store,
+            textures: &self.textures,
+            culler: &self.culler,
+            clipper: &self.clipper,
+            depth: self.target.depth,
+        });
         
-        // Если объекты грязные (dirty) - снимаем флаг 
-        // (так как изменения уже отрисованы)
-        if self.store.dirty {
-            self.store.dirty = false;
+        // Снимаем флаги грязности и список изменённых объектов
+        // (так как изменения уже отрисованы, а в prepare мы уже
+        // всё отсортировали)
+        self.store.clear_changes();
+
+        // Compute проходы (Например сборка инстансов на GPU) до
+        // того как их результат прочитает проход рендера
+        for submiter in self.batches.submiters.iter() {
+            submiter.dispatch(encoder);
         }
 
-        // Для Z индексом тоже снимаем флаг если он стоит
-        // так как в prepare мы уже всё отсортировали 
-        if self.store.z_dirty {
-            self.store.z_dirty = false;
+        let res = Resources {
+            shaders: &self.shaders,
+            textures: &self.textures,
+            depth: self.target.depth,
+            stencil: self.target.stencil,
+        };
+
+        let surface = (ctx.config.width, ctx.config.height);
+
+        // Группы с альфа маской рисуются в свои слои до основного
+        // прохода. Буфер глубины и MSAA цель общие, основной проход
+        // потом очистит их заново
+        for (layer, view) in self.clipper.layers() {
+            let clear = Some(wgpu::Color::TRANSPARENT);
+
+            let mut pass = match &self.msaa_view {
+                Some(msaa_view) => RenderPass::multisampled(encoder, msaa_view, view, clear, self.depth_view.as_ref()),
+                None => RenderPass::with_depth(encoder, view, clear, self.depth_view.as_ref()),
+            };
+
+            pass.set_bind_group(0, &self.proj_bind_group);
+            self.draw_runs(&mut pass, res, surface, layer);
         }
 
         // Создаём проход рендера
-        let mut pass = RenderPass::new(
-            encoder,
-            target,
-            
-            // Цвет заливки
-            Some(wgpu::Color {
-                r: 0.1,
-                g: 0.1,
-                b: 0.1,
-                a: 1.0
-            })
-        );
+        // Цвет заливки
+        let clear = Some(wgpu::Color {
+            r: 0.1,
+            g: 0.1,
+            b: 0.1,
+            a: 1.0
+        });
+
+        // С MSAA рисуем в мультисэмпловый цвет, который в конце
+        // прохода сводится в текстуру поверхности
+        let mut pass = match &self.msaa_view {
+            Some(msaa_view) => RenderPass::multisampled(encoder, msaa_view, target, clear, self.depth_view.as_ref()),
+            None => RenderPass::with_depth(encoder, target, clear, self.depth_view.as_ref()),
+        };
 
         pass.set_bind_group(0, &self.proj_bind_group);
 
-        // Проверяем конвейер рендера (Хардкод для прямоугольников)
-        if let Some(pipeline) = self.shaders.get_pipeline(self.rect_shader) {
-            // Устаналиваем пайплайн
-            pass.set_pipeline(pipeline);
-            
-            // Отрисовываем прямоугольники
-            self.batches.rects.render(&mut pass);
+        // В режиме глубины сначала рисуем все непрозрачные объекты,
+        // порядок между ними решает тест глубины
+        if self.target.depth {
+            for submiter in self.batches.submiters.iter() {
+                submiter.render_opaque(&mut pass, res);
+            }
+        }
+
+        self.draw_runs(&mut pass, res, surface, NO_CLIP);
+    }
+
+    /// Рисует отрезки общего порядка по z, которые принадлежат слою
+    /// layer (NO_CLIP это основной проход). Каждый батч сам
+    /// выставляет свой конвейер, а обрезка меняется между отрезками.
+    /// В основном проходе вместо содержимого группы с альфа маской
+    /// рисуется её слой, на месте первого отрезка группы
+    fn draw_runs<'a>(&'a self, pass: &mut RenderPass<'a>, res: Resources<'a>, surface: (u32, u32), layer: u32) {
+        let mut cursor = ClipCursor::new();
+        let mut composited: Vec<u32> = Vec::new();
+
+        for run in self.batches.order.runs.iter() {
+            let run_layer = self.clipper.layer_of(run.clip);
+
+            if run_layer != layer {
+                if layer == NO_CLIP && !composited.contains(&run_layer) {
+                    composited.push(run_layer);
+                    self.clipper.composite(pass, res, &mut cursor, run_layer, surface);
+                }
+
+                continue;
+            }
+
+            self.clipper.apply(pass, res, &mut cursor, run.clip, surface);
+
+            if let Some(submiter) = self.batches.submiters.get(run.batch) {
+                submiter.render_range(pass, res, run.start, run.count);
+            }
         }
     }
 }
\ No newline at end of file
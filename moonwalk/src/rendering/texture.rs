@@ -0,0 +1,171 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+use crate::easy_gpu::{self, Context, MipmapGenerator, SamplerOptions, Texture};
+use crate::objects::TextureId;
+use crate::error::MoonWalkError;
+
+/// Хранилище текстур. Хранит сами текстуры, их настройки сэмплера
+/// и готовые бинд группы. Бинд группа по умолчанию использует сэмплер
+/// текстуры, а группы для изображений с собственным сэмплером
+/// создаются тут же, но хранит их батч изображений
+pub struct TextureStore {
+    textures: Vec<Texture>,
+    options: Vec<SamplerOptions>,
+
+    // Текстуры без прозрачных пикселей. Их изображения можно
+    // рисовать как непрозрачные объекты с буфером глубины
+    opaque: Vec<bool>,
+    default_groups: Vec<wgpu::BindGroup>,
+    mipmaps: MipmapGenerator,
+    pub layout: wgpu::BindGroupLayout,
+}
+
+impl TextureStore {
+    pub fn new(ctx: &Context) -> Self {
+        let layout = ctx.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Texture Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        Self {
+            textures: Vec::new(),
+            options: Vec::new(),
+            opaque: Vec::new(),
+            default_groups: Vec::new(),
+            mipmaps: MipmapGenerator::new(ctx),
+            layout,
+        }
+    }
+
+    /// Загружает RGBA8 (sRGB) текстуру и сразу генерирует для неё мипмапы
+    pub fn load_rgba(
+        &mut self,
+        ctx: &Context,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<TextureId, MoonWalkError> {
+        let options = SamplerOptions::default();
+
+        let texture = Texture::from_bytes(
+            ctx, data, width, height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            &options,
+            Some(&mut self.mipmaps),
+        ).map_err(MoonWalkError::TextureLoading)?;
+
+        let id = self.insert(ctx, texture, options);
+        self.opaque[id.0 as usize] = data.chunks_exact(4).all(|pixel| pixel[3] == 255);
+
+        Ok(id)
+    }
+
+    /// Загружает текстуру из KTX2 файла. Сжатые форматы загружаются
+    /// на GPU как есть, если устройство их поддерживает, иначе
+    /// распаковываются на процессоре
+    pub fn load_ktx2(&mut self, ctx: &Context, data: &[u8]) -> Result<TextureId, MoonWalkError> {
+        let options = SamplerOptions::default();
+
+        let texture = easy_gpu::load_ktx2(ctx, data, &options, &mut self.mipmaps)
+            .map_err(MoonWalkError::TextureLoading)?;
+
+        Ok(self.insert(ctx, texture, options))
+    }
+
+    /// Добавляет готовую текстуру в хранилище
+    pub fn insert(&mut self, ctx: &Context, texture: Texture, options: SamplerOptions) -> TextureId {
+        let id = TextureId(self.textures.len() as u32);
+        let group = self.create_group(ctx, &texture.view, &texture.sampler);
+
+        self.textures.push(texture);
+        self.options.push(options);
+        self.opaque.push(false);
+        self.default_groups.push(group);
+
+        id
+    }
+
+    /// Нет ли в текстуре прозрачных пикселей. Для сжатых текстур
+    /// это неизвестно, поэтому они всегда считаются прозрачными
+    pub fn is_opaque(&self, id: TextureId) -> bool {
+        self.opaque.get(id.0 as usize).copied().unwrap_or(false)
+    }
+
+    /// Меняет сэмплер текстуры по умолчанию
+    pub fn set_sampler(&mut self, ctx: &Context, id: TextureId, options: SamplerOptions) {
+        let index = id.0 as usize;
+        if index >= self.textures.len() {
+            return;
+        }
+
+        self.textures[index].set_sampler(ctx, &options);
+        self.options[index] = options;
+
+        let texture = &self.textures[index];
+        self.default_groups[index] = self.create_group(ctx, &texture.view, &texture.sampler);
+    }
+
+    /// Создаёт бинд группу текстуры с другим сэмплером. Хранит её
+    /// тот, кто рисует (Батч изображений), а не хранилище
+    pub fn create_sampler_group(&self, ctx: &Context, id: TextureId, options: SamplerOptions) -> Option<wgpu::BindGroup> {
+        let texture = self.textures.get(id.0 as usize)?;
+        let sampler = options.create_sampler(ctx);
+
+        Some(self.create_group(ctx, &texture.view, &sampler))
+    }
+
+    /// Создаёт бинд группу для текстуры вне хранилища (Например
+    /// для слоя, в который рисует проход)
+    pub fn create_target_group(&self, ctx: &Context, texture: &Texture) -> wgpu::BindGroup {
+        self.create_group(ctx, &texture.view, &texture.sampler)
+    }
+
+    /// Возвращает бинд группу текстуры с её сэмплером по умолчанию
+    pub fn get_group(&self, id: TextureId) -> Option<&wgpu::BindGroup> {
+        self.default_groups.get(id.0 as usize)
+    }
+
+    fn create_group(&self, ctx: &Context, view: &wgpu::TextureView, sampler: &wgpu::Sampler) -> wgpu::BindGroup {
+        Self::create_group_with(ctx, &self.layout, view, sampler)
+    }
+
+    fn create_group_with(
+        ctx: &Context,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+}
\ No newline at end of file
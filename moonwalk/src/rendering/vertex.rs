@@ -3,8 +3,6 @@
 
 use bytemuck::{Pod, Zeroable};
 
-use crate::batching::common::SortableInstance; 
-
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct QuadVertex {
@@ -30,13 +28,16 @@ impl QuadVertex {
 /// 2: radii (tl, tr, br, bl) (Верх-лево, верх-право, низ-право, низ-лево)
 /// 3: extra (z, rotation)
 /// 4: color (r, g, b, a) (красный, зелёный, синий и альфв канал)
+/// 5: transform (scale x, scale y, pivot x, pivot y) (Масштаб и точка
+///    опоры в долях размера)
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct RectInstance {
-    pub pos_size: [f32; 4],
-    pub radii:    [f32; 4],
-    pub extra:    [f32; 2],
-    pub color:    u32,
+    pub pos_size:  [f32; 4],
+    pub radii:     [f32; 4],
+    pub extra:     [f32; 2],
+    pub color:     u32,
+    pub transform: [f32; 4],
 }
 
 impl RectInstance {
@@ -54,8 +55,26 @@ impl RectInstance {
     }
 }
 
-impl SortableInstance for RectInstance {
-    fn get_z_index(&self) -> f32 {
-        self.extra[0]
-    }
+/// Инстанс объекта с пользовательским эффектом. Это инстанс
+/// прямоугольника и блок параметров объекта после него:
+/// 6: params0 (Первые 4 параметра)
+/// 7: params1 (Следующие 4 параметра)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct EffectInstance {
+    pub rect: RectInstance,
+    pub params: [[f32; 4]; 2],
+}
+
+/// Вершина векторного пути. Путь тесселируется на процессоре, поэтому
+/// вершины уже в мировых координатах. Лайаут:
+/// 1: position (x, y)
+/// 2: z (z индекс объекта)
+/// 3: color (r, g, b, a) упакованный так же как у прямоугольника
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct PathVertex {
+    pub position: [f32; 2],
+    pub z: f32,
+    pub color: u32,
 }
\ No newline at end of file
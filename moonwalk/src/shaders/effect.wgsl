@@ -0,0 +1,108 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Пролог пользовательского эффекта. Вершинная стадия такая же как у
+// прямоугольника, а фрагментную (fs_main) пишет пользователь, его код
+// дописывается в конец этого файла
+
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> ubo: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(1) pos_size: vec4<f32>,
+    @location(2) radii: vec4<f32>,
+    @location(3) extra: vec2<f32>,
+    @location(4) color_packed: u32,
+    @location(5) transform: vec4<f32>,
+    @location(6) params0: vec4<f32>,
+    @location(7) params1: vec4<f32>,
+};
+
+// Всё что получает fs_main:
+//     color - цвет объекта с учётом прозрачности
+//     uv - координаты внутри объекта от 0 до 1
+//     local_pos - то же самое в пикселях
+//     size - размер объекта в пикселях
+//     radii - радиусы скругления (Смотрите shape_alpha)
+//     params0, params1 - параметры объекта (set_effect_params)
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) local_pos: vec2<f32>,
+    @location(3) size: vec2<f32>,
+    @location(4) radii: vec4<f32>,
+    @location(5) params0: vec4<f32>,
+    @location(6) params1: vec4<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let pos = instance.pos_size.xy;
+    let size = instance.pos_size.zw;
+    let z_index = instance.extra.x;
+    let rotation = instance.extra.y;
+    let scale = instance.transform.xy;
+
+    let pivot = size * instance.transform.zw;
+    let local_unrotated = ((in.position * size) - pivot) * scale;
+
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let rotated_x = local_unrotated.x * c - local_unrotated.y * s;
+    let rotated_y = local_unrotated.x * s + local_unrotated.y * c;
+
+    let final_x = rotated_x + pivot.x + pos.x;
+    let final_y = rotated_y + pivot.y + pos.y;
+
+    out.clip_position = ubo.view_proj * vec4<f32>(final_x, final_y, z_index, 1.0);
+    out.color = unpack4x8unorm(instance.color_packed);
+    out.uv = in.position;
+    out.local_pos = in.position * size;
+    out.size = size;
+    out.radii = instance.radii;
+    out.params0 = instance.params0;
+    out.params1 = instance.params1;
+
+    return out;
+}
+
+fn sd_rounded_box(p: vec2<f32>, b: vec2<f32>, r: vec4<f32>) -> f32 {
+    var radius = r.x; // TL
+    if (p.x > 0.0) {
+        if (p.y > 0.0) {
+            radius = r.z;
+        } else {
+            radius = r.y;
+        }
+    } else {
+        if (p.y > 0.0) {
+            radius = r.w;
+        }
+    }
+
+    let q = abs(p) - b + radius;
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0))) - radius;
+}
+
+// Сглаженная альфа скруглённой формы объекта (Как у прямоугольника).
+// Умножьте на неё альфу результата, чтобы эффект учитывал set_rounded
+fn shape_alpha(in: VertexOutput) -> f32 {
+    let half_size = in.size * 0.5;
+    let p = in.local_pos - half_size;
+
+    let min_half = min(half_size.x, half_size.y);
+    let r = min(in.radii, vec4<f32>(min_half));
+
+    let dist = sd_rounded_box(p, half_size, r);
+
+    return 1.0 - smoothstep(-0.5, 0.5, dist / length(vec2<f32>(dpdx(dist), dpdy(dist))));
+}
\ No newline at end of file
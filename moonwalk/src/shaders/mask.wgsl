@@ -0,0 +1,76 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Слой группы с альфа маской. Квад рисуется по изображению маски
+// (Вершинная стадия как у прямоугольника), а фрагмент берёт пиксель
+// слоя под собой и умножает его на альфу текстуры маски. Цвет слоя
+// уже умножен на альфу (Слой рисуется на прозрачном фоне), поэтому
+// смешивание тоже premultiplied
+
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> ubo: Uniforms;
+
+@group(1) @binding(0) var t_mask: texture_2d<f32>;
+@group(1) @binding(1) var s_mask: sampler;
+
+@group(2) @binding(0) var t_layer: texture_2d<f32>;
+@group(2) @binding(1) var s_layer: sampler;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(1) pos_size: vec4<f32>,
+    @location(2) radii: vec4<f32>,
+    @location(3) extra: vec2<f32>,
+    @location(4) color_packed: u32,
+    @location(5) transform: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) opacity: f32,
+};
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let pos = instance.pos_size.xy;
+    let size = instance.pos_size.zw;
+    let z_index = instance.extra.x;
+    let rotation = instance.extra.y;
+    let scale = instance.transform.xy;
+
+    let pivot = size * instance.transform.zw;
+    let local_unrotated = ((in.position * size) - pivot) * scale;
+
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let rotated_x = local_unrotated.x * c - local_unrotated.y * s;
+    let rotated_y = local_unrotated.x * s + local_unrotated.y * c;
+
+    let final_x = rotated_x + pivot.x + pos.x;
+    let final_y = rotated_y + pivot.y + pos.y;
+
+    out.clip_position = ubo.view_proj * vec4<f32>(final_x, final_y, z_index, 1.0);
+    out.uv = in.position;
+    out.opacity = unpack4x8unorm(instance.color_packed).a;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Слой размером с цель прохода, поэтому его координаты это
+    // позиция фрагмента на экране
+    let layer_uv = in.clip_position.xy / vec2<f32>(textureDimensions(t_layer));
+    let content = textureSample(t_layer, s_layer, layer_uv);
+    let mask = textureSample(t_mask, s_mask, in.uv).a;
+
+    return content * mask * in.opacity;
+}
\ No newline at end of file
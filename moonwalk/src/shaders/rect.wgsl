@@ -15,6 +15,7 @@ struct InstanceInput {
     @location(2) radii: vec4<f32>,
     @location(3) extra: vec2<f32>,
     @location(4) color_packed: u32,
+    @location(5) transform: vec4<f32>,
 };
 
 struct VertexOutput {
@@ -33,17 +34,20 @@ fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
     let size = instance.pos_size.zw;
     let z_index = instance.extra.x;
     let rotation = instance.extra.y;
+    let scale = instance.transform.xy;
 
-    let center_offset = size * 0.5;
-    let local_unrotated = (in.position * size) - center_offset;
+    // Точка опоры в пикселях (По умолчанию центр). Масштаб и
+    // вращение происходят вокруг неё
+    let pivot = size * instance.transform.zw;
+    let local_unrotated = ((in.position * size) - pivot) * scale;
 
     let c = cos(rotation);
     let s = sin(rotation);
     let rotated_x = local_unrotated.x * c - local_unrotated.y * s;
     let rotated_y = local_unrotated.x * s + local_unrotated.y * c;
     
-    let final_x = rotated_x + center_offset.x + pos.x;
-    let final_y = rotated_y + center_offset.y + pos.y;
+    let final_x = rotated_x + pivot.x + pos.x;
+    let final_y = rotated_y + pivot.y + pos.y;
 
     out.clip_position = ubo.view_proj * vec4<f32>(final_x, final_y, z_index, 1.0);
     out.color = unpack4x8unorm(instance.color_packed);
@@ -0,0 +1,254 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Подготовка инстансов прямоугольников на GPU. Массивы хранилища
+// объектов лежат в буферах секциями по capacity элементов (Каждая
+// секция это отдельный SoA массив), а order это индексы объектов
+// в порядке отрисовки. Видимые объекты сжимаются без пропусков
+// внутри своего отрезка общего порядка (ranges), порядок между ними
+// сохраняется. Работа идёт в три прохода:
+//     cs_cull - отмечает видимые объекты и считает их в каждой группе
+//     cs_scan - превращает счётчики групп в смещения (Одна группа)
+//     cs_main - пишет инстансы на сжатые места и количество
+//               инстансов каждого отрезка для косвенной отрисовки
+
+struct Params {
+    count: u32,
+    capacity: u32,
+    cull: u32,
+    runs: u32,
+    view_min: vec2<f32>,
+    view_max: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+
+// Позиции | размеры | масштабы | точки опоры
+@group(0) @binding(1) var<storage, read> vec2s: array<vec2<f32>>;
+
+// Цвета | скругления
+@group(0) @binding(2) var<storage, read> vec4s: array<vec4<f32>>;
+
+// Вращения | z индексы | прозрачность | видимость (0 или 1)
+@group(0) @binding(3) var<storage, read> scalars: array<f32>;
+
+@group(0) @binding(4) var<storage, read> order: array<u32>;
+
+// RectInstance по 15 слов (60 байт), как в вершинном лайауте
+@group(0) @binding(5) var<storage, read_write> instances: array<u32>;
+
+// Отметки видимости (capacity) | смещения групп (Группа на каждые
+// WORKGROUP объектов и ещё одна для общего количества)
+@group(0) @binding(6) var<storage, read_write> scan: array<u32>;
+
+// Начало и количество элементов каждого отрезка батча
+@group(0) @binding(7) var<storage, read> ranges: array<vec2<u32>>;
+
+// DrawIndexedIndirectArgs каждого отрезка (По 5 слов)
+@group(0) @binding(8) var<storage, read_write> indirect: array<u32>;
+
+const WORKGROUP: u32 = 64u;
+const INSTANCE_WORDS: u32 = 15u;
+
+var<workgroup> partial: array<u32, WORKGROUP>;
+
+fn rotate(v: vec2<f32>, c: f32, s: f32) -> vec2<f32> {
+    return vec2<f32>(v.x * c - v.y * s, v.x * s + v.y * c);
+}
+
+// Рамка с учётом вращения и масштаба вокруг точки опоры, так же
+// как в вершинном шейдере
+fn is_visible(idx: u32) -> bool {
+    let cap = params.capacity;
+
+    if (scalars[3u * cap + idx] == 0.0) {
+        return false;
+    }
+
+    if (params.cull == 0u) {
+        return true;
+    }
+
+    let pos = vec2s[idx];
+    let size = vec2s[cap + idx];
+    let scale = vec2s[2u * cap + idx];
+    let pivot = size * vec2s[3u * cap + idx];
+    let rotation = scalars[idx];
+    let c = cos(rotation);
+    let s = sin(rotation);
+
+    let a = rotate((vec2<f32>(0.0, 0.0) - pivot) * scale, c, s);
+    let b = rotate((vec2<f32>(size.x, 0.0) - pivot) * scale, c, s);
+    let d = rotate((vec2<f32>(0.0, size.y) - pivot) * scale, c, s);
+    let e = rotate((size - pivot) * scale, c, s);
+
+    let lo = min(min(a, b), min(d, e)) + pivot + pos;
+    let hi = max(max(a, b), max(d, e)) + pivot + pos;
+
+    return all(lo <= params.view_max) && all(hi >= params.view_min);
+}
+
+// Включающая сумма значений потоков группы. Вызывается всеми
+// потоками группы, после неё partial[WORKGROUP - 1] это сумма группы
+fn workgroup_scan(value: u32, lid: u32) -> u32 {
+    partial[lid] = value;
+    workgroupBarrier();
+
+    for (var stride = 1u; stride < WORKGROUP; stride *= 2u) {
+        var sum = partial[lid];
+        if (lid >= stride) {
+            sum += partial[lid - stride];
+        }
+
+        workgroupBarrier();
+        partial[lid] = sum;
+        workgroupBarrier();
+    }
+
+    return partial[lid];
+}
+
+// Сколько видимых объектов стоит в порядке раньше позиции position
+fn prefix_at(position: u32) -> u32 {
+    let group = position / WORKGROUP;
+    var sum = scan[params.capacity + group];
+
+    for (var k = group * WORKGROUP; k < position; k++) {
+        sum += scan[k];
+    }
+
+    return sum;
+}
+
+// Отрезок, в котором лежит позиция. Отрезки идут подряд по
+// возрастанию начала и покрывают весь порядок
+fn find_run(position: u32) -> u32 {
+    var lo = 0u;
+    var hi = params.runs;
+
+    while (hi - lo > 1u) {
+        let mid = (lo + hi) / 2u;
+        if (ranges[mid].x <= position) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    return lo;
+}
+
+@compute @workgroup_size(64)
+fn cs_cull(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(local_invocation_id) lid: vec3<u32>,
+    @builtin(workgroup_id) wid: vec3<u32>,
+) {
+    let i = gid.x;
+
+    var mark = 0u;
+    if (i < params.count) {
+        mark = u32(is_visible(order[i]));
+        scan[i] = mark;
+    }
+
+    let total = workgroup_scan(mark, lid.x);
+    if (lid.x == WORKGROUP - 1u) {
+        scan[params.capacity + wid.x] = total;
+    }
+}
+
+@compute @workgroup_size(64)
+fn cs_scan(@builtin(local_invocation_id) lid: vec3<u32>) {
+    let groups = (params.count + WORKGROUP - 1u) / WORKGROUP;
+    let sums = params.capacity;
+    var carry = 0u;
+
+    // Последний элемент (groups) становится общим количеством
+    for (var base = 0u; base <= groups; base += WORKGROUP) {
+        let k = base + lid.x;
+
+        var value = 0u;
+        if (k < groups) {
+            value = scan[sums + k];
+        }
+
+        let inclusive = workgroup_scan(value, lid.x);
+        if (k <= groups) {
+            scan[sums + k] = carry + inclusive - value;
+        }
+
+        carry += partial[WORKGROUP - 1u];
+        workgroupBarrier();
+    }
+}
+
+@compute @workgroup_size(64)
+fn cs_main(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(local_invocation_id) lid: vec3<u32>,
+    @builtin(workgroup_id) wid: vec3<u32>,
+) {
+    let i = gid.x;
+
+    var mark = 0u;
+    if (i < params.count) {
+        mark = scan[i];
+    }
+
+    let inclusive = workgroup_scan(mark, lid.x);
+    if (i >= params.count) {
+        return;
+    }
+
+    let run = find_run(i);
+    let range = ranges[run];
+    let first = prefix_at(range.x);
+
+    // Первый объект отрезка пишет его параметры отрисовки. Инстансы
+    // отрезка начинаются с range.x, поэтому first_instance это 0
+    if (i == range.x) {
+        let args = run * 5u;
+        indirect[args + 0u] = 6u;
+        indirect[args + 1u] = prefix_at(range.x + range.y) - first;
+        indirect[args + 2u] = 0u;
+        indirect[args + 3u] = 0u;
+        indirect[args + 4u] = 0u;
+    }
+
+    if (mark == 0u) {
+        return;
+    }
+
+    let idx = order[i];
+    let cap = params.capacity;
+
+    let pos = vec2s[idx];
+    let size = vec2s[cap + idx];
+    let scale = vec2s[2u * cap + idx];
+    let pivot_n = vec2s[3u * cap + idx];
+    let color = vec4s[idx];
+    let radii = vec4s[cap + idx];
+    let rotation = scalars[idx];
+    let z_index = scalars[cap + idx];
+    let opacity = scalars[2u * cap + idx];
+
+    let slot = range.x + scan[cap + wid.x] + inclusive - mark - first;
+    let base = slot * INSTANCE_WORDS;
+
+    instances[base + 0u] = bitcast<u32>(pos.x);
+    instances[base + 1u] = bitcast<u32>(pos.y);
+    instances[base + 2u] = bitcast<u32>(size.x);
+    instances[base + 3u] = bitcast<u32>(size.y);
+    instances[base + 4u] = bitcast<u32>(radii.x);
+    instances[base + 5u] = bitcast<u32>(radii.y);
+    instances[base + 6u] = bitcast<u32>(radii.z);
+    instances[base + 7u] = bitcast<u32>(radii.w);
+    instances[base + 8u] = bitcast<u32>(z_index);
+    instances[base + 9u] = bitcast<u32>(rotation);
+    instances[base + 10u] = pack4x8unorm(vec4<f32>(color.rgb, color.a * opacity));
+    instances[base + 11u] = bitcast<u32>(scale.x);
+    instances[base + 12u] = bitcast<u32>(scale.y);
+    instances[base + 13u] = bitcast<u32>(pivot_n.x);
+    instances[base + 14u] = bitcast<u32>(pivot_n.y);
+}
\ No newline at end of file
@@ -17,7 +17,7 @@ struct VertexOutput {
 var<uniform> projection: mat4x4<f32>;
 
 @vertex
-fn vs_text_main(model: VertexInput) -> VertexOutput {
+fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.clip_position = projection * vec4<f32>(model.position, 1.0);
     out.color = model.color;
@@ -31,7 +31,7 @@ var t_diffuse: texture_2d<f32>;
 var s_diffuse: sampler;
 
 @fragment
-fn fs_text_main(in: VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let alpha = textureSample(t_diffuse, s_diffuse, in.tex_coords).r;
     if (alpha < 0.01) {
         discard;
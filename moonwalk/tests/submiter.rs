@@ -0,0 +1,101 @@
+// Часть проекта MoonWalk с открытым исходным кодом.
+// Лицензия EPL 2.0, подробнее в файле LICENSE. Copyright (c) 2025 MoonWalk
+
+// Пользовательский батч снаружи движка. Проверяет, что через
+// публичный интерфейс крейта можно реализовать Submiter и добавить
+// его в рендер (MoonWalk::add_submiter)
+
+use glam::Vec2;
+use moonwalk::error::MoonWalkError;
+use moonwalk::{
+    Clipper, Context, Culler, DrawItems, Frame, MoonWalk, ObjectStore, RenderPass,
+    RenderTarget, Resources, ShaderStore, Submiter, TextureStore,
+};
+
+/// Батч, который рисует отметки поверх видимых прямоугольников
+#[derive(Default)]
+struct Markers {
+    samples: u32,
+    order: Vec<usize>,
+}
+
+impl Markers {
+    /// Собирает видимые прямоугольники. Возвращает true если их
+    /// состав изменился
+    fn collect(&mut self, store: &ObjectStore, culler: &Culler, clipper: Option<&Clipper>) -> bool {
+        let order: Vec<usize> = store.rect_ids.iter()
+            .map(|id| id.index())
+            .filter(|&index| store.visible[index] && culler.in_view(index))
+            .filter(|&index| clipper.is_none_or(|clipper| !clipper.is_clipped(index)))
+            .collect();
+
+        let changed = order != self.order;
+        self.order = order;
+        changed
+    }
+}
+
+impl Submiter for Markers {
+    fn configure(
+        &mut self,
+        _ctx: &Context,
+        _shaders: &mut ShaderStore,
+        _textures: &TextureStore,
+        target: &RenderTarget,
+    ) -> Result<(), MoonWalkError> {
+        self.samples = target.samples;
+        Ok(())
+    }
+
+    fn prepare(&mut self, frame: &Frame) -> bool {
+        self.collect(frame.store, frame.culler, Some(frame.clipper))
+    }
+
+    fn items(&self) -> DrawItems<'_> {
+        DrawItems::Objects(&self.order)
+    }
+
+    fn render_range<'a>(&'a self, _pass: &mut RenderPass<'a>, _res: Resources<'a>, _start: u32, _count: u32) {}
+}
+
+/// Регистрация батча. Сам MoonWalk без окна не создать, поэтому
+/// тест проверяет, что вызов собирается
+fn register(moonwalk: &mut MoonWalk) -> Result<(), MoonWalkError> {
+    moonwalk.add_submiter(Box::new(Markers::default()))
+}
+
+#[test]
+fn external_submiter_can_be_registered() {
+    let _register: fn(&mut MoonWalk) -> Result<(), MoonWalkError> = register;
+
+    let submiter: Box<dyn Submiter> = Box::new(Markers::default());
+    match submiter.items() {
+        DrawItems::Objects(items) => assert!(items.is_empty()),
+        DrawItems::Layer { .. } => panic!("markers are store objects"),
+    }
+}
+
+#[test]
+fn external_submiter_reads_the_store() {
+    let mut store = ObjectStore::new();
+    let shown = store.new_rect();
+    let hidden = store.new_rect();
+    let moved = store.new_rect();
+
+    store.config_size(shown, Vec2::new(10.0, 10.0));
+    store.config_visible(hidden, false);
+    store.config_size(moved, Vec2::new(10.0, 10.0));
+    store.config_position(moved, Vec2::new(500.0, 500.0));
+
+    let mut culler = Culler::new();
+    culler.set_enabled(true);
+    culler.set_viewport(Vec2::ZERO, Vec2::new(100.0, 100.0));
+    culler.update(&store);
+
+    let mut markers = Markers::default();
+    assert!(markers.collect(&store, &culler, None));
+    assert_eq!(markers.order, vec![shown.index()]);
+
+    // Без изменений состав тот же
+    assert!(!markers.collect(&store, &culler, None));
+}
\ No newline at end of file